@@ -0,0 +1,201 @@
+use std::collections::BTreeSet;
+
+use meilisearch_tokenizer::TokenizerOptions;
+use once_cell::sync::Lazy;
+use rust_stemmers::{Algorithm, Stemmer};
+use serde::{Deserialize, Serialize};
+
+use crate::database::MainT;
+use crate::{store, MResult};
+
+pub use meilisearch_schema::Language;
+
+static ENGLISH_STEMMER: Lazy<Stemmer> = Lazy::new(|| Stemmer::create(Algorithm::English));
+static FRENCH_STEMMER: Lazy<Stemmer> = Lazy::new(|| Stemmer::create(Algorithm::French));
+
+static ENGLISH_STOP_WORDS: &str = include_str!("../../misc/en.stopwords.txt");
+static FRENCH_STOP_WORDS: &str = include_str!("../../misc/fr.stopwords.txt");
+
+/// Returns the stop words of a language embedded in the binary,
+/// these are the lists of the `misc` folder, one word by line.
+///
+/// There is a list for each of the analyzed languages, English and French,
+/// the presets of other languages require a list and a `Language` variant.
+pub fn stop_words_preset(language: Language) -> impl Iterator<Item = &'static str> {
+    let text = match language {
+        Language::English => ENGLISH_STOP_WORDS,
+        Language::French => FRENCH_STOP_WORDS,
+    };
+
+    text.lines().map(str::trim).filter(|word| !word.is_empty())
+}
+
+fn stemmer(language: Language) -> &'static Stemmer {
+    match language {
+        Language::English => &ENGLISH_STEMMER,
+        Language::French => &FRENCH_STEMMER,
+    }
+}
+
+/// Defines the language aware normalizations applied to the words of an index,
+/// on top of the lowercasing and the accents removal that are always done.
+///
+/// It is applied to the documents words at indexing time and to the query words
+/// at search time, the normalized words are indexed alongside the original ones.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Analyzer {
+    pub stemmer: Option<Language>,
+    pub cjk_segmentation: bool,
+    pub separators: Vec<char>,
+    pub non_separators: Vec<char>,
+    pub keep_emails: bool,
+    pub keep_urls: bool,
+    pub keep_versions: bool,
+    pub keep_together: Vec<String>,
+    pub split_sub_words: bool,
+    /// Words that are not indexed, in addition to the stop words of the index.
+    pub stop_words: BTreeSet<String>,
+}
+
+/// The analyzer as it was stored by the previous versions,
+/// when it only defined the stemmer of the index.
+#[derive(Deserialize)]
+pub struct AnalyzerV0 {
+    stemmer: Option<Language>,
+}
+
+impl From<AnalyzerV0> for Analyzer {
+    fn from(analyzer: AnalyzerV0) -> Analyzer {
+        Analyzer {
+            stemmer: analyzer.stemmer,
+            ..Analyzer::default()
+        }
+    }
+}
+
+impl Analyzer {
+    /// The analyzer used for a language that has not been specifically
+    /// defined, it is this analyzer stemming the words of the language.
+    pub fn with_language(&self, language: Language) -> Analyzer {
+        Analyzer {
+            stemmer: Some(language),
+            ..self.clone()
+        }
+    }
+
+    /// The options given to the tokenizer, the documents texts
+    /// and the queries must be tokenized in the same way.
+    pub fn tokenizer_options(&self) -> TokenizerOptions {
+        TokenizerOptions {
+            cjk_segmentation: self.cjk_segmentation,
+            separators: self.separators.clone(),
+            non_separators: self.non_separators.clone(),
+            keep_emails: self.keep_emails,
+            keep_urls: self.keep_urls,
+            keep_versions: self.keep_versions,
+            keep_together: self.keep_together.clone(),
+            split_sub_words: self.split_sub_words,
+        }
+    }
+
+    /// The options given to the tokenizer to split the queries, the sub words are
+    /// not emitted because the whole words are indexed along with their sub words.
+    pub fn query_tokenizer_options(&self) -> TokenizerOptions {
+        TokenizerOptions {
+            split_sub_words: false,
+            ..self.tokenizer_options()
+        }
+    }
+
+    /// Returns the stem of the given lowercased word,
+    /// only if it is different from the word itself.
+    pub fn stem(&self, word: &str) -> Option<String> {
+        let language = self.stemmer?;
+        let stem = stemmer(language).stem(word);
+
+        if stem.is_empty() || stem == word {
+            None
+        } else {
+            Some(stem.into_owned())
+        }
+    }
+}
+
+/// Returns the analyzer to use for the texts in the given language, the index
+/// analyzer is used when no language is given, the attributes and the queries
+/// in a language must be analyzed the same way.
+pub fn language_analyzer(
+    reader: &heed::RoTxn<MainT>,
+    main_store: store::Main,
+    language: Option<Language>,
+) -> MResult<Analyzer> {
+    let analyzer = main_store.analyzer(reader)?.unwrap_or_default();
+
+    let language = match language {
+        Some(language) => language,
+        None => return Ok(analyzer),
+    };
+
+    let analyzers = main_store.language_analyzers(reader)?.unwrap_or_default();
+    match analyzers.get(&language) {
+        Some(language_analyzer) => Ok(language_analyzer.clone()),
+        None => Ok(analyzer.with_language(language)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn english_stems() {
+        let analyzer = Analyzer {
+            stemmer: Some(Language::English),
+            ..Analyzer::default()
+        };
+
+        assert_eq!(analyzer.stem("running"), Some("run".to_owned()));
+        assert_eq!(analyzer.stem("runs"), Some("run".to_owned()));
+        assert_eq!(analyzer.stem("run"), None);
+    }
+
+    #[test]
+    fn french_stems() {
+        let analyzer = Analyzer {
+            stemmer: Some(Language::French),
+            ..Analyzer::default()
+        };
+
+        assert_eq!(analyzer.stem("continuellement"), Some("continuel".to_owned()));
+        assert_eq!(analyzer.stem("continuel"), None);
+    }
+
+    #[test]
+    fn language_stemmer() {
+        let analyzer = Analyzer {
+            keep_emails: true,
+            ..Analyzer::default()
+        };
+
+        let french = analyzer.with_language(Language::French);
+        assert_eq!(french.stem("continuellement"), Some("continuel".to_owned()));
+        assert!(french.keep_emails);
+    }
+
+    #[test]
+    fn stop_words_presets() {
+        let english: Vec<_> = stop_words_preset(Language::English).collect();
+        assert!(english.contains(&"the"));
+        assert!(!english.contains(&""));
+
+        let french: Vec<_> = stop_words_preset(Language::French).collect();
+        assert!(french.contains(&"le"));
+    }
+
+    #[test]
+    fn no_stemmer() {
+        let analyzer = Analyzer::default();
+        assert_eq!(analyzer.stem("running"), None);
+    }
+}
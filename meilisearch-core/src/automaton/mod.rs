@@ -2,17 +2,21 @@ mod dfa;
 mod query_enhancer;
 
 use std::cmp::Reverse;
+use std::convert::TryFrom;
 use std::{cmp, vec};
 
 use fst::{IntoStreamer, Streamer};
 use levenshtein_automata::DFA;
-use meilisearch_tokenizer::{is_cjk, split_query_string};
+use meilisearch_tokenizer::{is_cjk, split_query_string, split_query_string_with_options};
 
+use crate::analyzer::language_analyzer;
 use crate::database::MainT;
 use crate::error::MResult;
-use crate::store;
+use crate::phonetic::phonetic_key;
+use crate::raw_indexer::INFIX_MIN_LENGTH;
+use crate::{store, Language};
 
-use self::dfa::{build_dfa, build_prefix_dfa};
+use self::dfa::{build_dfa, build_exact_dfa, build_exact_prefix_dfa, build_prefix_dfa};
 pub use self::query_enhancer::QueryEnhancer;
 use self::query_enhancer::QueryEnhancerBuilder;
 
@@ -26,6 +30,10 @@ impl AutomatonProducer {
     pub fn new(
         reader: &heed::RoTxn<MainT>,
         query: &str,
+        words: &fst::Set,
+        infix_words: &fst::Set,
+        phonetic_words: &fst::Set,
+        language: Option<Language>,
         main_store: store::Main,
         postings_list_store: store::PostingsLists,
         synonyms_store: store::Synonyms,
@@ -33,6 +41,10 @@ impl AutomatonProducer {
         let (automatons, query_enhancer) = generate_automatons(
             reader,
             query,
+            words,
+            infix_words,
+            phonetic_words,
+            language,
             main_store,
             postings_list_store,
             synonyms_store,
@@ -68,6 +80,17 @@ impl AutomatonGroup {
     }
 }
 
+/// The words FST an automaton must be evaluated against.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AutomatonKind {
+    /// The words of the documents.
+    Word,
+    /// The suffixes of the words of the infix attributes.
+    Infix,
+    /// The phonetic keys of the words of the phonetic attributes.
+    Phonetic,
+}
+
 #[derive(Debug)]
 pub struct Automaton {
     pub index: usize,
@@ -75,15 +98,28 @@ pub struct Automaton {
     pub query_len: usize,
     pub is_exact: bool,
     pub is_prefix: bool,
+    pub kind: AutomatonKind,
     pub query: String,
+    /// The query word as typed, with its accents and case,
+    /// only known for the words of the original query.
+    pub original_form: Option<String>,
+    /// The penalty of the synonym this automaton has been built
+    /// from, zero for the words that are not synonyms.
+    pub synonym_penalty: u8,
+    /// Whether this automaton has been built from a synonym.
+    pub is_synonym: bool,
+    /// The query words this automaton has been derived from,
+    /// reported to the users along with the matches it finds.
+    pub query_word: String,
 }
 
 impl Automaton {
     pub fn dfa(&self) -> DFA {
-        if self.is_prefix {
-            build_prefix_dfa(&self.query)
-        } else {
-            build_dfa(&self.query)
+        match self.kind {
+            AutomatonKind::Infix => build_exact_prefix_dfa(&self.query),
+            AutomatonKind::Phonetic => build_exact_dfa(&self.query),
+            AutomatonKind::Word if self.is_prefix => build_prefix_dfa(&self.query),
+            AutomatonKind::Word => build_dfa(&self.query),
         }
     }
 
@@ -94,7 +130,12 @@ impl Automaton {
             query_len: query.len(),
             is_exact: true,
             is_prefix: false,
+            kind: AutomatonKind::Word,
             query: query.to_string(),
+            original_form: None,
+            synonym_penalty: 0,
+            is_synonym: false,
+            query_word: query.to_string(),
         }
     }
 
@@ -105,7 +146,12 @@ impl Automaton {
             query_len: query.len(),
             is_exact: true,
             is_prefix: true,
+            kind: AutomatonKind::Word,
             query: query.to_string(),
+            original_form: None,
+            synonym_penalty: 0,
+            is_synonym: false,
+            query_word: query.to_string(),
         }
     }
 
@@ -116,7 +162,44 @@ impl Automaton {
             query_len: query.len(),
             is_exact: false,
             is_prefix: false,
+            kind: AutomatonKind::Word,
+            query: query.to_string(),
+            original_form: None,
+            synonym_penalty: 0,
+            is_synonym: false,
+            query_word: query.to_string(),
+        }
+    }
+
+    fn infix(index: usize, ngram: usize, query: &str) -> Automaton {
+        Automaton {
+            index,
+            ngram,
+            query_len: query.len(),
+            is_exact: false,
+            is_prefix: true,
+            kind: AutomatonKind::Infix,
             query: query.to_string(),
+            original_form: None,
+            synonym_penalty: 0,
+            is_synonym: false,
+            query_word: query.to_string(),
+        }
+    }
+
+    fn phonetic(index: usize, ngram: usize, key: &str) -> Automaton {
+        Automaton {
+            index,
+            ngram,
+            query_len: key.len(),
+            is_exact: false,
+            is_prefix: false,
+            kind: AutomatonKind::Phonetic,
+            query: key.to_string(),
+            original_form: None,
+            synonym_penalty: 0,
+            is_synonym: false,
+            query_word: key.to_string(),
         }
     }
 }
@@ -143,12 +226,12 @@ fn split_best_frequency<'a>(
         let (left, right) = word.split_at(i);
 
         let left_freq = postings_lists_store
-            .postings_list(reader, left.as_ref())?
-            .map_or(0, |i| i.len());
+            .postings_docids(reader, left.as_ref())?
+            .map_or(0, |docids| docids.len());
 
         let right_freq = postings_lists_store
-            .postings_list(reader, right.as_ref())?
-            .map_or(0, |i| i.len());
+            .postings_docids(reader, right.as_ref())?
+            .map_or(0, |docids| docids.len());
 
         let min_freq = cmp::min(left_freq, right_freq);
         if min_freq != 0 && best.map_or(true, |(old, _, _)| min_freq > old) {
@@ -159,15 +242,72 @@ fn split_best_frequency<'a>(
     Ok(best.map(|(_, l, r)| (l, r)))
 }
 
+fn concatenation_exists(words: &fst::Set, concat: &str, is_prefix: bool) -> bool {
+    if is_prefix {
+        let mut stream = words.range().ge(concat).into_stream();
+        stream.next().map_or(false, |word| word.starts_with(concat.as_bytes()))
+    } else {
+        words.contains(concat)
+    }
+}
+
+// Removes the stop words from the query, the last word is kept while it is
+// being typed, all the words are kept if the query is only made of stop words.
+fn remove_stop_words<F>(
+    original_forms: Vec<&str>,
+    query_words: Vec<String>,
+    has_end_whitespace: bool,
+    is_stop_word: F,
+) -> (Vec<&str>, Vec<String>)
+where
+    F: Fn(&str) -> bool,
+{
+    let len = query_words.len();
+    let removed: Vec<_> = query_words
+        .iter()
+        .enumerate()
+        .map(|(i, word)| (i + 1 < len || has_end_whitespace) && is_stop_word(word))
+        .collect();
+
+    if removed.iter().all(|removed| *removed) {
+        return (original_forms, query_words);
+    }
+
+    original_forms
+        .into_iter()
+        .zip(query_words)
+        .zip(removed)
+        .filter(|(_, removed)| !removed)
+        .map(|(words, _)| words)
+        .unzip()
+}
+
 fn generate_automatons(
     reader: &heed::RoTxn<MainT>,
     query: &str,
+    words: &fst::Set,
+    infix_words: &fst::Set,
+    phonetic_words: &fst::Set,
+    language: Option<Language>,
     main_store: store::Main,
     postings_lists_store: store::PostingsLists,
     synonym_store: store::Synonyms,
 ) -> MResult<(Vec<AutomatonGroup>, QueryEnhancer)> {
     let has_end_whitespace = query.chars().last().map_or(false, char::is_whitespace);
-    let query_words: Vec<_> = split_query_string(query).map(str::to_lowercase).collect();
+    let analyzer = language_analyzer(reader, main_store, language)?;
+    let original_forms: Vec<_> =
+        split_query_string_with_options(query, analyzer.query_tokenizer_options()).collect();
+    let query_words: Vec<_> = original_forms.iter().map(|word| word.to_lowercase()).collect();
+    let stop_words = main_store.stop_words_fst(reader)?.unwrap_or_default();
+    let (original_forms, query_words) = remove_stop_words(
+        original_forms,
+        query_words,
+        has_end_whitespace,
+        |word| {
+            let word = normalize_str(word);
+            stop_words.contains(&word) || analyzer.stop_words.contains(&word)
+        },
+    );
     let synonyms = match main_store.synonyms_fst(reader)? {
         Some(synonym) => synonym,
         None => fst::Set::default(),
@@ -180,16 +320,17 @@ fn generate_automatons(
     // We must not declare the original words to the query enhancer
     // *but* we need to push them in the automatons list first
     let mut original_automatons = Vec::new();
-    let mut original_words = query_words.iter().peekable();
-    while let Some(word) = original_words.next() {
+    let mut original_words = query_words.iter().zip(&original_forms).peekable();
+    while let Some((word, original_form)) = original_words.next() {
         let has_following_word = original_words.peek().is_some();
         let not_prefix_dfa = has_following_word || has_end_whitespace || word.chars().all(is_cjk);
 
-        let automaton = if not_prefix_dfa {
+        let mut automaton = if not_prefix_dfa {
             Automaton::exact(automaton_index, 1, word)
         } else {
             Automaton::prefix_exact(automaton_index, 1, word)
         };
+        automaton.original_form = Some(original_form.to_string());
         automaton_index += 1;
         original_automatons.push(automaton);
     }
@@ -225,9 +366,11 @@ fn generate_automatons(
                     continue;
                 }
 
-                if let Some(synonyms) = synonym_store.synonyms(reader, base.as_bytes())? {
+                let synonyms = synonym_store.synonyms_penalties(reader, base.as_bytes())?;
+                if let Some(synonyms) = synonyms {
                     let mut stream = synonyms.into_stream();
-                    while let Some(synonyms) = stream.next() {
+                    while let Some((synonyms, penalty)) = stream.next() {
+                        let penalty = u8::try_from(penalty).unwrap_or(u8::max_value());
                         let synonyms = std::str::from_utf8(synonyms).unwrap();
                         let synonyms_words: Vec<_> = split_query_string(synonyms).collect();
                         let nb_synonym_words = synonyms_words.len();
@@ -240,11 +383,14 @@ fn generate_automatons(
                         );
 
                         for synonym in synonyms_words {
-                            let automaton = if nb_synonym_words == 1 {
+                            let mut automaton = if nb_synonym_words == 1 {
                                 Automaton::exact(automaton_index, n, synonym)
                             } else {
                                 Automaton::non_exact(automaton_index, n, synonym)
                             };
+                            automaton.synonym_penalty = penalty;
+                            automaton.is_synonym = true;
+                            automaton.query_word = ngram.clone();
                             automaton_index += 1;
                             automatons.push(AutomatonGroup::normal(vec![automaton]));
                         }
@@ -256,27 +402,75 @@ fn generate_automatons(
                 if let Some((left, right)) =
                     split_best_frequency(reader, &normalized, postings_lists_store)?
                 {
-                    let a = Automaton::exact(automaton_index, 1, left);
+                    let mut a = Automaton::exact(automaton_index, 1, left);
+                    a.query_word = ngram.clone();
                     enhancer_builder.declare(query_range.clone(), automaton_index, &[left]);
                     automaton_index += 1;
 
-                    let b = Automaton::exact(automaton_index, 1, right);
-                    enhancer_builder.declare(query_range.clone(), automaton_index, &[left]);
+                    let mut b = Automaton::exact(automaton_index, 1, right);
+                    b.query_word = ngram.clone();
+                    enhancer_builder.declare(query_range.clone(), automaton_index, &[right]);
                     automaton_index += 1;
 
                     automatons.push(AutomatonGroup::phrase_query(vec![a, b]));
                 }
+
+                // automaton of the stem of the query word, the documents words
+                // are indexed along with their stems, stems are not exact matches
+                if !ngram.contains(is_cjk) {
+                    if let Some(stem) = analyzer.stem(&ngram) {
+                        enhancer_builder.declare(query_range.clone(), automaton_index, &[&stem]);
+
+                        let mut automaton = Automaton::non_exact(automaton_index, 1, &stem);
+                        automaton.query_word = ngram.clone();
+                        automaton_index += 1;
+                        automatons.push(AutomatonGroup::normal(vec![automaton]));
+                    }
+                }
+
+                // automaton of the query word found in the middle or at the end
+                // of the words of the infix attributes, never an exact match
+                let is_infix_candidate = normalized.chars().count() >= INFIX_MIN_LENGTH;
+                if !infix_words.is_empty() && is_infix_candidate && !ngram.contains(is_cjk) {
+                    enhancer_builder.declare(query_range.clone(), automaton_index, &[&normalized]);
+
+                    let mut automaton = Automaton::infix(automaton_index, 1, &normalized);
+                    automaton.query_word = ngram.clone();
+                    automaton_index += 1;
+                    automatons.push(AutomatonGroup::normal(vec![automaton]));
+                }
+
+                // automaton of the phonetic key of the query word, only
+                // if a word of the phonetic attributes sounds alike
+                if let Some(key) = phonetic_key(&ngram) {
+                    if phonetic_words.contains(&key) {
+                        enhancer_builder.declare(query_range.clone(), automaton_index, &[&key]);
+
+                        let mut automaton = Automaton::phonetic(automaton_index, 1, &key);
+                        automaton.query_word = ngram.clone();
+                        automaton_index += 1;
+                        automatons.push(AutomatonGroup::normal(vec![automaton]));
+                    }
+                }
             } else {
-                // automaton of concatenation of query words
+                // automaton of concatenation of query words,
+                // only if this concatenation is a word of the index
                 let concat = ngram_slice.concat();
                 let normalized = normalize_str(&concat);
 
-                let real_query_index = automaton_index;
-                enhancer_builder.declare(query_range.clone(), real_query_index, &[&normalized]);
+                if concatenation_exists(words, &normalized, !not_prefix_dfa) {
+                    let real_query_index = automaton_index;
+                    enhancer_builder.declare(query_range.clone(), real_query_index, &[&normalized]);
 
-                let automaton = Automaton::exact(automaton_index, n, &normalized);
-                automaton_index += 1;
-                automatons.push(AutomatonGroup::normal(vec![automaton]));
+                    let mut automaton = if not_prefix_dfa {
+                        Automaton::exact(automaton_index, n, &normalized)
+                    } else {
+                        Automaton::prefix_exact(automaton_index, n, &normalized)
+                    };
+                    automaton.query_word = ngram.clone();
+                    automaton_index += 1;
+                    automatons.push(AutomatonGroup::normal(vec![automaton]));
+                }
             }
         }
     }
@@ -294,3 +488,36 @@ fn generate_automatons(
 
     Ok((automatons, enhancer_builder.build()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn remove(query: &str, stop_words: &[&str]) -> Vec<String> {
+        let has_end_whitespace = query.ends_with(' ');
+        let original_forms: Vec<_> = query.split_whitespace().collect();
+        let query_words = original_forms.iter().map(|w| w.to_lowercase()).collect();
+        let (_, query_words) =
+            remove_stop_words(original_forms, query_words, has_end_whitespace, |word| {
+                stop_words.contains(&word)
+            });
+        query_words
+    }
+
+    #[test]
+    fn stop_words_removed() {
+        let stop_words = &["the", "of"];
+
+        assert_eq!(remove("The lord of the rings", stop_words), vec!["lord", "rings"]);
+        assert_eq!(remove("lord of the", stop_words), vec!["lord", "the"]);
+        assert_eq!(remove("lord of the ", stop_words), vec!["lord"]);
+    }
+
+    #[test]
+    fn only_stop_words_kept() {
+        let stop_words = &["the", "who"];
+
+        assert_eq!(remove("The Who ", stop_words), vec!["the", "who"]);
+        assert_eq!(remove("the", stop_words), vec!["the"]);
+    }
+}
@@ -0,0 +1,78 @@
+use std::cmp::Ordering;
+
+use slice_group_by::GroupBy;
+
+use crate::criterion::Criterion;
+use crate::RawDocument;
+
+#[inline]
+fn number_exact_form_matches(query_index: &[u32], is_exact_form: &[bool]) -> usize {
+    let mut count = 0;
+    let mut index = 0;
+
+    for group in query_index.linear_group() {
+        let len = group.len();
+        count += is_exact_form[index..index + len].contains(&true) as usize;
+        index += len;
+    }
+
+    count
+}
+
+// The query words that appear in the document with
+// the same accents and case as in the query are counted.
+#[derive(Debug, Clone, Copy)]
+pub struct ExactForm;
+
+impl Criterion for ExactForm {
+    fn evaluate(&self, lhs: &RawDocument, rhs: &RawDocument) -> Ordering {
+        let lhs = {
+            let query_index = lhs.query_index();
+            let is_exact_form = lhs.is_exact_form();
+            number_exact_form_matches(query_index, is_exact_form)
+        };
+
+        let rhs = {
+            let query_index = rhs.query_index();
+            let is_exact_form = rhs.is_exact_form();
+            number_exact_form_matches(query_index, is_exact_form)
+        };
+
+        lhs.cmp(&rhs).reverse()
+    }
+
+    fn name(&self) -> &str {
+        "ExactForm"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // typing: "résumé"
+    //
+    // doc0: "résumé"
+    // doc1: "resume"
+    #[test]
+    fn accents() {
+        let doc0 = number_exact_form_matches(&[0], &[true]);
+        let doc1 = number_exact_form_matches(&[0], &[false]);
+
+        assert_eq!(doc0.cmp(&doc1).reverse(), Ordering::Less);
+    }
+
+    // typing: "US army"
+    //
+    // doc0: "the us army"
+    // doc1: "the US Army us"
+    #[test]
+    fn case() {
+        let doc0 = number_exact_form_matches(&[0, 1], &[false, true]);
+        let doc1 = number_exact_form_matches(&[0, 0, 1], &[true, false, false]);
+
+        assert_eq!(doc0, 1);
+        assert_eq!(doc1, 1);
+        assert_eq!(doc0.cmp(&doc1).reverse(), Ordering::Equal);
+    }
+}
@@ -0,0 +1,372 @@
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+use meilisearch_schema::{Schema, SchemaAttr};
+
+use crate::criterion::Criterion;
+use crate::{DocumentId, RankedMap, RawDocument};
+
+/// The name of the attribute that can be used to promote,
+/// or demote with a negative value, specific documents.
+pub const BOOST_ATTRIBUTE: &str = "_boost";
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f64),
+    Attribute(SchemaAttr),
+    Neg(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Div(Box<Expr>, Box<Expr>),
+    Function(Function, Box<Expr>),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Function {
+    Log,
+    Ln,
+    Sqrt,
+    Abs,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Function> {
+        match name {
+            "log" => Some(Function::Log),
+            "ln" => Some(Function::Ln),
+            "sqrt" => Some(Function::Sqrt),
+            "abs" => Some(Function::Abs),
+            _ => None,
+        }
+    }
+
+    fn apply(self, value: f64) -> f64 {
+        match self {
+            Function::Log => value.log10(),
+            Function::Ln => value.ln(),
+            Function::Sqrt => value.sqrt(),
+            Function::Abs => value.abs(),
+        }
+    }
+}
+
+impl Expr {
+    // the attributes missing from a document are considered equal to zero
+    fn eval(&self, ranked_map: &RankedMap, id: DocumentId) -> f64 {
+        match self {
+            Expr::Number(n) => *n,
+            Expr::Attribute(attr) => ranked_map.get(id, *attr).map_or(0.0, |n| n.to_f64()),
+            Expr::Neg(e) => -e.eval(ranked_map, id),
+            Expr::Add(a, b) => a.eval(ranked_map, id) + b.eval(ranked_map, id),
+            Expr::Sub(a, b) => a.eval(ranked_map, id) - b.eval(ranked_map, id),
+            Expr::Mul(a, b) => a.eval(ranked_map, id) * b.eval(ranked_map, id),
+            Expr::Div(a, b) => a.eval(ranked_map, id) / b.eval(ranked_map, id),
+            Expr::Function(f, e) => f.apply(e.eval(ranked_map, id)),
+        }
+    }
+}
+
+struct Parser<'a, 's> {
+    input: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    schema: &'s Schema,
+}
+
+impl<'a, 's> Parser<'a, 's> {
+    fn skip_whitespaces(&mut self) {
+        while let Some((_, c)) = self.chars.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.chars.next();
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespaces();
+        self.chars.peek().map(|(_, c)| *c)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), FormulaError> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.chars.next();
+                Ok(())
+            }
+            Some(c) => Err(FormulaError::UnexpectedChar(c)),
+            None => Err(FormulaError::UnexpectedEnd),
+        }
+    }
+
+    // consumes the characters while the predicate is true
+    fn take_while<F: Fn(char) -> bool>(&mut self, predicate: F) -> &'a str {
+        let start = self.chars.peek().map_or(self.input.len(), |(i, _)| *i);
+        let mut end = start;
+        while let Some((i, c)) = self.chars.peek() {
+            if !predicate(*c) {
+                break;
+            }
+            end = i + c.len_utf8();
+            self.chars.next();
+        }
+        &self.input[start..end]
+    }
+
+    // expr := term (('+' | '-') term)*
+    fn expr(&mut self) -> Result<Expr, FormulaError> {
+        let mut expr = self.term()?;
+        loop {
+            match self.peek() {
+                Some('+') => {
+                    self.chars.next();
+                    expr = Expr::Add(Box::new(expr), Box::new(self.term()?));
+                }
+                Some('-') => {
+                    self.chars.next();
+                    expr = Expr::Sub(Box::new(expr), Box::new(self.term()?));
+                }
+                _ => return Ok(expr),
+            }
+        }
+    }
+
+    // term := factor (('*' | '/') factor)*
+    fn term(&mut self) -> Result<Expr, FormulaError> {
+        let mut expr = self.factor()?;
+        loop {
+            match self.peek() {
+                Some('*') => {
+                    self.chars.next();
+                    expr = Expr::Mul(Box::new(expr), Box::new(self.factor()?));
+                }
+                Some('/') => {
+                    self.chars.next();
+                    expr = Expr::Div(Box::new(expr), Box::new(self.factor()?));
+                }
+                _ => return Ok(expr),
+            }
+        }
+    }
+
+    // factor := '-' factor | '(' expr ')' | number | function '(' expr ')' | attribute
+    fn factor(&mut self) -> Result<Expr, FormulaError> {
+        match self.peek() {
+            Some('-') => {
+                self.chars.next();
+                Ok(Expr::Neg(Box::new(self.factor()?)))
+            }
+            Some('(') => {
+                self.chars.next();
+                let expr = self.expr()?;
+                self.expect(')')?;
+                Ok(expr)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let number = self.take_while(|c| c.is_ascii_digit() || c == '.');
+                match number.parse() {
+                    Ok(number) => Ok(Expr::Number(number)),
+                    Err(_) => Err(FormulaError::InvalidNumber(number.to_string())),
+                }
+            }
+            Some(c) if c.is_alphanumeric() || c == '_' => {
+                let name = self.take_while(|c| c.is_alphanumeric() || c == '_');
+                if self.peek() == Some('(') {
+                    let function = match Function::from_name(name) {
+                        Some(function) => function,
+                        None => return Err(FormulaError::UnknownFunction(name.to_string())),
+                    };
+                    self.chars.next();
+                    let expr = self.expr()?;
+                    self.expect(')')?;
+                    Ok(Expr::Function(function, Box::new(expr)))
+                } else {
+                    let attr = match self.schema.attribute(name) {
+                        Some(attr) => attr,
+                        None => return Err(FormulaError::AttributeNotFound(name.to_string())),
+                    };
+                    if !self.schema.props(attr).is_ranked() {
+                        let name = name.to_string();
+                        return Err(FormulaError::AttributeNotRegisteredForRanking(name));
+                    }
+                    Ok(Expr::Attribute(attr))
+                }
+            }
+            Some(c) => Err(FormulaError::UnexpectedChar(c)),
+            None => Err(FormulaError::UnexpectedEnd),
+        }
+    }
+}
+
+fn parse(formula: &str, schema: &Schema) -> Result<Expr, FormulaError> {
+    let mut parser = Parser {
+        input: formula,
+        chars: formula.char_indices().peekable(),
+        schema,
+    };
+
+    let expr = parser.expr()?;
+    match parser.peek() {
+        Some(c) => Err(FormulaError::UnexpectedChar(c)),
+        None => Ok(expr),
+    }
+}
+
+/// Ranks the documents by the value of an arithmetic expression
+/// over their ranked attributes, the higher the value the better.
+///
+/// The expressions support numbers, ranked attributes, the `+`, `-`, `*`
+/// and `/` operators, parentheses and the `log`, `ln`, `sqrt` and `abs`
+/// functions, e.g. `popularity * 0.7 + log(reviews)`.
+///
+/// The documents for which the value is not a finite number are ranked last.
+pub struct Formula<'a> {
+    ranked_map: &'a RankedMap,
+    expr: Expr,
+}
+
+impl<'a> Formula<'a> {
+    pub fn new(
+        ranked_map: &'a RankedMap,
+        schema: &Schema,
+        formula: &str,
+    ) -> Result<Formula<'a>, FormulaError> {
+        let expr = parse(formula, schema)?;
+        Ok(Formula { ranked_map, expr })
+    }
+
+    /// Ranks the documents by the value of their `_boost` attribute,
+    /// the documents without a boost are considered to have a boost of zero.
+    pub fn boost(ranked_map: &'a RankedMap, schema: &Schema) -> Result<Formula<'a>, FormulaError> {
+        Formula::new(ranked_map, schema, BOOST_ATTRIBUTE)
+    }
+
+    fn score(&self, id: DocumentId) -> Option<f64> {
+        let score = self.expr.eval(self.ranked_map, id);
+        if score.is_finite() {
+            Some(score)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a> Criterion for Formula<'a> {
+    fn evaluate(&self, lhs: &RawDocument, rhs: &RawDocument) -> Ordering {
+        let lhs = self.score(lhs.id);
+        let rhs = self.score(rhs.id);
+
+        match (lhs, rhs) {
+            (Some(lhs), Some(rhs)) => lhs.partial_cmp(&rhs).unwrap_or(Ordering::Equal).reverse(),
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (None, None) => Ordering::Equal,
+        }
+    }
+
+    fn name(&self) -> &str {
+        "Formula"
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FormulaError {
+    UnexpectedChar(char),
+    UnexpectedEnd,
+    InvalidNumber(String),
+    UnknownFunction(String),
+    AttributeNotFound(String),
+    AttributeNotRegisteredForRanking(String),
+}
+
+impl fmt::Display for FormulaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use FormulaError::*;
+        match self {
+            UnexpectedChar(c) => write!(f, "unexpected character {:?} in formula", c),
+            UnexpectedEnd => f.write_str("unexpected end of formula"),
+            InvalidNumber(n) => write!(f, "invalid number {:?} in formula", n),
+            UnknownFunction(name) => write!(f, "unknown function {:?} in formula", name),
+            AttributeNotFound(name) => write!(f, "attribute {:?} not found in the schema", name),
+            AttributeNotRegisteredForRanking(name) => {
+                write!(f, "attribute {:?} not registered for ranking", name)
+            }
+        }
+    }
+}
+
+impl Error for FormulaError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Number;
+    use meilisearch_schema::{SchemaBuilder, DISPLAYED, RANKED};
+
+    fn schema() -> Schema {
+        let mut builder = SchemaBuilder::with_identifier("id");
+        builder.new_attribute("id", DISPLAYED);
+        builder.new_attribute("title", DISPLAYED);
+        builder.new_attribute("popularity", RANKED);
+        builder.new_attribute("reviews", RANKED);
+        builder.new_attribute("_boost", RANKED);
+        builder.build()
+    }
+
+    #[test]
+    fn parse_and_evaluate() {
+        let schema = schema();
+        let popularity = schema.attribute("popularity").unwrap();
+        let reviews = schema.attribute("reviews").unwrap();
+
+        let mut ranked_map = RankedMap::default();
+        ranked_map.insert(DocumentId(0), popularity, Number::Unsigned(10));
+        ranked_map.insert(DocumentId(0), reviews, Number::Unsigned(1000));
+        ranked_map.insert(DocumentId(1), popularity, Number::Float(2.5.into()));
+
+        let formula = Formula::new(&ranked_map, &schema, "popularity * 0.7 + log(reviews)").unwrap();
+        assert_eq!(formula.score(DocumentId(0)), Some(10.0 * 0.7 + 3.0));
+        // log(0) is not finite, the document is ranked last
+        assert_eq!(formula.score(DocumentId(1)), None);
+
+        let formula = Formula::new(&ranked_map, &schema, "-(popularity - 1) / 2").unwrap();
+        assert_eq!(formula.score(DocumentId(0)), Some(-4.5));
+        assert_eq!(formula.score(DocumentId(1)), Some(-0.75));
+    }
+
+    #[test]
+    fn boost() {
+        let schema = schema();
+        let boost = schema.attribute("_boost").unwrap();
+
+        let mut ranked_map = RankedMap::default();
+        ranked_map.insert(DocumentId(0), boost, Number::Signed(-1));
+        ranked_map.insert(DocumentId(1), boost, Number::Unsigned(5));
+
+        let formula = Formula::boost(&ranked_map, &schema).unwrap();
+        assert_eq!(formula.score(DocumentId(0)), Some(-1.0));
+        assert_eq!(formula.score(DocumentId(1)), Some(5.0));
+        assert_eq!(formula.score(DocumentId(2)), Some(0.0));
+    }
+
+    #[test]
+    fn invalid_formulas() {
+        let schema = schema();
+        let ranked_map = RankedMap::default();
+
+        let error = |formula| Formula::new(&ranked_map, &schema, formula).err();
+
+        assert_eq!(error("popularity +"), Some(FormulaError::UnexpectedEnd));
+        assert_eq!(error("popularity reviews"), Some(FormulaError::UnexpectedChar('r')));
+        assert_eq!(error("(popularity"), Some(FormulaError::UnexpectedEnd));
+        assert_eq!(error("exp(popularity)"), Some(FormulaError::UnknownFunction("exp".into())));
+        assert_eq!(error("price"), Some(FormulaError::AttributeNotFound("price".into())));
+        assert_eq!(
+            error("title"),
+            Some(FormulaError::AttributeNotRegisteredForRanking("title".into()))
+        );
+    }
+}
@@ -1,20 +1,38 @@
 mod document_id;
 mod exact;
+mod exact_form;
+mod formula;
 mod number_of_words;
+mod phonetic;
 mod sort_by_attr;
 mod sum_of_typos;
 mod sum_of_words_attribute;
 mod sum_of_words_position;
+mod synonym_penalty;
+mod time_decay;
+mod whole_attribute;
 mod words_proximity;
+mod words_rarity;
 
 use crate::RawDocument;
 use std::cmp::Ordering;
 
 pub use self::{
-    document_id::DocumentId, exact::Exact, number_of_words::NumberOfWords,
-    sort_by_attr::SortByAttr, sum_of_typos::SumOfTypos,
-    sum_of_words_attribute::SumOfWordsAttribute, sum_of_words_position::SumOfWordsPosition,
+    document_id::DocumentId,
+    exact::Exact,
+    exact_form::ExactForm,
+    formula::{Formula, FormulaError, BOOST_ATTRIBUTE},
+    number_of_words::NumberOfWords,
+    phonetic::Phonetic,
+    sort_by_attr::{SortByAttr, SortByAttrError},
+    sum_of_typos::SumOfTypos,
+    sum_of_words_attribute::SumOfWordsAttribute,
+    sum_of_words_position::SumOfWordsPosition,
+    synonym_penalty::SynonymPenalty,
+    time_decay::{Decay, DecayFunction, TimeDecay},
+    whole_attribute::WholeAttribute,
     words_proximity::WordsProximity,
+    words_rarity::WordsRarity,
 };
 
 pub trait Criterion: Send + Sync {
@@ -26,6 +44,28 @@ pub trait Criterion: Send + Sync {
     fn eq(&self, lhs: &RawDocument, rhs: &RawDocument) -> bool {
         self.evaluate(lhs, rhs) == Ordering::Equal
     }
+
+    /// Returns `true` if this criterion ranks `document` before any document
+    /// only matching the `query_len` query words with `min_distance` typos or more.
+    ///
+    /// When it is the first criterion the documents only containing words with
+    /// more typos are never fetched once enough documents are ranked before them.
+    #[inline]
+    fn ranks_before_typos(
+        &self,
+        _document: &RawDocument,
+        _query_len: usize,
+        _min_distance: u8,
+    ) -> bool {
+        false
+    }
+
+    /// Returns `true` if the `ranks_before_typos` method of this criterion
+    /// can return `true`, the documents are otherwise all fetched at once.
+    #[inline]
+    fn bounds_typos(&self) -> bool {
+        false
+    }
 }
 
 impl<'a, T: Criterion + ?Sized + Send + Sync> Criterion for &'a T {
@@ -40,6 +80,19 @@ impl<'a, T: Criterion + ?Sized + Send + Sync> Criterion for &'a T {
     fn eq(&self, lhs: &RawDocument, rhs: &RawDocument) -> bool {
         (**self).eq(lhs, rhs)
     }
+
+    fn ranks_before_typos(
+        &self,
+        document: &RawDocument,
+        query_len: usize,
+        min_distance: u8,
+    ) -> bool {
+        (**self).ranks_before_typos(document, query_len, min_distance)
+    }
+
+    fn bounds_typos(&self) -> bool {
+        (**self).bounds_typos()
+    }
 }
 
 impl<T: Criterion + ?Sized> Criterion for Box<T> {
@@ -54,6 +107,19 @@ impl<T: Criterion + ?Sized> Criterion for Box<T> {
     fn eq(&self, lhs: &RawDocument, rhs: &RawDocument) -> bool {
         (**self).eq(lhs, rhs)
     }
+
+    fn ranks_before_typos(
+        &self,
+        document: &RawDocument,
+        query_len: usize,
+        min_distance: u8,
+    ) -> bool {
+        (**self).ranks_before_typos(document, query_len, min_distance)
+    }
+
+    fn bounds_typos(&self) -> bool {
+        (**self).bounds_typos()
+    }
 }
 
 #[derive(Default)]
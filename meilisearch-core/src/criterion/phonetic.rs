@@ -0,0 +1,88 @@
+use std::cmp::Ordering;
+
+use slice_group_by::GroupBy;
+
+use crate::criterion::Criterion;
+use crate::RawDocument;
+
+// The matches of a query word are sorted by distance then by phonetic
+// flag, the first one is the best match of this query word.
+#[inline]
+fn number_of_phonetic_matches(query_index: &[u32], is_phonetic: &[bool]) -> usize {
+    let mut count = 0;
+    let mut index = 0;
+
+    for group in query_index.linear_group() {
+        if is_phonetic[index] {
+            count += 1;
+        }
+        index += group.len();
+    }
+
+    count
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Phonetic;
+
+impl Criterion for Phonetic {
+    fn evaluate(&self, lhs: &RawDocument, rhs: &RawDocument) -> Ordering {
+        let lhs = {
+            let query_index = lhs.query_index();
+            let is_phonetic = lhs.is_phonetic();
+            number_of_phonetic_matches(query_index, is_phonetic)
+        };
+
+        let rhs = {
+            let query_index = rhs.query_index();
+            let is_phonetic = rhs.is_phonetic();
+            number_of_phonetic_matches(query_index, is_phonetic)
+        };
+
+        lhs.cmp(&rhs)
+    }
+
+    fn name(&self) -> &str {
+        "Phonetic"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // typing: "Catherine Smith"
+    //
+    // doc0: "Catherine Smith"
+    // doc1: "Kathryn Smith"
+    #[test]
+    fn phonetic_after_exact() {
+        let query_index0 = &[0, 1];
+        let is_phonetic0 = &[false, false];
+
+        let query_index1 = &[0, 1];
+        let is_phonetic1 = &[true, false];
+
+        let doc0 = number_of_phonetic_matches(query_index0, is_phonetic0);
+        let doc1 = number_of_phonetic_matches(query_index1, is_phonetic1);
+        assert_eq!(doc0.cmp(&doc1), Ordering::Less);
+    }
+
+    // typing: "Catherine Smith"
+    //
+    // doc0: "Catherine Smith Smyth"
+    // doc1: "Kathryn Smith"
+    #[test]
+    fn best_match_only() {
+        let query_index0 = &[0, 1, 1];
+        let is_phonetic0 = &[false, false, true];
+
+        let query_index1 = &[0, 1];
+        let is_phonetic1 = &[true, false];
+
+        let doc0 = number_of_phonetic_matches(query_index0, is_phonetic0);
+        let doc1 = number_of_phonetic_matches(query_index1, is_phonetic1);
+        assert_eq!(doc0, 0);
+        assert_eq!(doc0.cmp(&doc1), Ordering::Less);
+    }
+}
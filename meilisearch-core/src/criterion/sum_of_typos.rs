@@ -19,14 +19,29 @@ fn custom_log10(n: u8) -> f32 {
     }
 }
 
+// The number of typos a phonetic match costs, more than the
+// maximum number of typos a word can be matched with.
+const PHONETIC_TYPOS: u8 = 3;
+
 #[inline]
-fn sum_matches_typos(query_index: &[u32], distance: &[u8]) -> usize {
+fn sum_matches_typos(query_index: &[u32], distance: &[u8], is_phonetic: &[bool]) -> usize {
     let mut number_words: usize = 0;
     let mut sum_typos = 0.0;
     let mut index = 0;
 
     for group in query_index.linear_group() {
-        sum_typos += custom_log10(distance[index]);
+        let typos = (index..index + group.len())
+            .map(|i| {
+                if is_phonetic[i] {
+                    PHONETIC_TYPOS
+                } else {
+                    distance[i]
+                }
+            })
+            .min()
+            .unwrap_or_default();
+
+        sum_typos += custom_log10(typos);
         number_words += 1;
         index += group.len();
     }
@@ -42,13 +57,15 @@ impl Criterion for SumOfTypos {
         let lhs = {
             let query_index = lhs.query_index();
             let distance = lhs.distance();
-            sum_matches_typos(query_index, distance)
+            let is_phonetic = lhs.is_phonetic();
+            sum_matches_typos(query_index, distance, is_phonetic)
         };
 
         let rhs = {
             let query_index = rhs.query_index();
             let distance = rhs.distance();
-            sum_matches_typos(query_index, distance)
+            let is_phonetic = rhs.is_phonetic();
+            sum_matches_typos(query_index, distance, is_phonetic)
         };
 
         lhs.cmp(&rhs).reverse()
@@ -57,6 +74,31 @@ impl Criterion for SumOfTypos {
     fn name(&self) -> &str {
         "SumOfTypos"
     }
+
+    fn ranks_before_typos(
+        &self,
+        document: &RawDocument,
+        query_len: usize,
+        min_distance: u8,
+    ) -> bool {
+        // the best such a document can do is to match every query word
+        let query_index: Vec<_> = (0..query_len as u32).collect();
+        let distance = vec![min_distance; query_len];
+        let is_phonetic = vec![false; query_len];
+        let best = sum_matches_typos(&query_index, &distance, &is_phonetic);
+
+        let score = sum_matches_typos(
+            document.query_index(),
+            document.distance(),
+            document.is_phonetic(),
+        );
+
+        score > best
+    }
+
+    fn bounds_typos(&self) -> bool {
+        true
+    }
 }
 
 #[cfg(test)]
@@ -71,12 +113,14 @@ mod tests {
     fn one_typo_reference() {
         let query_index0 = &[0, 1];
         let distance0 = &[0, 0];
+        let is_phonetic0 = &[false, false];
 
         let query_index1 = &[0, 1];
         let distance1 = &[1, 0];
+        let is_phonetic1 = &[false, false];
 
-        let doc0 = sum_matches_typos(query_index0, distance0);
-        let doc1 = sum_matches_typos(query_index1, distance1);
+        let doc0 = sum_matches_typos(query_index0, distance0, is_phonetic0);
+        let doc1 = sum_matches_typos(query_index1, distance1, is_phonetic1);
         assert_eq!(doc0.cmp(&doc1).reverse(), Ordering::Less);
     }
 
@@ -88,12 +132,14 @@ mod tests {
     fn no_typo() {
         let query_index0 = &[0, 1];
         let distance0 = &[0, 0];
+        let is_phonetic0 = &[false, false];
 
         let query_index1 = &[0];
         let distance1 = &[0];
+        let is_phonetic1 = &[false];
 
-        let doc0 = sum_matches_typos(query_index0, distance0);
-        let doc1 = sum_matches_typos(query_index1, distance1);
+        let doc0 = sum_matches_typos(query_index0, distance0, is_phonetic0);
+        let doc1 = sum_matches_typos(query_index1, distance1, is_phonetic1);
         assert_eq!(doc0.cmp(&doc1).reverse(), Ordering::Less);
     }
 
@@ -105,12 +151,52 @@ mod tests {
     fn one_typo() {
         let query_index0 = &[0, 1];
         let distance0 = &[0, 1];
+        let is_phonetic0 = &[false, false];
 
         let query_index1 = &[0];
         let distance1 = &[0];
+        let is_phonetic1 = &[false];
+
+        let doc0 = sum_matches_typos(query_index0, distance0, is_phonetic0);
+        let doc1 = sum_matches_typos(query_index1, distance1, is_phonetic1);
+        assert_eq!(doc0.cmp(&doc1).reverse(), Ordering::Less);
+    }
+
+    // typing: "Catherine Smith"
+    //
+    // doc0: "Catherina Smith"
+    // doc1: "Kathryn Smith"
+    #[test]
+    fn phonetic_after_one_typo() {
+        let query_index0 = &[0, 1];
+        let distance0 = &[1, 0];
+        let is_phonetic0 = &[false, false];
+
+        let query_index1 = &[0, 1];
+        let distance1 = &[0, 0];
+        let is_phonetic1 = &[true, false];
+
+        let doc0 = sum_matches_typos(query_index0, distance0, is_phonetic0);
+        let doc1 = sum_matches_typos(query_index1, distance1, is_phonetic1);
+        assert_eq!(doc0.cmp(&doc1).reverse(), Ordering::Less);
+    }
+
+    // typing: "Catherine Smith"
+    //
+    // doc0: "Catherine Smith", also matched phonetically
+    // doc1: "Catherina Smith"
+    #[test]
+    fn exact_and_phonetic() {
+        let query_index0 = &[0, 0, 1];
+        let distance0 = &[0, 0, 0];
+        let is_phonetic0 = &[false, true, false];
+
+        let query_index1 = &[0, 1];
+        let distance1 = &[1, 0];
+        let is_phonetic1 = &[false, false];
 
-        let doc0 = sum_matches_typos(query_index0, distance0);
-        let doc1 = sum_matches_typos(query_index1, distance1);
+        let doc0 = sum_matches_typos(query_index0, distance0, is_phonetic0);
+        let doc1 = sum_matches_typos(query_index1, distance1, is_phonetic1);
         assert_eq!(doc0.cmp(&doc1).reverse(), Ordering::Less);
     }
 }
@@ -0,0 +1,84 @@
+use std::cmp::Ordering;
+
+use slice_group_by::GroupBy;
+
+use crate::criterion::Criterion;
+use crate::RawDocument;
+
+// For each query word the smallest penalty of its matches is kept,
+// the words matched directly and not by a synonym have no penalty.
+#[inline]
+fn sum_of_synonym_penalties(query_index: &[u32], synonym_penalty: &[u8]) -> usize {
+    let mut sum = 0;
+    let mut index = 0;
+
+    for group in query_index.linear_group() {
+        let len = group.len();
+        let penalties = &synonym_penalty[index..index + len];
+        sum += penalties.iter().min().map_or(0, |p| usize::from(*p));
+        index += len;
+    }
+
+    sum
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SynonymPenalty;
+
+impl Criterion for SynonymPenalty {
+    fn evaluate(&self, lhs: &RawDocument, rhs: &RawDocument) -> Ordering {
+        let lhs = {
+            let query_index = lhs.query_index();
+            let synonym_penalty = lhs.synonym_penalty();
+            sum_of_synonym_penalties(query_index, synonym_penalty)
+        };
+
+        let rhs = {
+            let query_index = rhs.query_index();
+            let synonym_penalty = rhs.synonym_penalty();
+            sum_of_synonym_penalties(query_index, synonym_penalty)
+        };
+
+        lhs.cmp(&rhs)
+    }
+
+    fn name(&self) -> &str {
+        "SynonymPenalty"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // typing: "sofa"
+    //
+    // "couch" is a synonym of "sofa" with a weight of 0.8
+    //
+    // doc0: "red sofa"
+    // doc1: "red couch"
+    #[test]
+    fn direct_match_first() {
+        let doc0 = sum_of_synonym_penalties(&[0], &[0]);
+        let doc1 = sum_of_synonym_penalties(&[0], &[20]);
+
+        assert_eq!(doc0.cmp(&doc1), Ordering::Less);
+    }
+
+    // typing: "big sofa"
+    //
+    // "couch" is a synonym of "sofa" with a weight of 0.8
+    // "large" is a synonym of "big" with a weight of 0.5
+    //
+    // doc0: "large sofa couch"
+    // doc1: "big couch"
+    #[test]
+    fn best_match_of_each_word() {
+        let doc0 = sum_of_synonym_penalties(&[0, 1, 1], &[50, 0, 20]);
+        let doc1 = sum_of_synonym_penalties(&[0, 1], &[0, 20]);
+
+        assert_eq!(doc0, 50);
+        assert_eq!(doc1, 20);
+        assert_eq!(doc0.cmp(&doc1), Ordering::Greater);
+    }
+}
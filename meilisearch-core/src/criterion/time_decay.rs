@@ -0,0 +1,150 @@
+use std::cmp::Ordering;
+
+use meilisearch_schema::{Schema, SchemaAttr};
+use serde::{Deserialize, Serialize};
+
+use crate::criterion::{Criterion, SortByAttrError};
+use crate::{Number, RankedMap, RawDocument};
+
+/// The number of steps in which the decay scores are rounded, documents
+/// in the same step are considered equally fresh and the following
+/// criteria decide of their order.
+const SCORE_STEPS: f64 = 10.0;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DecayFunction {
+    Linear,
+    Exponential,
+    Gauss,
+}
+
+/// Describes how the score of a document decreases with
+/// the distance between its date and the origin.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Decay {
+    pub function: DecayFunction,
+    /// The timestamp from which the distances are computed, usually now.
+    pub origin: i64,
+    /// The distance, in seconds, at which the score is equal to `decay`.
+    pub scale: u64,
+    /// The distance, in seconds, under which the score is not decreased.
+    pub offset: u64,
+    /// The score of the documents at `scale` seconds from the origin.
+    pub decay: f64,
+}
+
+impl Decay {
+    fn score(&self, timestamp: f64) -> f64 {
+        let distance = (timestamp - self.origin as f64).abs() - self.offset as f64;
+        let distance = distance.max(0.0);
+        let scale = (self.scale as f64).max(1.0);
+        let decay = self.decay.max(f64::EPSILON).min(1.0 - f64::EPSILON);
+
+        match self.function {
+            DecayFunction::Linear => {
+                let scale = scale / (1.0 - decay);
+                ((scale - distance) / scale).max(0.0)
+            }
+            DecayFunction::Exponential => (decay.ln() / scale * distance).exp(),
+            DecayFunction::Gauss => (decay.ln() * distance.powi(2) / scale.powi(2)).exp(),
+        }
+    }
+}
+
+/// Ranks higher the documents whose date, stored in a ranked attribute
+/// as a timestamp in seconds, is close to the origin of the decay.
+pub struct TimeDecay<'a> {
+    ranked_map: &'a RankedMap,
+    attr: SchemaAttr,
+    decay: Decay,
+}
+
+impl<'a> TimeDecay<'a> {
+    pub fn new(
+        ranked_map: &'a RankedMap,
+        schema: &Schema,
+        attr_name: &str,
+        decay: Decay,
+    ) -> Result<TimeDecay<'a>, SortByAttrError> {
+        let attr = match schema.attribute(attr_name) {
+            Some(attr) => attr,
+            None => return Err(SortByAttrError::AttributeNotFound),
+        };
+
+        if !schema.props(attr).is_ranked() {
+            return Err(SortByAttrError::AttributeNotRegisteredForRanking);
+        }
+
+        Ok(TimeDecay {
+            ranked_map,
+            attr,
+            decay,
+        })
+    }
+
+    fn step(&self, value: Number) -> i64 {
+        let score = self.decay.score(value.to_f64());
+        (score * SCORE_STEPS).round() as i64
+    }
+}
+
+impl<'a> Criterion for TimeDecay<'a> {
+    fn evaluate(&self, lhs: &RawDocument, rhs: &RawDocument) -> Ordering {
+        let lhs = self.ranked_map.get(lhs.id, self.attr).map(|v| self.step(v));
+        let rhs = self.ranked_map.get(rhs.id, self.attr).map(|v| self.step(v));
+
+        match (lhs, rhs) {
+            (Some(lhs), Some(rhs)) => lhs.cmp(&rhs).reverse(),
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (None, None) => Ordering::Equal,
+        }
+    }
+
+    fn name(&self) -> &str {
+        "TimeDecay"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 24 * 60 * 60;
+    const NOW: i64 = 1_570_000_000;
+
+    fn decay(function: DecayFunction) -> Decay {
+        Decay {
+            function,
+            origin: NOW,
+            scale: 7 * DAY,
+            offset: DAY,
+            decay: 0.5,
+        }
+    }
+
+    #[test]
+    fn score_at_scale() {
+        for function in &[DecayFunction::Linear, DecayFunction::Exponential, DecayFunction::Gauss] {
+            let decay = decay(*function);
+            let at_scale = NOW - (8 * DAY) as i64;
+            assert!((decay.score(at_scale as f64) - 0.5).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn recent_documents_first() {
+        for function in &[DecayFunction::Linear, DecayFunction::Exponential, DecayFunction::Gauss] {
+            let decay = decay(*function);
+
+            let in_offset = decay.score((NOW - (DAY / 2) as i64) as f64);
+            let last_week = decay.score((NOW - (5 * DAY) as i64) as f64);
+            let last_month = decay.score((NOW - (30 * DAY) as i64) as f64);
+
+            assert_eq!(in_offset, 1.0);
+            assert!(in_offset > last_week);
+            assert!(last_week > last_month);
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+
+use meilisearch_schema::SchemaAttr;
+use sdset::Set;
+use slice_group_by::GroupBy;
+
+use crate::criterion::Criterion;
+use crate::RawDocument;
+
+// Returns `true` if the document has an attribute made only of query words,
+// all matched exactly, in the order of the query and next to each other.
+//
+// The attribute can be equal to any part of the query, the other query words
+// can be matched in other attributes. The words positions are not always
+// starting from zero in an attribute, only the relative positions are considered.
+#[inline]
+fn has_whole_attribute_match(
+    query_index: &[u32],
+    attribute: &[u16],
+    word_index: &[u16],
+    is_exact: &[bool],
+    fields_counts: &Set<(SchemaAttr, u64)>,
+) -> bool {
+    let mut exact_matches: Vec<_> = (0..query_index.len())
+        .filter(|&i| is_exact[i])
+        .map(|i| (attribute[i], word_index[i], query_index[i]))
+        .collect();
+    exact_matches.sort_unstable();
+    exact_matches.dedup();
+
+    fields_counts
+        .iter()
+        .filter(|(_, count)| *count != 0)
+        .any(|(attr, count)| {
+            exact_matches
+                .iter()
+                .filter(|(a, _, _)| *a == attr.0)
+                .any(|&(a, start, query)| {
+                    // the run of exact matches starting here must cover the whole attribute
+                    (1..*count).all(|i| match u16::try_from(u64::from(start) + i) {
+                        Ok(position) => {
+                            let position = (a, position, query + i as u32);
+                            exact_matches.binary_search(&position).is_ok()
+                        }
+                        Err(_) => false,
+                    })
+                })
+        })
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct WholeAttribute;
+
+impl Criterion for WholeAttribute {
+    fn evaluate(&self, lhs: &RawDocument, rhs: &RawDocument) -> Ordering {
+        let lhs = has_whole_attribute_match(
+            lhs.query_index(),
+            lhs.attribute(),
+            lhs.word_index(),
+            lhs.is_exact(),
+            &lhs.fields_counts,
+        );
+
+        let rhs = has_whole_attribute_match(
+            rhs.query_index(),
+            rhs.attribute(),
+            rhs.word_index(),
+            rhs.is_exact(),
+            &rhs.fields_counts,
+        );
+
+        lhs.cmp(&rhs).reverse()
+    }
+
+    fn name(&self) -> &str {
+        "WholeAttribute"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // typing: "iphone case"
+    //
+    // doc0: { 0. "iphone case" }
+    // doc1: { 0. "iphone case black" }
+    #[test]
+    fn whole_title() {
+        let doc0 = {
+            let query_index = &[0, 1];
+            let attribute = &[0, 0];
+            let word_index = &[0, 1];
+            let is_exact = &[true, true];
+            let fields_counts = Set::new(&[(SchemaAttr(0), 2)]).unwrap();
+
+            has_whole_attribute_match(query_index, attribute, word_index, is_exact, fields_counts)
+        };
+
+        let doc1 = {
+            let query_index = &[0, 1];
+            let attribute = &[0, 0];
+            let word_index = &[0, 1];
+            let is_exact = &[true, true];
+            let fields_counts = Set::new(&[(SchemaAttr(0), 3)]).unwrap();
+
+            has_whole_attribute_match(query_index, attribute, word_index, is_exact, fields_counts)
+        };
+
+        assert!(doc0);
+        assert!(!doc1);
+        assert_eq!(doc0.cmp(&doc1).reverse(), Ordering::Less);
+    }
+
+    // typing: "iphone case"
+    //
+    // doc0: { 0. "case iphone" }
+    // doc1: { 0. "iphone cases" }
+    // doc2: { 0. "iphone", 1. "case" }, each attribute is a part of the query
+    #[test]
+    fn order_and_exactness() {
+        let doc0 = {
+            let query_index = &[0, 1];
+            let attribute = &[0, 0];
+            let word_index = &[1, 0];
+            let is_exact = &[true, true];
+            let fields_counts = Set::new(&[(SchemaAttr(0), 2)]).unwrap();
+
+            has_whole_attribute_match(query_index, attribute, word_index, is_exact, fields_counts)
+        };
+
+        let doc1 = {
+            let query_index = &[0, 1];
+            let attribute = &[0, 0];
+            let word_index = &[0, 1];
+            let is_exact = &[true, false];
+            let fields_counts = Set::new(&[(SchemaAttr(0), 2)]).unwrap();
+
+            has_whole_attribute_match(query_index, attribute, word_index, is_exact, fields_counts)
+        };
+
+        let doc2 = {
+            let query_index = &[0, 1];
+            let attribute = &[0, 1];
+            let word_index = &[0, 0];
+            let is_exact = &[true, true];
+            let fields_counts = Set::new(&[(SchemaAttr(0), 1), (SchemaAttr(1), 1)]).unwrap();
+
+            has_whole_attribute_match(query_index, attribute, word_index, is_exact, fields_counts)
+        };
+
+        assert!(!doc0);
+        assert!(!doc1);
+        assert!(doc2);
+    }
+
+    // typing: "iphone case black"
+    //
+    // doc0: { 0. "iphone case", 1. "black leather" }
+    // doc1: { 0. "iphone case", 1. "red leather" }
+    // doc2: { 0. "iphone case black leather" }
+    #[test]
+    fn across_attributes() {
+        let doc0 = {
+            let query_index = &[0, 1, 2];
+            let attribute = &[0, 0, 1];
+            let word_index = &[0, 1, 0];
+            let is_exact = &[true, true, true];
+            let fields_counts = Set::new(&[(SchemaAttr(0), 2), (SchemaAttr(1), 2)]).unwrap();
+
+            has_whole_attribute_match(query_index, attribute, word_index, is_exact, fields_counts)
+        };
+
+        let doc1 = {
+            let query_index = &[0, 1];
+            let attribute = &[0, 0];
+            let word_index = &[0, 1];
+            let is_exact = &[true, true];
+            let fields_counts = Set::new(&[(SchemaAttr(0), 2), (SchemaAttr(1), 2)]).unwrap();
+
+            has_whole_attribute_match(query_index, attribute, word_index, is_exact, fields_counts)
+        };
+
+        let doc2 = {
+            let query_index = &[0, 1, 2];
+            let attribute = &[0, 0, 0];
+            let word_index = &[0, 1, 2];
+            let is_exact = &[true, true, true];
+            let fields_counts = Set::new(&[(SchemaAttr(0), 4)]).unwrap();
+
+            has_whole_attribute_match(query_index, attribute, word_index, is_exact, fields_counts)
+        };
+
+        assert!(doc0);
+        assert!(doc1);
+        assert!(!doc2);
+    }
+
+    // typing: "black iphone case"
+    //
+    // doc0: { 0. "iphone case" }
+    #[test]
+    fn inside_the_query() {
+        let query_index = &[1, 2];
+        let attribute = &[0, 0];
+        let word_index = &[0, 1];
+        let is_exact = &[true, true];
+        let fields_counts = Set::new(&[(SchemaAttr(0), 2)]).unwrap();
+
+        assert!(has_whole_attribute_match(
+            query_index,
+            attribute,
+            word_index,
+            is_exact,
+            fields_counts
+        ));
+    }
+
+    // typing: "iphone case"
+    //
+    // doc0: { 0. "iphone case" } with positions not starting from zero
+    // doc1: { 0. "iphone ... case" } with a gap between the words
+    #[test]
+    fn relative_positions() {
+        let doc0 = {
+            let query_index = &[0, 1];
+            let attribute = &[0, 0];
+            let word_index = &[8, 9];
+            let is_exact = &[true, true];
+            let fields_counts = Set::new(&[(SchemaAttr(0), 2)]).unwrap();
+
+            has_whole_attribute_match(query_index, attribute, word_index, is_exact, fields_counts)
+        };
+
+        let doc1 = {
+            let query_index = &[0, 1];
+            let attribute = &[0, 0];
+            let word_index = &[0, 2];
+            let is_exact = &[true, true];
+            let fields_counts = Set::new(&[(SchemaAttr(0), 2)]).unwrap();
+
+            has_whole_attribute_match(query_index, attribute, word_index, is_exact, fields_counts)
+        };
+
+        assert!(doc0);
+        assert!(!doc1);
+    }
+}
@@ -0,0 +1,146 @@
+use std::cmp::Ordering;
+
+use meilisearch_schema::SchemaAttr;
+use sdset::Set;
+use slice_group_by::GroupBy;
+
+use crate::criterion::Criterion;
+use crate::RawDocument;
+
+// The inverse document frequency of a word,
+// the rarer the word the higher its weight.
+#[inline]
+fn inverse_document_frequency(number_of_documents: u64, documents_frequency: u32) -> f64 {
+    let number_of_documents = number_of_documents as f64 + 1.0;
+    let documents_frequency = f64::from(documents_frequency) + 1.0;
+    (number_of_documents / documents_frequency).ln().max(0.0)
+}
+
+// For each query word, the weights of its matching positions in an attribute
+// are summed and divided by the number of words of this attribute,
+// the attribute where the query word weighs the most is kept.
+#[inline]
+fn tf_idf_score(
+    query_index: &[u32],
+    attribute: &[u16],
+    word_index: &[u16],
+    documents_frequency: &[u32],
+    fields_counts: &Set<(SchemaAttr, u64)>,
+    number_of_documents: u64,
+) -> f64 {
+    let mut score = 0.0;
+    let mut index = 0;
+
+    for group in query_index.linear_group() {
+        let range = index..index + group.len();
+        index += group.len();
+
+        // the positions matched by this query word, a position can be matched
+        // by multiple words (e.g. a word and its stem), the rarest one is kept
+        let mut positions: Vec<_> = range
+            .map(|i| {
+                let idf = inverse_document_frequency(number_of_documents, documents_frequency[i]);
+                (attribute[i], word_index[i], idf)
+            })
+            .collect();
+        positions.sort_unstable_by(|a, b| {
+            (a.0, a.1).cmp(&(b.0, b.1)).then(b.2.partial_cmp(&a.2).unwrap_or(Ordering::Equal))
+        });
+        positions.dedup_by_key(|(attr, word_index, _)| (*attr, *word_index));
+
+        let best = positions
+            .linear_group_by_key(|(attr, _, _)| *attr)
+            .map(|group| {
+                let attr = group[0].0;
+                let weights: f64 = group.iter().map(|(_, _, idf)| idf).sum();
+                let count = match fields_counts.binary_search_by_key(&attr, |(a, _)| a.0) {
+                    Ok(pos) => fields_counts[pos].1.max(1),
+                    Err(_) => 1,
+                };
+                weights / count as f64
+            })
+            .fold(0.0, f64::max);
+
+        score += best;
+    }
+
+    score
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct WordsRarity {
+    number_of_documents: u64,
+}
+
+impl WordsRarity {
+    pub fn new(number_of_documents: u64) -> WordsRarity {
+        WordsRarity { number_of_documents }
+    }
+}
+
+impl Criterion for WordsRarity {
+    fn evaluate(&self, lhs: &RawDocument, rhs: &RawDocument) -> Ordering {
+        let lhs = tf_idf_score(
+            lhs.query_index(),
+            lhs.attribute(),
+            lhs.word_index(),
+            lhs.documents_frequency(),
+            &lhs.fields_counts,
+            self.number_of_documents,
+        );
+
+        let rhs = tf_idf_score(
+            rhs.query_index(),
+            rhs.attribute(),
+            rhs.word_index(),
+            rhs.documents_frequency(),
+            &rhs.fields_counts,
+            self.number_of_documents,
+        );
+
+        lhs.partial_cmp(&rhs).unwrap_or(Ordering::Equal).reverse()
+    }
+
+    fn name(&self) -> &str {
+        "WordsRarity"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // typing: "the kubernetes"
+    //
+    // doc0: { 0. "the cluster" }
+    // doc1: { 0. "kubernetes cluster" }
+    //
+    // "the" is in 900 documents, "kubernetes" in 10
+    #[test]
+    fn rare_words_weigh_more() {
+        let fields_counts = Set::new(&[(SchemaAttr(0), 2)]).unwrap();
+
+        let doc0 = tf_idf_score(&[0], &[0], &[0], &[900], fields_counts, 1000);
+        let doc1 = tf_idf_score(&[1], &[0], &[0], &[10], fields_counts, 1000);
+
+        assert_eq!(doc0.partial_cmp(&doc1).unwrap().reverse(), Ordering::Greater);
+    }
+
+    // typing: "kubernetes"
+    //
+    // doc0: { 0. "kubernetes" }
+    // doc1: { 0. "kubernetes cluster setup" }
+    // doc2: { 0. "kubernetes on kubernetes" }
+    #[test]
+    fn term_frequency_in_attribute() {
+        let short = Set::new(&[(SchemaAttr(0), 1)]).unwrap();
+        let long = Set::new(&[(SchemaAttr(0), 3)]).unwrap();
+
+        let doc0 = tf_idf_score(&[0], &[0], &[0], &[10], short, 1000);
+        let doc1 = tf_idf_score(&[0], &[0], &[0], &[10], long, 1000);
+        let doc2 = tf_idf_score(&[0, 0], &[0, 0], &[0, 2], &[10, 10], long, 1000);
+
+        assert!(doc0 > doc1);
+        assert!(doc2 > doc1);
+    }
+}
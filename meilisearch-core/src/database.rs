@@ -180,6 +180,11 @@ impl Database {
                 }
             };
 
+            // the stores written by a previous version are converted once
+            let mut writer = env.typed_write_txn::<MainT>()?;
+            store::upgrade(&mut writer, &index)?;
+            writer.commit()?;
+
             let env_clone = env.clone();
             let update_env_clone = update_env.clone();
             let index_clone = index.clone();
@@ -236,8 +241,20 @@ impl Database {
                 let (sender, receiver) = crossbeam_channel::bounded(100);
                 let index = store::create(&self.env, &self.update_env, name, sender)?;
 
-                let mut writer = self.env.write_txn()?;
+                let mut writer = self.env.typed_write_txn::<MainT>()?;
                 self.indexes_store.put(&mut writer, name, &())?;
+                index
+                    .main
+                    .put_postings_lists_version(&mut writer, store::POSTINGS_LISTS_VERSION)?;
+                index
+                    .main
+                    .put_schema_version(&mut writer, store::SCHEMA_VERSION)?;
+                index
+                    .main
+                    .put_filter_values_version(&mut writer, store::FILTER_VALUES_VERSION)?;
+                index
+                    .main
+                    .put_analyzer_version(&mut writer, store::ANALYZER_VERSION)?;
 
                 let env_clone = self.env.clone();
                 let update_env_clone = self.update_env.clone();
@@ -335,7 +352,8 @@ mod tests {
 
     use crate::criterion::{self, CriteriaBuilder};
     use crate::update::{ProcessedUpdateResult, UpdateStatus};
-    use crate::{Document, DocumentId};
+    use crate::{DocIndex, Document, DocumentId};
+    use meilisearch_schema::{SchemaAttr, SchemaBuilder, DISPLAYED, INDEXED};
     use serde::de::IgnoredAny;
     use std::sync::mpsc;
 
@@ -978,6 +996,371 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn upgrade_postings_lists() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let database = Database::open_or_create(dir.path()).unwrap();
+        let db = &database;
+
+        // an index created by a previous version has no postings lists version
+        let (sender, _receiver) = crossbeam_channel::bounded(100);
+        let index = store::create(&db.env, &db.update_env, "test", sender).unwrap();
+
+        let doc_index = DocIndex {
+            document_id: DocumentId(42),
+            attribute: 1,
+            word_index: 3,
+            char_index: 12,
+            char_length: 5,
+        };
+
+        // the postings lists were stored as the documents indexes memory layout
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&doc_index.document_id.0.to_ne_bytes());
+        bytes.extend_from_slice(&doc_index.attribute.to_ne_bytes());
+        bytes.extend_from_slice(&doc_index.word_index.to_ne_bytes());
+        bytes.extend_from_slice(&doc_index.char_index.to_ne_bytes());
+        bytes.extend_from_slice(&doc_index.char_length.to_ne_bytes());
+
+        let mut writer = db.main_write_txn().unwrap();
+        index
+            .postings_lists
+            .postings_lists
+            .put(&mut writer, b"hello", &bytes)
+            .unwrap();
+        store::upgrade(&mut writer, &index).unwrap();
+        writer.commit().unwrap();
+
+        let reader = db.main_read_txn().unwrap();
+        let postings = index.postings_lists.postings_list(&reader, b"hello").unwrap();
+        assert_eq!(postings.unwrap().matches.as_slice(), &[doc_index]);
+
+        let version = index.main.postings_lists_version(&reader).unwrap();
+        assert_eq!(version, Some(store::POSTINGS_LISTS_VERSION));
+    }
+
+    #[test]
+    fn upgrade_schema() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let database = Database::open_or_create(dir.path()).unwrap();
+        let db = &database;
+
+        // an index created by a previous version has no schema version
+        let (sender, _receiver) = crossbeam_channel::bounded(100);
+        let index = store::create(&db.env, &db.update_env, "test", sender).unwrap();
+
+        // the properties were only made of the displayed, indexed and ranked flags
+        let mut attributes = indexmap::IndexMap::new();
+        attributes.insert("id", (true, false, false));
+        attributes.insert("name", (true, true, false));
+        let bytes = bincode::serialize(&("id", attributes)).unwrap();
+
+        let mut writer = db.main_write_txn().unwrap();
+        index
+            .main
+            .main
+            .put::<_, Str, heed::types::ByteSlice>(&mut writer, "schema", &bytes)
+            .unwrap();
+        store::upgrade(&mut writer, &index).unwrap();
+        writer.commit().unwrap();
+
+        let mut builder = SchemaBuilder::with_identifier("id");
+        builder.new_attribute("id", DISPLAYED);
+        builder.new_attribute("name", DISPLAYED | INDEXED);
+        let schema = builder.build();
+
+        let reader = db.main_read_txn().unwrap();
+        assert_eq!(index.main.schema(&reader).unwrap(), Some(schema));
+
+        let version = index.main.schema_version(&reader).unwrap();
+        assert_eq!(version, Some(store::SCHEMA_VERSION));
+    }
+
+    #[test]
+    fn upgrade_analyzer() {
+        use crate::{Analyzer, Language};
+
+        let dir = tempfile::tempdir().unwrap();
+
+        let database = Database::open_or_create(dir.path()).unwrap();
+        let db = &database;
+
+        // an index created by a previous version has no analyzer version
+        let (sender, _receiver) = crossbeam_channel::bounded(100);
+        let index = store::create(&db.env, &db.update_env, "test", sender).unwrap();
+
+        // the analyzer was only made of the stemmer
+        let bytes = bincode::serialize(&Some(Language::French)).unwrap();
+
+        let mut writer = db.main_write_txn().unwrap();
+        index
+            .main
+            .main
+            .put::<_, Str, heed::types::ByteSlice>(&mut writer, "analyzer", &bytes)
+            .unwrap();
+        store::upgrade(&mut writer, &index).unwrap();
+        writer.commit().unwrap();
+
+        let analyzer = Analyzer {
+            stemmer: Some(Language::French),
+            ..Analyzer::default()
+        };
+
+        let reader = db.main_read_txn().unwrap();
+        assert_eq!(index.main.analyzer(&reader).unwrap(), Some(analyzer));
+
+        let version = index.main.analyzer_version(&reader).unwrap();
+        assert_eq!(version, Some(store::ANALYZER_VERSION));
+    }
+
+    #[test]
+    fn upgrade_filter_values() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let database = Database::open_or_create(dir.path()).unwrap();
+        let db = &database;
+
+        // an index created by a previous version has no filter values
+        let (sender, _receiver) = crossbeam_channel::bounded(100);
+        let index = store::create(&db.env, &db.update_env, "test", sender).unwrap();
+
+        let mut writer = db.main_write_txn().unwrap();
+        index
+            .documents_fields
+            .put_document_field(&mut writer, DocumentId(42), SchemaAttr(1), br#""Drama""#)
+            .unwrap();
+        store::upgrade(&mut writer, &index).unwrap();
+        writer.commit().unwrap();
+
+        let reader = db.main_read_txn().unwrap();
+        let documents = index
+            .documents_fields
+            .filter_value_documents(&reader, SchemaAttr(1), "drama")
+            .unwrap();
+        assert_eq!(
+            documents.map(|d| d.iter().collect::<Vec<_>>()),
+            Some(vec![42])
+        );
+
+        let version = index.main.filter_values_version(&reader).unwrap();
+        assert_eq!(version, Some(store::FILTER_VALUES_VERSION));
+    }
+
+    #[test]
+    fn filter_values_follow_documents() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let database = Database::open_or_create(dir.path()).unwrap();
+        let db = &database;
+
+        let (sender, receiver) = mpsc::sync_channel(100);
+        let update_fn = move |_name: &str, update: ProcessedUpdateResult| {
+            sender.send(update.update_id).unwrap()
+        };
+        let index = database.create_index("test").unwrap();
+
+        database.set_update_callback(Box::new(update_fn));
+
+        let schema = {
+            let data = r#"
+                identifier = "id"
+
+                [attributes."name"]
+                displayed = true
+                indexed = true
+
+                [attributes."genre"]
+                displayed = true
+            "#;
+            toml::from_str(data).unwrap()
+        };
+
+        let mut update_writer = db.update_write_txn().unwrap();
+        let _update_id = index.schema_update(&mut update_writer, schema).unwrap();
+        update_writer.commit().unwrap();
+
+        let doc1 = serde_json::json!({ "id": 1, "name": "Alien", "genre": "Horror" });
+        let doc2 = serde_json::json!({ "id": 2, "name": "Heat", "genre": "Drama" });
+        let doc3 = serde_json::json!({ "id": 3, "name": "Fargo", "genre": "Drama" });
+
+        let mut additions = index.documents_addition();
+        additions.update_document(doc1);
+        additions.update_document(doc2);
+        additions.update_document(doc3);
+
+        let mut update_writer = db.update_write_txn().unwrap();
+        let update_id = additions.finalize(&mut update_writer).unwrap();
+        update_writer.commit().unwrap();
+
+        // block until the transaction is processed
+        let _ = receiver.iter().find(|id| *id == update_id);
+
+        let reader = db.main_read_txn().unwrap();
+        let schema = index.main.schema(&reader).unwrap().unwrap();
+        let genre = schema.attribute("genre").unwrap();
+        let drama = index
+            .documents_fields
+            .filter_value_documents(&reader, genre, "drama")
+            .unwrap()
+            .unwrap();
+        assert_eq!(drama.len(), 2);
+        reader.abort();
+
+        let mut deletion = index.documents_deletion();
+        deletion.delete_document_by_id(DocumentId(drama.min().unwrap()));
+
+        let mut update_writer = db.update_write_txn().unwrap();
+        let update_id = deletion.finalize(&mut update_writer).unwrap();
+        update_writer.commit().unwrap();
+
+        // block until the transaction is processed
+        let _ = receiver.iter().find(|id| *id == update_id);
+
+        let reader = db.main_read_txn().unwrap();
+        let documents = index
+            .documents_fields
+            .filter_value_documents(&reader, genre, "drama")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            documents.iter().collect::<Vec<_>>(),
+            vec![drama.max().unwrap()]
+        );
+
+        let documents = index
+            .documents_fields
+            .filter_value_documents(&reader, genre, "horror")
+            .unwrap();
+        assert_eq!(documents.map(|d| d.len()), Some(1));
+    }
+
+    #[test]
+    fn original_forms_follow_documents() {
+        use crate::raw_indexer::{altered_forms_key, original_form_key};
+
+        let dir = tempfile::tempdir().unwrap();
+
+        let database = Database::open_or_create(dir.path()).unwrap();
+        let db = &database;
+
+        let (sender, receiver) = mpsc::sync_channel(100);
+        let update_fn = move |_name: &str, update: ProcessedUpdateResult| {
+            sender.send(update.update_id).unwrap()
+        };
+        let index = database.create_index("test").unwrap();
+
+        database.set_update_callback(Box::new(update_fn));
+
+        let schema = {
+            let data = r#"
+                identifier = "id"
+
+                [attributes."name"]
+                displayed = true
+                indexed = true
+            "#;
+            toml::from_str(data).unwrap()
+        };
+
+        let mut update_writer = db.update_write_txn().unwrap();
+        let _update_id = index.schema_update(&mut update_writer, schema).unwrap();
+        update_writer.commit().unwrap();
+
+        let doc1 = serde_json::json!({ "id": 1, "name": "Alien" });
+        let doc2 = serde_json::json!({ "id": 2, "name": "ALIEN" });
+
+        let mut additions = index.documents_addition();
+        additions.update_document(doc1);
+        additions.update_document(doc2);
+
+        let mut update_writer = db.update_write_txn().unwrap();
+        let update_id = additions.finalize(&mut update_writer).unwrap();
+        update_writer.commit().unwrap();
+
+        // block until the transaction is processed
+        let _ = receiver.iter().find(|id| *id == update_id);
+
+        let reader = db.main_read_txn().unwrap();
+        let original_forms = index.original_forms_postings_lists;
+        let alien = original_forms
+            .postings_docids(&reader, &original_form_key("Alien"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(alien.len(), 1);
+        let altered = original_forms
+            .postings_docids(&reader, &altered_forms_key("alien"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(altered.len(), 2);
+
+        // the original forms are neither indexed words nor documents words
+        let key = original_form_key("Alien");
+        let postings = index.postings_lists.postings_list(&reader, &key).unwrap();
+        assert!(postings.is_none());
+        let words = index.main.words_fst(&reader).unwrap().unwrap();
+        assert_eq!(words.stream().into_strs().unwrap(), vec!["alien"]);
+        let id = DocumentId(alien.min().unwrap());
+        let words = index.docs_words.doc_words(&reader, id).unwrap().unwrap();
+        assert_eq!(words.stream().into_strs().unwrap(), vec!["alien"]);
+        reader.abort();
+
+        let mut deletion = index.documents_deletion();
+        deletion.delete_document_by_id(id);
+
+        let mut update_writer = db.update_write_txn().unwrap();
+        let update_id = deletion.finalize(&mut update_writer).unwrap();
+        update_writer.commit().unwrap();
+
+        // block until the transaction is processed
+        let _ = receiver.iter().find(|id| *id == update_id);
+
+        let reader = db.main_read_txn().unwrap();
+        let alien = original_forms
+            .postings_docids(&reader, &original_form_key("Alien"))
+            .unwrap();
+        assert!(alien.is_none());
+        let altered = original_forms
+            .postings_docids(&reader, &altered_forms_key("alien"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(altered.len(), 1);
+        assert!(!altered.contains(id.0));
+    }
+
+    #[test]
+    fn open_index_without_infix_nor_phonetic_postings_lists() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let database = Database::open_or_create(dir.path()).unwrap();
+        let db = &database;
+
+        // the stores of an index created by a previous version
+        let env = &db.env;
+        env.create_poly_database(Some("store-test")).unwrap();
+        env.create_database::<Str, Unit>(Some("store-test-postings-lists")).unwrap();
+        env.create_database::<Str, Unit>(Some("store-test-documents-fields")).unwrap();
+        env.create_database::<Str, Unit>(Some("store-test-documents-fields-counts")).unwrap();
+        env.create_database::<Str, Unit>(Some("store-test-synonyms")).unwrap();
+        env.create_database::<Str, Unit>(Some("store-test-docs-words")).unwrap();
+        let update_env = &db.update_env;
+        update_env.create_database::<Str, Unit>(Some("store-test-updates")).unwrap();
+        update_env.create_database::<Str, Unit>(Some("store-test-updates-results")).unwrap();
+
+        let (sender, _receiver) = crossbeam_channel::bounded(100);
+        let index = store::open(env, update_env, "test", sender).unwrap();
+        let index = index.expect("the index must be opened");
+
+        let reader = db.main_read_txn().unwrap();
+        let postings = index.infix_postings_lists.postings_list(&reader, b"hello");
+        assert!(postings.unwrap().is_none());
+        let postings = index.phonetic_postings_lists.postings_list(&reader, b"hello");
+        assert!(postings.unwrap().is_none());
+        let postings = index.original_forms_postings_lists.postings_list(&reader, b"hello");
+        assert!(postings.unwrap().is_none());
+    }
+
     #[test]
     fn check_number_ordering() {
         let dir = tempfile::tempdir().unwrap();
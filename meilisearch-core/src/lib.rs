@@ -2,6 +2,7 @@
 #[macro_use]
 extern crate assert_matches;
 
+mod analyzer;
 mod automaton;
 pub mod criterion;
 mod database;
@@ -9,7 +10,9 @@ mod distinct_map;
 mod error;
 mod levenshtein;
 mod number;
+mod phonetic;
 mod query_builder;
+mod query_rules;
 mod ranked_map;
 mod raw_document;
 pub mod raw_indexer;
@@ -18,29 +21,37 @@ pub mod serde;
 pub mod store;
 mod update;
 
+pub use self::analyzer::{language_analyzer, stop_words_preset, Analyzer, Language};
 pub use self::database::{BoxUpdateFn, Database, MainT, UpdateT};
 pub use self::error::{Error, MResult};
 pub use self::number::{Number, ParseNumberError};
+pub use self::query_rules::{matching_rule, PatternMatch, PinnedDocument, QueryRule};
 pub use self::ranked_map::RankedMap;
 pub use self::raw_document::RawDocument;
 pub use self::store::Index;
 pub use self::update::{EnqueuedUpdateResult, ProcessedUpdateResult, UpdateStatus, UpdateType};
-pub use meilisearch_types::{DocIndex, DocumentId, Highlight};
+pub use meilisearch_types::{DocIndex, DocumentId, Highlight, MatchKind};
 
 #[doc(hidden)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct TmpMatch {
     pub query_index: u32,
     pub distance: u8,
+    pub is_phonetic: bool,
     pub attribute: u16,
     pub word_index: u16,
     pub is_exact: bool,
+    pub is_exact_form: bool,
+    pub documents_frequency: u32,
+    pub synonym_penalty: u8,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Document {
     pub id: DocumentId,
     pub highlights: Vec<Highlight>,
+    /// The query words the highlights refer to by their query index.
+    pub query_words: Vec<String>,
 
     #[cfg(test)]
     pub matches: Vec<TmpMatch>,
@@ -48,31 +59,40 @@ pub struct Document {
 
 impl Document {
     #[cfg(not(test))]
-    fn from_raw(raw: RawDocument) -> Document {
+    fn from_raw(raw: RawDocument, query_words: &[String]) -> Document {
         Document {
             id: raw.id,
             highlights: raw.highlights,
+            query_words: query_words.to_vec(),
         }
     }
 
     #[cfg(test)]
-    fn from_raw(raw: RawDocument) -> Document {
+    fn from_raw(raw: RawDocument, query_words: &[String]) -> Document {
         let len = raw.query_index().len();
         let mut matches = Vec::with_capacity(len);
 
         let query_index = raw.query_index();
         let distance = raw.distance();
+        let is_phonetic = raw.is_phonetic();
         let attribute = raw.attribute();
         let word_index = raw.word_index();
         let is_exact = raw.is_exact();
+        let is_exact_form = raw.is_exact_form();
+        let documents_frequency = raw.documents_frequency();
+        let synonym_penalty = raw.synonym_penalty();
 
         for i in 0..len {
             let match_ = TmpMatch {
                 query_index: query_index[i],
                 distance: distance[i],
+                is_phonetic: is_phonetic[i],
                 attribute: attribute[i],
                 word_index: word_index[i],
                 is_exact: is_exact[i],
+                is_exact_form: is_exact_form[i],
+                documents_frequency: documents_frequency[i],
+                synonym_penalty: synonym_penalty[i],
             };
             matches.push(match_);
         }
@@ -81,6 +101,7 @@ impl Document {
             id: raw.id,
             matches,
             highlights: raw.highlights,
+            query_words: query_words.to_vec(),
         }
     }
 }
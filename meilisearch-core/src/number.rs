@@ -13,6 +13,17 @@ pub enum Number {
     Float(OrderedFloat<f64>),
 }
 
+impl Number {
+    /// Converts this number into a float, large integers lose precision.
+    pub fn to_f64(self) -> f64 {
+        match self {
+            Number::Unsigned(n) => n as f64,
+            Number::Signed(n) => n as f64,
+            Number::Float(n) => n.into_inner(),
+        }
+    }
+}
+
 impl FromStr for Number {
     type Err = ParseNumberError;
 
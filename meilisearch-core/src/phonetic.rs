@@ -0,0 +1,156 @@
+//! A phonetic encoding of the words, inspired by the primary key of the Double Metaphone
+//! algorithm, that gives the same key to words that sound alike (e.g. "Catherine" and "Kathryn").
+
+/// The maximum length of a phonetic key, longer keys are truncated.
+const PHONETIC_KEY_LENGTH: usize = 4;
+
+fn is_vowel(c: char) -> bool {
+    match c {
+        'a' | 'e' | 'i' | 'o' | 'u' | 'y' => true,
+        _ => false,
+    }
+}
+
+/// Returns the phonetic key of a word or `None` if the word
+/// is not only made of latin letters once deunicoded.
+pub fn phonetic_key(word: &str) -> Option<String> {
+    let word = deunicode::deunicode_with_tofu(word, "").to_lowercase();
+    if word.is_empty() || !word.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    let chars: Vec<char> = word.chars().collect();
+    let at = |i: usize| chars.get(i).cloned().unwrap_or('\0');
+    let mut key = String::with_capacity(PHONETIC_KEY_LENGTH);
+
+    // the first letters of these groups are silent
+    let mut i = match (at(0), at(1)) {
+        ('k', 'n') | ('g', 'n') | ('p', 'n') | ('w', 'r') | ('a', 'e') => 1,
+        _ => 0,
+    };
+
+    while i < chars.len() {
+        let c = chars[i];
+        let (next, after) = (at(i + 1), at(i + 2));
+
+        // doubled letters are pronounced once
+        if i > 0 && c == at(i - 1) && c != 'c' {
+            i += 1;
+            continue;
+        }
+
+        let mut skip = 1;
+        let code = match c {
+            'a' | 'e' | 'i' | 'o' | 'u' | 'y' if i == 0 => "A",
+            'a' | 'e' | 'i' | 'o' | 'u' => "",
+            'b' if i > 0 && at(i - 1) == 'm' && next == '\0' => "",
+            'p' if next == 'h' => {
+                skip = 2;
+                "F"
+            }
+            'b' | 'p' => "P",
+            'c' if next == 'h' => {
+                skip = 2;
+                "X"
+            }
+            'c' if next == 'i' && after == 'a' => "X",
+            'c' if next == 'e' || next == 'i' || next == 'y' => "S",
+            'c' if next == 'k' || next == 'q' => {
+                skip = 2;
+                "K"
+            }
+            'c' | 'k' | 'q' => "K",
+            'd' if next == 'g' && (after == 'e' || after == 'i' || after == 'y') => {
+                skip = 2;
+                "J"
+            }
+            't' if next == 'h' => {
+                skip = 2;
+                "0"
+            }
+            't' if next == 'i' && (after == 'a' || after == 'o') => "X",
+            't' if next == 'c' && after == 'h' => "",
+            'd' | 't' => "T",
+            'f' | 'v' => "F",
+            'g' if next == 'h' => {
+                skip = 2;
+                if i == 0 { "K" } else { "" }
+            }
+            'g' if next == 'n' => "",
+            'g' if next == 'e' || next == 'i' || next == 'y' => "J",
+            'g' => "K",
+            'h' if (i == 0 || is_vowel(at(i - 1))) && is_vowel(next) => "H",
+            'h' => "",
+            'j' => "J",
+            'l' => "L",
+            'm' => "M",
+            'n' => "N",
+            'r' => "R",
+            's' if next == 'h' => {
+                skip = 2;
+                "X"
+            }
+            's' if next == 'i' && (after == 'a' || after == 'o') => "X",
+            's' | 'z' => "S",
+            'w' if i == 0 && next == 'h' => {
+                skip = 2;
+                "W"
+            }
+            'w' if is_vowel(next) => "W",
+            'y' if is_vowel(next) => "Y",
+            'w' | 'y' => "",
+            'x' if i == 0 => "S",
+            'x' => "KS",
+            _ => "",
+        };
+
+        for code in code.chars() {
+            // consecutive identical sounds are encoded once
+            if !key.ends_with(code) {
+                key.push(code);
+            }
+        }
+
+        if key.len() >= PHONETIC_KEY_LENGTH {
+            key.truncate(PHONETIC_KEY_LENGTH);
+            break;
+        }
+
+        i += skip;
+    }
+
+    if key.is_empty() {
+        None
+    } else {
+        Some(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_sounding_alike() {
+        assert_eq!(phonetic_key("Catherine"), Some(String::from("K0RN")));
+        assert_eq!(phonetic_key("Kathryn"), Some(String::from("K0RN")));
+
+        assert_eq!(phonetic_key("Smith"), phonetic_key("Smyth"));
+        assert_eq!(phonetic_key("Philip"), phonetic_key("Filip"));
+        assert_eq!(phonetic_key("Jon"), phonetic_key("John"));
+        assert_eq!(phonetic_key("knight"), phonetic_key("nite"));
+        assert_eq!(phonetic_key("Zoë"), phonetic_key("Zoe"));
+    }
+
+    #[test]
+    fn names_sounding_differently() {
+        assert_ne!(phonetic_key("Catherine"), phonetic_key("Caroline"));
+        assert_ne!(phonetic_key("Smith"), phonetic_key("Schmidt"));
+    }
+
+    #[test]
+    fn not_a_latin_word() {
+        assert_eq!(phonetic_key("iphone7"), None);
+        assert_eq!(phonetic_key(""), None);
+    }
+}
@@ -1,4 +1,4 @@
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::ops::Range;
 use std::rc::Rc;
@@ -6,25 +6,38 @@ use std::time::{Duration, Instant};
 use std::{cmp, mem};
 
 use fst::{IntoStreamer, Streamer};
+use rayon::prelude::*;
+use roaring::RoaringTreemap;
 use sdset::SetBuf;
 use slice_group_by::{GroupBy, GroupByMut};
 
 use crate::database::MainT;
-use crate::automaton::{Automaton, AutomatonGroup, AutomatonProducer, QueryEnhancer};
+use crate::automaton::{
+    Automaton, AutomatonGroup, AutomatonKind, AutomatonProducer, QueryEnhancer,
+};
 use crate::distinct_map::{BufferedDistinctMap, DistinctMap};
 use crate::levenshtein::prefix_damerau_levenshtein;
 use crate::raw_document::{raw_documents_from, RawDocument};
-use crate::{criterion::Criteria, Document, DocumentId, Highlight, TmpMatch};
-use crate::{reordered_attrs::ReorderedAttrs, store, MResult};
+use crate::raw_indexer::{altered_forms_key, original_form_key};
+use crate::{criterion::Criteria, Document, DocumentId, Highlight, Language, MatchKind, TmpMatch};
+use crate::{reordered_attrs::ReorderedAttrs, store, DocIndex, MResult};
+
+/// The maximum number of typos the automatons accept in a word.
+const MAX_DISTANCE: u8 = 2;
 
 pub struct QueryBuilder<'c, 'f, 'd> {
     criteria: Criteria<'c>,
     searchable_attrs: Option<ReorderedAttrs>,
+    candidates: Option<RoaringTreemap>,
     filter: Option<Box<dyn Fn(DocumentId) -> bool + 'f>>,
     distinct: Option<(Box<dyn Fn(DocumentId) -> Option<u64> + 'd>, usize)>,
     timeout: Option<Duration>,
+    language: Option<Language>,
     main_store: store::Main,
     postings_lists_store: store::PostingsLists,
+    infix_postings_lists_store: store::PostingsLists,
+    phonetic_postings_lists_store: store::PostingsLists,
+    original_forms_postings_lists_store: store::PostingsLists,
     documents_fields_counts_store: store::DocumentsFieldsCounts,
     synonyms_store: store::Synonyms,
 }
@@ -139,84 +152,297 @@ fn multiword_rewrite_matches(
     SetBuf::new_unchecked(padded_matches)
 }
 
+/// A word of the words FST accepted by an automaton.
+struct WordMatch {
+    word: Vec<u8>,
+    distance: u8,
+    is_phonetic: bool,
+    is_exact: bool,
+    covered_area: usize,
+}
+
+fn automaton_words(words: &fst::Set, automaton: &Automaton) -> Vec<WordMatch> {
+    let Automaton {
+        is_exact,
+        query_len,
+        query,
+        kind,
+        ..
+    } = automaton;
+    let dfa = automaton.dfa();
+
+    let mut words_matches = Vec::new();
+    let mut stream = words.search(&dfa).into_stream();
+    while let Some(input) = stream.next() {
+        // a phonetic match is not a typo but it is never exact,
+        // the found word only sounds like the query word
+        let is_phonetic = *kind == AutomatonKind::Phonetic;
+        let distance = if is_phonetic { 0 } else { dfa.eval(input).to_u8() };
+        let is_exact = *is_exact && !is_phonetic && distance == 0 && input.len() == *query_len;
+
+        // an infix match is found in the middle of a word and a phonetic
+        // match does not share its letters, the whole word is highlighted
+        let covered_area = if *kind != AutomatonKind::Word {
+            usize::max_value()
+        } else if *query_len > input.len() {
+            input.len()
+        } else {
+            prefix_damerau_levenshtein(query.as_bytes(), input).1
+        };
+
+        words_matches.push(WordMatch {
+            word: input.to_vec(),
+            distance,
+            is_phonetic,
+            is_exact,
+            covered_area,
+        });
+    }
+
+    words_matches
+}
+
+fn match_kind(automaton: &Automaton, word_match: &WordMatch) -> MatchKind {
+    if automaton.is_synonym {
+        MatchKind::Synonym
+    } else if word_match.is_phonetic {
+        MatchKind::Phonetic
+    } else if word_match.distance != 0 {
+        MatchKind::Typo(word_match.distance)
+    } else if automaton.is_prefix && word_match.word.len() > automaton.query_len {
+        MatchKind::Prefix
+    } else {
+        MatchKind::Exact
+    }
+}
+
+/// Returns the query words the automatons have been derived from,
+/// indexed by the query index of the automatons.
+fn query_words(automatons: &[AutomatonGroup]) -> Vec<String> {
+    let mut query_words = Vec::new();
+    for automaton in automatons.iter().flat_map(|group| &group.automatons) {
+        if query_words.len() <= automaton.index {
+            query_words.resize(automaton.index + 1, String::new());
+        }
+        query_words[automaton.index] = automaton.query_word.clone();
+    }
+    query_words
+}
+
+/// Returns the words accepted by each automaton of the group.
+fn group_words(
+    words: &fst::Set,
+    infix_words: &fst::Set,
+    phonetic_words: &fst::Set,
+    group: &AutomatonGroup,
+) -> Vec<Vec<WordMatch>> {
+    // walking the words FST is the costly part of the
+    // search, every automaton is evaluated in parallel
+    group
+        .automatons
+        .par_iter()
+        .map(|automaton| match automaton.kind {
+            AutomatonKind::Word => automaton_words(words, automaton),
+            AutomatonKind::Infix => automaton_words(infix_words, automaton),
+            AutomatonKind::Phonetic => automaton_words(phonetic_words, automaton),
+        })
+        .collect()
+}
+
+/// Returns the number of query indices the matches can have
+/// once they have been rewritten by the query enhancer.
+fn rewritten_query_len(
+    automatons_groups: &[AutomatonGroup],
+    query_enhancer: &QueryEnhancer,
+) -> usize {
+    let mut query_indexes = HashSet::new();
+    for automaton in automatons_groups.iter().flat_map(|group| &group.automatons) {
+        query_indexes.extend(query_enhancer.replacement(automaton.index as u32));
+    }
+    query_indexes.len()
+}
+
+/// Returns the documents containing at least one of the words accepted with at
+/// most `max_distance` typos, only the documents ids bitmaps are read and the
+/// phrase queries are not checked, these documents are a superset of the ones
+/// that would be found by only fetching these words.
+fn documents_within_typos(
+    reader: &heed::RoTxn<MainT>,
+    automatons_groups: &[AutomatonGroup],
+    groups_words: &[Vec<Vec<WordMatch>>],
+    candidates: Option<&RoaringTreemap>,
+    max_distance: u8,
+    postings_lists_store: store::PostingsLists,
+    infix_postings_lists_store: store::PostingsLists,
+    phonetic_postings_lists_store: store::PostingsLists,
+) -> MResult<RoaringTreemap> {
+    let mut documents = RoaringTreemap::new();
+
+    for (group, group_words) in automatons_groups.iter().zip(groups_words) {
+        for (automaton, words_matches) in group.automatons.iter().zip(group_words) {
+            let postings_lists_store = match automaton.kind {
+                AutomatonKind::Word => postings_lists_store,
+                AutomatonKind::Infix => infix_postings_lists_store,
+                AutomatonKind::Phonetic => phonetic_postings_lists_store,
+            };
+
+            for word_match in words_matches.iter().filter(|w| w.distance <= max_distance) {
+                let word = word_match.word.as_slice();
+                if let Some(docids) = postings_lists_store.postings_docids(reader, word)? {
+                    documents.union_with(&docids);
+                }
+            }
+        }
+    }
+
+    if let Some(candidates) = candidates {
+        documents.intersect_with(candidates);
+    }
+
+    Ok(documents)
+}
+
+// The positions of the documents words that are in the original form of a query word.
+enum OriginalForm {
+    Within(Option<SetBuf<DocIndex>>),
+    Outside(Option<SetBuf<DocIndex>>),
+}
+
+impl OriginalForm {
+    fn contains(&self, doc_index: &DocIndex) -> bool {
+        match self {
+            OriginalForm::Within(positions) => positions
+                .as_ref()
+                .map_or(false, |p| p.binary_search(doc_index).is_ok()),
+            OriginalForm::Outside(positions) => positions
+                .as_ref()
+                .map_or(true, |p| p.binary_search(doc_index).is_err()),
+        }
+    }
+}
+
 fn fetch_raw_documents(
     reader: &heed::RoTxn<MainT>,
     automatons_groups: &[AutomatonGroup],
+    groups_words: &[Vec<Vec<WordMatch>>],
     query_enhancer: &QueryEnhancer,
     searchables: Option<&ReorderedAttrs>,
-    main_store: store::Main,
+    candidates: Option<&RoaringTreemap>,
     postings_lists_store: store::PostingsLists,
+    infix_postings_lists_store: store::PostingsLists,
+    phonetic_postings_lists_store: store::PostingsLists,
+    original_forms_postings_lists_store: store::PostingsLists,
     documents_fields_counts_store: store::DocumentsFieldsCounts,
 ) -> MResult<Vec<RawDocument>> {
     let mut matches = Vec::new();
     let mut highlights = Vec::new();
 
-    for group in automatons_groups {
+    for (group, group_words) in automatons_groups.iter().zip(groups_words) {
         let AutomatonGroup {
             is_phrase_query,
             automatons,
         } = group;
         let phrase_query_len = automatons.len();
 
-        let mut tmp_matches = Vec::new();
-        for (id, automaton) in automatons.into_iter().enumerate() {
-            let Automaton {
-                index,
-                is_exact,
-                query_len,
-                query,
-                ..
-            } = automaton;
-            let dfa = automaton.dfa();
-
-            let words = match main_store.words_fst(reader)? {
-                Some(words) => words,
-                None => return Ok(Vec::new()),
+        // a read transaction can not be shared between threads,
+        // the postings lists are retrieved sequentially
+        let mut postings_lists = Vec::new();
+        let mut original_forms = Vec::with_capacity(automatons.len());
+        let automatons_words = automatons.iter().zip(group_words);
+        for (id, (automaton, words_matches)) in automatons_words.enumerate() {
+            let original_form = match &automaton.original_form {
+                // the positions where the query word appears with its accents and case
+                Some(form) if *form != form.to_lowercase() => {
+                    let key = original_form_key(form);
+                    let positions =
+                        original_forms_postings_lists_store.postings_list(reader, &key)?;
+                    Some(OriginalForm::Within(positions.map(|p| p.matches)))
+                }
+                // the positions where the query word appears but not as is
+                Some(form) => {
+                    let key = altered_forms_key(form);
+                    let positions =
+                        original_forms_postings_lists_store.postings_list(reader, &key)?;
+                    Some(OriginalForm::Outside(positions.map(|p| p.matches)))
+                }
+                None => None,
             };
+            original_forms.push(original_form);
 
-            let mut stream = words.search(&dfa).into_stream();
-            while let Some(input) = stream.next() {
-                let distance = dfa.eval(input).to_u8();
-                let is_exact = *is_exact && distance == 0 && input.len() == *query_len;
+            let postings_lists_store = match automaton.kind {
+                AutomatonKind::Word => postings_lists_store,
+                AutomatonKind::Infix => infix_postings_lists_store,
+                AutomatonKind::Phonetic => phonetic_postings_lists_store,
+            };
 
-                let covered_area = if *query_len > input.len() {
-                    input.len()
-                } else {
-                    prefix_damerau_levenshtein(query.as_bytes(), input).1
-                };
+            for word_match in words_matches {
+                let word = word_match.word.as_slice();
 
-                let doc_indexes = match postings_lists_store.postings_list(reader, input)? {
-                    Some(doc_indexes) => doc_indexes,
-                    None => continue,
+                // the positions of the documents that are not candidates are not decoded
+                let postings = match candidates {
+                    Some(candidates) => {
+                        postings_lists_store.postings_list_within(reader, word, candidates)?
+                    }
+                    None => postings_lists_store.postings_list(reader, word)?,
                 };
 
-                tmp_matches.reserve(doc_indexes.len());
+                if let Some(postings) = postings {
+                    // the documents that are not candidates are
+                    // discarded by intersecting the bitmaps
+                    if let Some(candidates) = candidates {
+                        if postings.docids.is_disjoint(candidates) {
+                            continue;
+                        }
+                    }
+                    let frequency = postings.docids.len();
+                    let frequency = u32::try_from(frequency).unwrap_or(u32::max_value());
+                    let matches = postings.matches;
+                    postings_lists.push((id, automaton.index, word_match, frequency, matches));
+                }
+            }
+        }
 
-                for di in doc_indexes.as_ref() {
+        let mut tmp_matches: Vec<_> = postings_lists
+            .par_iter()
+            .flat_map(|(id, index, word_match, frequency, doc_indexes)| {
+                let covered_area = word_match.covered_area;
+                let covered_area = u16::try_from(covered_area).unwrap_or(u16::max_value());
+                let original_form = original_forms[*id].as_ref();
+                let query_index = u16::try_from(*index).unwrap_or(u16::max_value());
+                let kind = match_kind(&automatons[*id], word_match);
+                let mut tmp_matches = Vec::with_capacity(doc_indexes.len());
+
+                for di in doc_indexes.iter() {
                     let attribute = searchables.map_or(Some(di.attribute), |r| r.get(di.attribute));
                     if let Some(attribute) = attribute {
                         let match_ = TmpMatch {
                             query_index: *index as u32,
-                            distance,
+                            distance: word_match.distance,
+                            is_phonetic: word_match.is_phonetic,
                             attribute,
                             word_index: di.word_index,
-                            is_exact,
+                            is_exact: word_match.is_exact,
+                            is_exact_form: word_match.is_exact
+                                && original_form.map_or(false, |f| f.contains(di)),
+                            documents_frequency: *frequency,
+                            synonym_penalty: automatons[*id].synonym_penalty,
                         };
 
-                        let covered_area = u16::try_from(covered_area).unwrap_or(u16::max_value());
-                        let covered_area = cmp::min(covered_area, di.char_length);
-
                         let highlight = Highlight {
                             attribute: di.attribute,
                             char_index: di.char_index,
-                            char_length: covered_area,
+                            char_length: cmp::min(covered_area, di.char_length),
+                            query_index,
+                            kind,
                         };
 
-                        tmp_matches.push((di.document_id, id, match_, highlight));
+                        tmp_matches.push((di.document_id, *id, match_, highlight));
                     }
                 }
-            }
-        }
+
+                tmp_matches
+            })
+            .collect();
 
         if *is_phrase_query {
             tmp_matches.sort_unstable_by_key(|(id, _, m, _)| (*id, m.attribute, m.word_index));
@@ -272,16 +498,168 @@ fn fetch_raw_documents(
     Ok(raw_documents_from(matches, highlights, fields_counts))
 }
 
+/// Sorts the documents by applying the criteria one after the other, each criterion
+/// only splitting the groups of documents the previous ones considered equal.
+///
+/// Only the documents that can end up in the requested range are sorted: the group
+/// that straddles the end of the range is partially sorted using a bounded heap and
+/// the sort stops as soon as the range is settled, when every document of it is
+/// alone in its group. The documents after the end of the range are left unordered.
+///
+/// The documents are not streamed, they must all have been fetched beforehand. Only the
+/// documents that contain words with too many typos to end up in the range are not
+/// fetched, when the first criterion bounds the typos, see `Criterion::bounds_typos`.
+fn bucket_sort(documents: &mut [RawDocument], range: Range<usize>, criteria: &Criteria) {
+    if range.start >= range.end {
+        return;
+    }
+
+    let mut groups = vec![documents];
+
+    'criteria: for criterion in criteria.as_ref() {
+        let tmp_groups = mem::replace(&mut groups, Vec::new());
+        let mut documents_seen = 0;
+
+        for group in tmp_groups {
+            // if this group does not overlap with the requested range,
+            // push it without sorting and splitting it
+            if documents_seen + group.len() < range.start {
+                documents_seen += group.len();
+                groups.push(group);
+                continue;
+            }
+
+            // only the documents before the end of the range must be sorted,
+            // the others are only interesting if they are equal to the last one
+            let needed = range.end - documents_seen;
+            let group = if needed < group.len() {
+                partial_sort_by(group, needed, |a, b| criterion.evaluate(a, b));
+
+                let equal_to_last = {
+                    let (sorted, unsorted) = group.split_at_mut(needed);
+                    let last = &sorted[needed - 1];
+                    let mut count = 0;
+                    for i in 0..unsorted.len() {
+                        if criterion.eq(last, &unsorted[i]) {
+                            unsorted.swap(count, i);
+                            count += 1;
+                        }
+                    }
+                    count
+                };
+
+                let (group, _) = group.split_at_mut(needed + equal_to_last);
+                group
+            } else {
+                group.sort_unstable_by(|a, b| criterion.evaluate(a, b));
+                group
+            };
+
+            for group in group.binary_group_by_mut(|a, b| criterion.eq(a, b)) {
+                documents_seen += group.len();
+                groups.push(group);
+
+                // we have sort enough documents if the last document sorted is after
+                // the end of the requested range, we can continue to the next criterion
+                if documents_seen >= range.end {
+                    break;
+                }
+            }
+
+            if documents_seen >= range.end {
+                break;
+            }
+        }
+
+        // the range is settled when the following criteria
+        // can no more change the order of its documents
+        let mut documents_seen = 0;
+        for group in &groups {
+            if documents_seen + group.len() > range.start && group.len() > 1 {
+                continue 'criteria;
+            }
+            documents_seen += group.len();
+        }
+
+        break;
+    }
+}
+
+/// Moves the `k` smallest elements, according to `compare`, at the start of the slice
+/// and sorts them, the order of the remaining elements is unspecified.
+///
+/// A bounded max-heap of `k` elements is maintained at the start of the slice,
+/// which means that only `O(n log k)` comparisons are done.
+fn partial_sort_by<T, F>(v: &mut [T], k: usize, mut compare: F)
+where
+    F: FnMut(&T, &T) -> cmp::Ordering,
+{
+    let k = cmp::min(k, v.len());
+    if k == 0 {
+        return;
+    }
+
+    // build a max-heap with the first k elements
+    for i in (0..k / 2).rev() {
+        sift_down(&mut v[..k], i, &mut compare);
+    }
+
+    // replace the biggest element of the heap each
+    // time a smaller one is found in the remaining ones
+    for i in k..v.len() {
+        if compare(&v[i], &v[0]) == cmp::Ordering::Less {
+            v.swap(0, i);
+            sift_down(&mut v[..k], 0, &mut compare);
+        }
+    }
+
+    v[..k].sort_unstable_by(compare);
+}
+
+fn sift_down<T, F>(heap: &mut [T], mut node: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> cmp::Ordering,
+{
+    loop {
+        let left = 2 * node + 1;
+        if left >= heap.len() {
+            break;
+        }
+
+        let right = left + 1;
+        let child = if right < heap.len()
+            && compare(&heap[left], &heap[right]) == cmp::Ordering::Less
+        {
+            right
+        } else {
+            left
+        };
+
+        if compare(&heap[node], &heap[child]) != cmp::Ordering::Less {
+            break;
+        }
+
+        heap.swap(node, child);
+        node = child;
+    }
+}
+
 impl<'c, 'f, 'd> QueryBuilder<'c, 'f, 'd> {
     pub fn new(
         main: store::Main,
         postings_lists: store::PostingsLists,
+        infix_postings_lists: store::PostingsLists,
+        phonetic_postings_lists: store::PostingsLists,
+        original_forms_postings_lists: store::PostingsLists,
         documents_fields_counts: store::DocumentsFieldsCounts,
         synonyms: store::Synonyms,
     ) -> QueryBuilder<'c, 'f, 'd> {
         QueryBuilder::with_criteria(
             main,
             postings_lists,
+            infix_postings_lists,
+            phonetic_postings_lists,
+            original_forms_postings_lists,
             documents_fields_counts,
             synonyms,
             Criteria::default(),
@@ -291,6 +669,9 @@ impl<'c, 'f, 'd> QueryBuilder<'c, 'f, 'd> {
     pub fn with_criteria(
         main: store::Main,
         postings_lists: store::PostingsLists,
+        infix_postings_lists: store::PostingsLists,
+        phonetic_postings_lists: store::PostingsLists,
+        original_forms_postings_lists: store::PostingsLists,
         documents_fields_counts: store::DocumentsFieldsCounts,
         synonyms: store::Synonyms,
         criteria: Criteria<'c>,
@@ -298,11 +679,16 @@ impl<'c, 'f, 'd> QueryBuilder<'c, 'f, 'd> {
         QueryBuilder {
             criteria,
             searchable_attrs: None,
+            candidates: None,
             filter: None,
             distinct: None,
             timeout: None,
+            language: None,
             main_store: main,
             postings_lists_store: postings_lists,
+            infix_postings_lists_store: infix_postings_lists,
+            phonetic_postings_lists_store: phonetic_postings_lists,
+            original_forms_postings_lists_store: original_forms_postings_lists,
             documents_fields_counts_store: documents_fields_counts,
             synonyms_store: synonyms,
         }
@@ -317,10 +703,22 @@ impl<'c, 'f, 'd> QueryBuilder<'c, 'f, 'd> {
         self.filter = Some(Box::new(function))
     }
 
+    /// Restricts the search to the given documents, the postings lists are
+    /// intersected with these candidates before the matches are retrieved.
+    pub fn with_candidates(&mut self, candidates: RoaringTreemap) {
+        self.candidates = Some(candidates)
+    }
+
     pub fn with_fetch_timeout(&mut self, timeout: Duration) {
         self.timeout = Some(timeout)
     }
 
+    /// Analyzes the query with the analyzer of the given language,
+    /// the one used to index the attributes in this language.
+    pub fn with_language(&mut self, language: Language) {
+        self.language = Some(language)
+    }
+
     pub fn with_distinct<F, K>(&mut self, function: F, size: usize)
     where
         F: Fn(DocumentId) -> Option<u64> + 'd,
@@ -352,8 +750,13 @@ impl<'c, 'f, 'd> QueryBuilder<'c, 'f, 'd> {
                 self.timeout,
                 self.criteria,
                 self.searchable_attrs,
+                self.candidates,
+                self.language,
                 self.main_store,
                 self.postings_lists_store,
+                self.infix_postings_lists_store,
+                self.phonetic_postings_lists_store,
+                self.original_forms_postings_lists_store,
                 self.documents_fields_counts_store,
                 self.synonyms_store,
             ),
@@ -365,8 +768,13 @@ impl<'c, 'f, 'd> QueryBuilder<'c, 'f, 'd> {
                 self.timeout,
                 self.criteria,
                 self.searchable_attrs,
+                self.candidates,
+                self.language,
                 self.main_store,
                 self.postings_lists_store,
+                self.infix_postings_lists_store,
+                self.phonetic_postings_lists_store,
+                self.original_forms_postings_lists_store,
                 self.documents_fields_counts_store,
                 self.synonyms_store,
             ),
@@ -385,9 +793,14 @@ fn raw_query<'c, FI>(
 
     criteria: Criteria<'c>,
     searchable_attrs: Option<ReorderedAttrs>,
+    candidates: Option<RoaringTreemap>,
+    language: Option<Language>,
 
     main_store: store::Main,
     postings_lists_store: store::PostingsLists,
+    infix_postings_lists_store: store::PostingsLists,
+    phonetic_postings_lists_store: store::PostingsLists,
+    original_forms_postings_lists_store: store::PostingsLists,
     documents_fields_counts_store: store::DocumentsFieldsCounts,
     synonyms_store: store::Synonyms,
 ) -> MResult<Vec<Document>>
@@ -409,8 +822,13 @@ where
             timeout,
             criteria,
             searchable_attrs,
+            candidates,
+            language,
             main_store,
             postings_lists_store,
+            infix_postings_lists_store,
+            phonetic_postings_lists_store,
+            original_forms_postings_lists_store,
             documents_fields_counts_store,
             synonyms_store,
         );
@@ -419,9 +837,21 @@ where
     let start_processing = Instant::now();
     let mut raw_documents_processed = Vec::with_capacity(range.len());
 
+    // the words FST is decoded only once for the whole query
+    let words = match main_store.words_fst(reader)? {
+        Some(words) => words,
+        None => return Ok(Vec::new()),
+    };
+    let infix_words = main_store.infix_words_fst(reader)?.unwrap_or_default();
+    let phonetic_words = main_store.phonetic_words_fst(reader)?.unwrap_or_default();
+
     let (automaton_producer, query_enhancer) = AutomatonProducer::new(
         reader,
         query,
+        &words,
+        &infix_words,
+        &phonetic_words,
+        language,
         main_store,
         postings_lists_store,
         synonyms_store,
@@ -429,57 +859,103 @@ where
 
     let automaton_producer = automaton_producer.into_iter();
     let mut automatons = Vec::new();
+    let mut groups_words = Vec::new();
+
+    // the documents with too many typos can only be left out
+    // when the first criterion ranks them after the others
+    let first_criterion = criteria.as_ref().first();
+    let bounds_typos =
+        range.start < range.end && first_criterion.map_or(false, |c| c.bounds_typos());
 
     // aggregate automatons groups by groups after time
     for auts in automaton_producer {
+        groups_words.push(group_words(&words, &infix_words, &phonetic_words, &auts));
         automatons.push(auts);
 
-        // we must retrieve the documents associated
-        // with the current automatons
-        let mut raw_documents = fetch_raw_documents(
-            reader,
-            &automatons,
-            &query_enhancer,
-            searchable_attrs.as_ref(),
-            main_store,
-            postings_lists_store,
-            documents_fields_counts_store,
-        )?;
-
-        // stop processing when time is running out
-        if let Some(timeout) = timeout {
-            if !raw_documents_processed.is_empty() && start_processing.elapsed() > timeout {
-                break;
+        // the documents only containing words with too many typos to be ranked
+        // in the requested range are not retrieved, when it can be proven
+        let mut raw_documents = None;
+        let query_len = rewritten_query_len(&automatons, &query_enhancer);
+        let max_distances = if bounds_typos { 0..MAX_DISTANCE } else { 0..0 };
+        for max_distance in max_distances {
+            let documents = documents_within_typos(
+                reader,
+                &automatons,
+                &groups_words,
+                candidates.as_ref(),
+                max_distance,
+                postings_lists_store,
+                infix_postings_lists_store,
+                phonetic_postings_lists_store,
+            )?;
+
+            if documents.len() < range.end as u64 {
+                continue;
             }
-        }
 
-        let mut groups = vec![raw_documents.as_mut_slice()];
+            // the other words matches of these documents are needed to rank them
+            let mut documents = fetch_raw_documents(
+                reader,
+                &automatons,
+                &groups_words,
+                &query_enhancer,
+                searchable_attrs.as_ref(),
+                Some(&documents),
+                postings_lists_store,
+                infix_postings_lists_store,
+                phonetic_postings_lists_store,
+                original_forms_postings_lists_store,
+                documents_fields_counts_store,
+            )?;
+
+            if documents.len() < range.end {
+                continue;
+            }
 
-        'criteria: for criterion in criteria.as_ref() {
-            let tmp_groups = mem::replace(&mut groups, Vec::new());
-            let mut documents_seen = 0;
+            bucket_sort(&mut documents, range.clone(), &criteria);
 
-            for group in tmp_groups {
-                // if this group does not overlap with the requested range,
-                // push it without sorting and splitting it
-                if documents_seen + group.len() < range.start {
-                    documents_seen += group.len();
-                    groups.push(group);
-                    continue;
-                }
+            // the range is settled if the first criterion ranks its last document
+            // before any document only containing words with more typos
+            let last = &documents[range.end - 1];
+            let settled = match criteria.as_ref().first() {
+                Some(criterion) => criterion.ranks_before_typos(last, query_len, max_distance + 1),
+                None => false,
+            };
 
-                group.sort_unstable_by(|a, b| criterion.evaluate(a, b));
+            if settled {
+                raw_documents = Some(documents);
+                break;
+            }
+        }
 
-                for group in group.binary_group_by_mut(|a, b| criterion.eq(a, b)) {
-                    documents_seen += group.len();
-                    groups.push(group);
+        let mut raw_documents = match raw_documents {
+            Some(raw_documents) => raw_documents,
+            None => {
+                // we must retrieve all the documents associated
+                // with the current automatons
+                let mut raw_documents = fetch_raw_documents(
+                    reader,
+                    &automatons,
+                    &groups_words,
+                    &query_enhancer,
+                    searchable_attrs.as_ref(),
+                    candidates.as_ref(),
+                    postings_lists_store,
+                    infix_postings_lists_store,
+                    phonetic_postings_lists_store,
+                    original_forms_postings_lists_store,
+                    documents_fields_counts_store,
+                )?;
+
+                bucket_sort(&mut raw_documents, range.clone(), &criteria);
+                raw_documents
+            }
+        };
 
-                    // we have sort enough documents if the last document sorted is after
-                    // the end of the requested range, we can continue to the next criterion
-                    if documents_seen >= range.end {
-                        continue 'criteria;
-                    }
-                }
+        // stop processing when time is running out
+        if let Some(timeout) = timeout {
+            if !raw_documents_processed.is_empty() && start_processing.elapsed() > timeout {
+                break;
             }
         }
 
@@ -502,9 +978,10 @@ where
 
     // make real documents now that we know
     // those must be returned
+    let query_words = query_words(&automatons);
     let documents = raw_documents_processed
         .into_iter()
-        .map(Document::from_raw)
+        .map(|raw| Document::from_raw(raw, &query_words))
         .collect();
 
     Ok(documents)
@@ -524,9 +1001,14 @@ fn raw_query_with_distinct<'c, FI, FD>(
 
     criteria: Criteria<'c>,
     searchable_attrs: Option<ReorderedAttrs>,
+    candidates: Option<RoaringTreemap>,
+    language: Option<Language>,
 
     main_store: store::Main,
     postings_lists_store: store::PostingsLists,
+    infix_postings_lists_store: store::PostingsLists,
+    phonetic_postings_lists_store: store::PostingsLists,
+    original_forms_postings_lists_store: store::PostingsLists,
     documents_fields_counts_store: store::DocumentsFieldsCounts,
     synonyms_store: store::Synonyms,
 ) -> MResult<Vec<Document>>
@@ -537,9 +1019,21 @@ where
     let start_processing = Instant::now();
     let mut raw_documents_processed = Vec::new();
 
+    // the words FST is decoded only once for the whole query
+    let words = match main_store.words_fst(reader)? {
+        Some(words) => words,
+        None => return Ok(Vec::new()),
+    };
+    let infix_words = main_store.infix_words_fst(reader)?.unwrap_or_default();
+    let phonetic_words = main_store.phonetic_words_fst(reader)?.unwrap_or_default();
+
     let (automaton_producer, query_enhancer) = AutomatonProducer::new(
         reader,
         query,
+        &words,
+        &infix_words,
+        &phonetic_words,
+        language,
         main_store,
         postings_lists_store,
         synonyms_store,
@@ -547,9 +1041,11 @@ where
 
     let automaton_producer = automaton_producer.into_iter();
     let mut automatons = Vec::new();
+    let mut groups_words = Vec::new();
 
     // aggregate automatons groups by groups after time
     for auts in automaton_producer {
+        groups_words.push(group_words(&words, &infix_words, &phonetic_words, &auts));
         automatons.push(auts);
 
         // we must retrieve the documents associated
@@ -557,10 +1053,14 @@ where
         let mut raw_documents = fetch_raw_documents(
             reader,
             &automatons,
+            &groups_words,
             &query_enhancer,
             searchable_attrs.as_ref(),
-            main_store,
+            candidates.as_ref(),
             postings_lists_store,
+            infix_postings_lists_store,
+            phonetic_postings_lists_store,
+            original_forms_postings_lists_store,
             documents_fields_counts_store,
         )?;
 
@@ -680,9 +1180,10 @@ where
 
     // make real documents now that we know
     // those must be returned
+    let query_words = query_words(&automatons);
     let documents = raw_documents_processed
         .into_iter()
-        .map(Document::from_raw)
+        .map(|raw| Document::from_raw(raw, &query_words))
         .collect();
 
     Ok(documents)
@@ -802,6 +1303,37 @@ mod tests {
 
             writer.commit().unwrap();
         }
+
+        pub fn add_weighted_synonym(&mut self, word: &str, new: &[(&str, u64)]) {
+            let db = &self.database;
+            let mut writer = db.main_write_txn().unwrap();
+
+            let word = word.to_lowercase();
+
+            let mut new: Vec<_> = new.iter().map(|(s, p)| (normalize_str(s), *p)).collect();
+            new.sort_unstable();
+            let mut builder = fst::MapBuilder::memory();
+            builder.extend_iter(new).unwrap();
+            let alternatives = builder.into_inner().and_then(fst::Map::from_bytes).unwrap();
+
+            self.index
+                .synonyms
+                .put_weighted_synonyms(&mut writer, word.as_bytes(), &alternatives)
+                .unwrap();
+
+            let synonyms = match self.index.main.synonyms_fst(&writer).unwrap() {
+                Some(synonyms) => synonyms,
+                None => fst::Set::default(),
+            };
+
+            let synonyms_fst = insert_key(&synonyms, word.as_bytes());
+            self.index
+                .main
+                .put_synonyms_fst(&mut writer, &synonyms_fst)
+                .unwrap();
+
+            writer.commit().unwrap();
+        }
     }
 
     impl<'a> FromIterator<(&'a str, &'a [DocIndex])> for TempDatabase {
@@ -922,6 +1454,74 @@ mod tests {
         assert_matches!(iter.next(), None);
     }
 
+    #[test]
+    fn weighted_synonyms() {
+        use crate::criterion::{self, CriteriaBuilder, SumOfTypos, SynonymPenalty};
+
+        let mut store = TempDatabase::from_iter(vec![
+            ("couch", &[doc_index(0, 0)][..]),
+            ("sofa", &[doc_index(1, 0)][..]),
+        ]);
+
+        store.add_weighted_synonym("sofa", &[("couch", 20)]);
+
+        let db = &store.database;
+        let reader = db.main_read_txn().unwrap();
+
+        let criteria = CriteriaBuilder::new()
+            .add(SumOfTypos)
+            .add(SynonymPenalty)
+            .add(criterion::DocumentId)
+            .build();
+        let builder = store.index.query_builder_with_criteria(criteria);
+        let results = builder.query(&reader, "sofa", 0..20).unwrap();
+        let mut iter = results.into_iter();
+
+        assert_matches!(iter.next(), Some(Document { id: DocumentId(1), matches, .. }) => {
+            let mut matches = matches.into_iter();
+            assert_matches!(matches.next(), Some(TmpMatch { query_index: 0, synonym_penalty: 0, .. }));
+            assert_matches!(matches.next(), None);
+        });
+        assert_matches!(iter.next(), Some(Document { id: DocumentId(0), matches, .. }) => {
+            let mut matches = matches.into_iter();
+            assert_matches!(matches.next(), Some(TmpMatch { query_index: 0, synonym_penalty: 20, .. }));
+            assert_matches!(matches.next(), None);
+        });
+        assert_matches!(iter.next(), None);
+    }
+
+    #[test]
+    fn highlights_match_kinds() {
+        let mut store = TempDatabase::from_iter(vec![
+            ("hello", &[doc_char_index(0, 0, 0)][..]),
+            ("couch", &[doc_char_index(0, 1, 6)][..]),
+            ("worlds", &[doc_char_index(0, 2, 12)][..]),
+        ]);
+
+        store.add_synonym("sofa", SetBuf::from_dirty(vec!["couch"]));
+
+        let db = &store.database;
+        let reader = db.main_read_txn().unwrap();
+
+        let builder = store.query_builder();
+        let results = builder.query(&reader, "hallo sofa worl", 0..20).unwrap();
+        let mut iter = results.into_iter();
+
+        assert_matches!(iter.next(), Some(Document { id: DocumentId(0), mut highlights, query_words, .. }) => {
+            highlights.sort_unstable_by_key(|h| h.char_index);
+            let kinds: Vec<_> = highlights
+                .iter()
+                .map(|h| (query_words[h.query_index as usize].as_str(), h.kind))
+                .collect();
+            assert_eq!(kinds, vec![
+                ("hallo", MatchKind::Typo(1)),
+                ("sofa", MatchKind::Synonym),
+                ("worl", MatchKind::Prefix),
+            ]);
+        });
+        assert_matches!(iter.next(), None);
+    }
+
     #[test]
     fn prefix_synonyms() {
         let mut store = TempDatabase::from_iter(vec![("hello", &[doc_index(0, 0)][..])]);
@@ -1760,6 +2360,228 @@ mod tests {
         assert_matches!(iter.next(), None);
     }
 
+    #[test]
+    fn concatenation_only_when_known() {
+        let store = TempDatabase::from_iter(vec![
+            ("notebooks", &[doc_index(0, 0)][..]),
+            ("note", &[doc_index(1, 0)][..]),
+            ("book", &[doc_index(1, 1)][..]),
+        ]);
+
+        let db = &store.database;
+        let reader = db.main_read_txn().unwrap();
+
+        // the last word is a prefix, "noteboo" is the prefix of a known word
+        let builder = store.query_builder();
+        let results = builder.query(&reader, "note boo", 0..20).unwrap();
+        let mut ids: Vec<_> = results.into_iter().map(|d| d.id).collect();
+        ids.sort_unstable();
+
+        assert_eq!(ids, vec![DocumentId(0), DocumentId(1)]);
+
+        // "notebok" is not a known word, no concatenation is done
+        let builder = store.query_builder();
+        let results = builder.query(&reader, "note bok ", 0..20).unwrap();
+        let mut iter = results.into_iter();
+
+        assert_matches!(iter.next(), Some(Document { id: DocumentId(1), .. }));
+        assert_matches!(iter.next(), None);
+    }
+
+    #[test]
+    fn infix_words() {
+        let store = TempDatabase::from_iter(vec![
+            ("smartphone", &[doc_index(0, 0)][..]),
+            ("phone", &[doc_index(1, 0)][..]),
+        ]);
+
+        let db = &store.database;
+        let mut writer = db.main_write_txn().unwrap();
+
+        // the suffixes of "smartphone" as the indexer would have produced them
+        let suffixes = vec!["artphone", "hone", "martphone", "one", "phone", "rtphone", "tphone"];
+        for suffix in &suffixes {
+            let doc_indexes = SetBuf::new_unchecked(vec![doc_index(0, 0)]);
+            store
+                .index
+                .infix_postings_lists
+                .put_postings_list(&mut writer, suffix.as_bytes(), &doc_indexes)
+                .unwrap();
+        }
+        let infix_words = Set::from_iter(suffixes).unwrap();
+        store.index.main.put_infix_words_fst(&mut writer, &infix_words).unwrap();
+        writer.commit().unwrap();
+
+        let reader = db.main_read_txn().unwrap();
+
+        let builder = store.query_builder();
+        let results = builder.query(&reader, "phone", 0..20).unwrap();
+        let mut iter = results.into_iter();
+
+        assert_matches!(iter.next(), Some(Document { id: DocumentId(1), matches, .. }) => {
+            let mut iter = matches.into_iter();
+            assert_matches!(iter.next(), Some(TmpMatch { query_index: 0, is_exact: true, .. })); // phone
+            assert_matches!(iter.next(), None);
+        });
+        assert_matches!(iter.next(), Some(Document { id: DocumentId(0), matches, .. }) => {
+            let mut iter = matches.into_iter();
+            assert_matches!(iter.next(), Some(TmpMatch { query_index: 0, is_exact: false, .. })); // smartphone
+            assert_matches!(iter.next(), None);
+        });
+        assert_matches!(iter.next(), None);
+    }
+
+    #[test]
+    fn phonetic_words() {
+        let store = TempDatabase::from_iter(vec![
+            ("kathryn", &[doc_index(0, 0)][..]),
+            ("catherine", &[doc_index(1, 0)][..]),
+        ]);
+
+        let db = &store.database;
+        let mut writer = db.main_write_txn().unwrap();
+
+        // the phonetic key of both "kathryn" and "catherine"
+        let doc_indexes = SetBuf::new_unchecked(vec![doc_index(0, 0), doc_index(1, 0)]);
+        store
+            .index
+            .phonetic_postings_lists
+            .put_postings_list(&mut writer, b"K0RN", &doc_indexes)
+            .unwrap();
+        let phonetic_words = Set::from_iter(vec!["K0RN"]).unwrap();
+        store.index.main.put_phonetic_words_fst(&mut writer, &phonetic_words).unwrap();
+        writer.commit().unwrap();
+
+        let reader = db.main_read_txn().unwrap();
+
+        let builder = store.query_builder();
+        let results = builder.query(&reader, "catherine", 0..20).unwrap();
+        let mut iter = results.into_iter();
+
+        assert_matches!(iter.next(), Some(Document { id: DocumentId(1), matches, .. }) => {
+            let mut iter = matches.into_iter();
+            assert_matches!(iter.next(), Some(TmpMatch { query_index: 0, distance: 0, is_phonetic: false, is_exact: true, .. })); // catherine
+            assert_matches!(iter.next(), Some(TmpMatch { query_index: 0, distance: 0, is_phonetic: true, .. })); // catherine
+            assert_matches!(iter.next(), None);
+        });
+        assert_matches!(iter.next(), Some(Document { id: DocumentId(0), matches, .. }) => {
+            let mut iter = matches.into_iter();
+            assert_matches!(iter.next(), Some(TmpMatch { query_index: 0, distance: 0, is_phonetic: true, is_exact: false, .. })); // kathryn
+            assert_matches!(iter.next(), None);
+        });
+        assert_matches!(iter.next(), None);
+    }
+
+    #[test]
+    fn phonetic_words_after_typos() {
+        let store = TempDatabase::from_iter(vec![
+            ("kathryn", &[doc_index(0, 0)][..]),
+            ("catherina", &[doc_index(1, 0)][..]),
+        ]);
+
+        let db = &store.database;
+        let mut writer = db.main_write_txn().unwrap();
+
+        // the phonetic key of "kathryn", "catherina" and "catherine"
+        let doc_indexes = SetBuf::new_unchecked(vec![doc_index(0, 0), doc_index(1, 0)]);
+        store
+            .index
+            .phonetic_postings_lists
+            .put_postings_list(&mut writer, b"K0RN", &doc_indexes)
+            .unwrap();
+        let phonetic_words = Set::from_iter(vec!["K0RN"]).unwrap();
+        store.index.main.put_phonetic_words_fst(&mut writer, &phonetic_words).unwrap();
+        writer.commit().unwrap();
+
+        let reader = db.main_read_txn().unwrap();
+
+        let builder = store.query_builder();
+        let results = builder.query(&reader, "catherine", 0..20).unwrap();
+        let mut iter = results.into_iter();
+
+        assert_matches!(iter.next(), Some(Document { id: DocumentId(1), matches, .. }) => {
+            let mut iter = matches.into_iter();
+            assert_matches!(iter.next(), Some(TmpMatch { query_index: 0, distance: 0, is_phonetic: true, .. })); // catherina
+            assert_matches!(iter.next(), Some(TmpMatch { query_index: 0, distance: 1, is_phonetic: false, .. })); // catherina
+            assert_matches!(iter.next(), None);
+        });
+        assert_matches!(iter.next(), Some(Document { id: DocumentId(0), matches, .. }) => {
+            let mut iter = matches.into_iter();
+            assert_matches!(iter.next(), Some(TmpMatch { query_index: 0, distance: 0, is_phonetic: true, .. })); // kathryn
+            assert_matches!(iter.next(), None);
+        });
+        assert_matches!(iter.next(), None);
+    }
+
+    #[test]
+    fn exact_forms() {
+        use crate::criterion::{self, CriteriaBuilder, Exact, ExactForm};
+
+        let store = TempDatabase::from_iter(vec![
+            ("us", &[doc_index(0, 0)][..]),
+            ("us", &[doc_index(1, 0)][..]),
+        ]);
+
+        let db = &store.database;
+        let mut writer = db.main_write_txn().unwrap();
+
+        // the original form as the indexer would have produced it,
+        // only the document where it differs from "us" is concerned
+        let doc_indexes = SetBuf::new_unchecked(vec![doc_index(1, 0)]);
+        for key in &[original_form_key("US"), altered_forms_key("us")] {
+            store
+                .index
+                .original_forms_postings_lists
+                .put_postings_list(&mut writer, key, &doc_indexes)
+                .unwrap();
+        }
+        writer.commit().unwrap();
+
+        let reader = db.main_read_txn().unwrap();
+
+        let criteria = CriteriaBuilder::new()
+            .add(Exact)
+            .add(ExactForm)
+            .add(criterion::DocumentId)
+            .build();
+        let builder = store.index.query_builder_with_criteria(criteria);
+        let results = builder.query(&reader, "US", 0..20).unwrap();
+        let mut iter = results.into_iter();
+
+        assert_matches!(iter.next(), Some(Document { id: DocumentId(1), matches, .. }) => {
+            let mut iter = matches.into_iter();
+            assert_matches!(iter.next(), Some(TmpMatch { query_index: 0, is_exact: true, is_exact_form: true, .. })); // US
+            assert_matches!(iter.next(), None);
+        });
+        assert_matches!(iter.next(), Some(Document { id: DocumentId(0), matches, .. }) => {
+            let mut iter = matches.into_iter();
+            assert_matches!(iter.next(), Some(TmpMatch { query_index: 0, is_exact: true, is_exact_form: false, .. })); // us
+            assert_matches!(iter.next(), None);
+        });
+        assert_matches!(iter.next(), None);
+
+        let criteria = CriteriaBuilder::new()
+            .add(Exact)
+            .add(ExactForm)
+            .add(criterion::DocumentId)
+            .build();
+        let builder = store.index.query_builder_with_criteria(criteria);
+        let results = builder.query(&reader, "us", 0..20).unwrap();
+        let mut iter = results.into_iter();
+
+        assert_matches!(iter.next(), Some(Document { id: DocumentId(0), matches, .. }) => {
+            let mut iter = matches.into_iter();
+            assert_matches!(iter.next(), Some(TmpMatch { query_index: 0, is_exact: true, is_exact_form: true, .. })); // us
+            assert_matches!(iter.next(), None);
+        });
+        assert_matches!(iter.next(), Some(Document { id: DocumentId(1), matches, .. }) => {
+            let mut iter = matches.into_iter();
+            assert_matches!(iter.next(), Some(TmpMatch { query_index: 0, is_exact: true, is_exact_form: false, .. })); // US
+            assert_matches!(iter.next(), None);
+        });
+        assert_matches!(iter.next(), None);
+    }
+
     #[test]
     fn simple_phrase_query_splitting() {
         let store = TempDatabase::from_iter(vec![
@@ -1786,6 +2608,31 @@ mod tests {
         assert_matches!(iter.next(), None);
     }
 
+    #[test]
+    fn phrase_query_splitting_followed_by_word() {
+        let store = TempDatabase::from_iter(vec![
+            ("search", &[doc_index(0, 0)][..]),
+            ("engine", &[doc_index(0, 1)][..]),
+            ("fast", &[doc_index(0, 2)][..]),
+        ]);
+
+        let db = &store.database;
+        let reader = db.main_read_txn().unwrap();
+
+        let builder = store.query_builder();
+        let results = builder.query(&reader, "searchengine fast", 0..20).unwrap();
+        let mut iter = results.into_iter();
+
+        assert_matches!(iter.next(), Some(Document { id: DocumentId(0), matches, .. }) => {
+            let mut iter = matches.into_iter();
+            assert_matches!(iter.next(), Some(TmpMatch { query_index: 0, word_index: 0, distance: 0, .. })); // search
+            assert_matches!(iter.next(), Some(TmpMatch { query_index: 0, word_index: 1, distance: 0, .. })); // engine
+            assert_matches!(iter.next(), Some(TmpMatch { query_index: 1, word_index: 2, distance: 0, .. })); // fast
+            assert_matches!(iter.next(), None);
+        });
+        assert_matches!(iter.next(), None);
+    }
+
     #[test]
     fn harder_phrase_query_splitting() {
         let store = TempDatabase::from_iter(vec![
@@ -1823,4 +2670,200 @@ mod tests {
         });
         assert_matches!(iter.next(), None);
     }
+
+    #[test]
+    fn partial_sort() {
+        let mut values = vec![9, 3, 7, 1, 8, 2, 6, 4, 5, 0];
+        partial_sort_by(&mut values, 4, |a, b| a.cmp(b));
+        assert_eq!(&values[..4], &[0, 1, 2, 3]);
+
+        let mut rest = values[4..].to_vec();
+        rest.sort_unstable();
+        assert_eq!(rest, vec![4, 5, 6, 7, 8, 9]);
+
+        let mut values = vec![3, 1, 2];
+        partial_sort_by(&mut values, 10, |a, b| b.cmp(a));
+        assert_eq!(values, vec![3, 2, 1]);
+
+        let mut values = vec![3, 1, 2];
+        partial_sort_by(&mut values, 0, |a, b| a.cmp(b));
+        assert_eq!(values, vec![3, 1, 2]);
+    }
+
+    #[test]
+    fn paginated_results() {
+        let store = TempDatabase::from_iter(vec![
+            ("hello", &[doc_index(0, 3), doc_index(1, 2), doc_index(2, 1), doc_index(3, 0)][..]),
+            ("world", &[doc_index(0, 4), doc_index(1, 3), doc_index(2, 2), doc_index(3, 1)][..]),
+        ]);
+
+        let db = &store.database;
+        let reader = db.main_read_txn().unwrap();
+
+        let builder = store.query_builder();
+        let results = builder.query(&reader, "hello world", 0..20).unwrap();
+        let all: Vec<_> = results.into_iter().map(|d| d.id).collect();
+        assert_eq!(all.len(), 4);
+
+        let builder = store.query_builder();
+        let results = builder.query(&reader, "hello world", 1..3).unwrap();
+        let page: Vec<_> = results.into_iter().map(|d| d.id).collect();
+        assert_eq!(page, &all[1..3]);
+    }
+
+    #[test]
+    fn bucket_sort_comparisons() {
+        use std::sync::atomic::{self, AtomicUsize};
+
+        use crate::criterion::{CriteriaBuilder, Criterion};
+
+        struct CountedTypos<'a>(&'a AtomicUsize);
+
+        impl Criterion for CountedTypos<'_> {
+            fn evaluate(&self, lhs: &RawDocument, rhs: &RawDocument) -> cmp::Ordering {
+                self.0.fetch_add(1, atomic::Ordering::Relaxed);
+                lhs.distance().cmp(rhs.distance()).then(lhs.id.cmp(&rhs.id))
+            }
+
+            fn name(&self) -> &str {
+                "CountedTypos"
+            }
+        }
+
+        let mut matches = Vec::new();
+        let mut highlights = Vec::new();
+        let mut fields_counts = Vec::new();
+        for i in 0..10_000 {
+            let id = DocumentId(i);
+            let match_ = TmpMatch {
+                query_index: 0,
+                distance: (i % 3) as u8,
+                is_phonetic: false,
+                attribute: 0,
+                word_index: 0,
+                is_exact: false,
+                is_exact_form: false,
+                documents_frequency: 10_000,
+                synonym_penalty: 0,
+            };
+            let highlight = Highlight {
+                attribute: 0,
+                char_index: 0,
+                char_length: 0,
+                query_index: 0,
+                kind: MatchKind::Exact,
+            };
+            matches.push((id, match_));
+            highlights.push((id, highlight));
+            fields_counts.push((id, SchemaAttr::new(0), 1));
+        }
+
+        let documents = raw_documents_from(
+            SetBuf::new_unchecked(matches),
+            SetBuf::new_unchecked(highlights),
+            SetBuf::new_unchecked(fields_counts),
+        );
+
+        let sort = |range: Range<usize>| {
+            let count = AtomicUsize::new(0);
+            let criteria = CriteriaBuilder::new().add(CountedTypos(&count)).build();
+            let mut documents = documents.clone();
+            bucket_sort(&mut documents, range.clone(), &criteria);
+            let ids: Vec<_> = documents[range].iter().map(|d| d.id).collect();
+            (ids, count.load(atomic::Ordering::Relaxed))
+        };
+
+        let (all, all_comparisons) = sort(0..10_000);
+        let (page, page_comparisons) = sort(0..20);
+
+        // the first page is the same but sorting it requires far less comparisons
+        assert_eq!(page, &all[..20]);
+        assert!(page_comparisons * 2 < all_comparisons);
+    }
+
+    #[test]
+    fn typos_documents_not_fetched() {
+        use std::sync::atomic::{self, AtomicBool};
+
+        use crate::criterion::{CriteriaBuilder, Criterion, SumOfTypos};
+
+        struct SeenTypos<'a>(&'a AtomicBool);
+
+        impl Criterion for SeenTypos<'_> {
+            fn evaluate(&self, lhs: &RawDocument, rhs: &RawDocument) -> cmp::Ordering {
+                if lhs.distance().iter().chain(rhs.distance()).any(|d| *d != 0) {
+                    self.0.store(true, atomic::Ordering::Relaxed);
+                }
+                SumOfTypos.evaluate(lhs, rhs)
+            }
+
+            fn name(&self) -> &str {
+                "SeenTypos"
+            }
+
+            fn ranks_before_typos(
+                &self,
+                document: &RawDocument,
+                query_len: usize,
+                min_distance: u8,
+            ) -> bool {
+                SumOfTypos.ranks_before_typos(document, query_len, min_distance)
+            }
+
+            fn bounds_typos(&self) -> bool {
+                true
+            }
+        }
+
+        let hello: Vec<_> = (0..10).map(|id| doc_index(id, 0)).collect();
+        let hallo: Vec<_> = (10..20).map(|id| doc_index(id, 0)).collect();
+        let store = TempDatabase::from_iter(vec![("hello", &hello[..]), ("hallo", &hallo[..])]);
+
+        let db = &store.database;
+        let reader = db.main_read_txn().unwrap();
+
+        let query = |range: Range<usize>| {
+            let seen_typos = AtomicBool::new(false);
+            let criteria = CriteriaBuilder::new().add(SeenTypos(&seen_typos)).build();
+            let builder = store.index.query_builder_with_criteria(criteria);
+            let results = builder.query(&reader, "hello", range).unwrap();
+            let ids: Vec<_> = results.into_iter().map(|d| d.id.0).collect();
+            (ids, seen_typos.load(atomic::Ordering::Relaxed))
+        };
+
+        // the documents containing "hallo" can not be in the first page
+        let (ids, seen_typos) = query(0..5);
+        assert_eq!(ids.len(), 5);
+        assert!(ids.iter().all(|id| *id < 10));
+        assert!(!seen_typos);
+
+        // but they must be fetched to fill the second one
+        let (ids, seen_typos) = query(5..15);
+        assert_eq!(ids.len(), 10);
+        assert_eq!(ids.iter().filter(|id| **id >= 10).count(), 5);
+        assert!(seen_typos);
+    }
+
+    #[test]
+    fn restricted_to_candidates() {
+        let store = TempDatabase::from_iter(vec![
+            ("hello", &[doc_index(0, 0), doc_index(1, 0), doc_index(2, 0)][..]),
+        ]);
+
+        let db = &store.database;
+        let reader = db.main_read_txn().unwrap();
+
+        let mut candidates = RoaringTreemap::new();
+        candidates.insert(0);
+        candidates.insert(2);
+
+        let mut builder = store.query_builder();
+        builder.with_candidates(candidates);
+        let results = builder.query(&reader, "hello", 0..20).unwrap();
+        let mut iter = results.into_iter();
+
+        assert_matches!(iter.next(), Some(Document { id: DocumentId(0), .. }));
+        assert_matches!(iter.next(), Some(Document { id: DocumentId(2), .. }));
+        assert_matches!(iter.next(), None);
+    }
 }
@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PatternMatch {
+    /// The query must be equal to the pattern.
+    Exact,
+    /// The query must contain the words of the pattern, in order.
+    Contains,
+}
+
+impl Default for PatternMatch {
+    fn default() -> PatternMatch {
+        PatternMatch::Exact
+    }
+}
+
+/// A document placed at a fixed position of the results,
+/// the positions start at zero.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct PinnedDocument {
+    pub id: String,
+    pub position: usize,
+}
+
+/// Modifies the results of the queries matching a pattern: documents can be
+/// pinned at given positions, hidden from the results, or a filter injected.
+///
+/// The documents are referenced by their user identifiers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct QueryRule {
+    pub pattern: String,
+    #[serde(default)]
+    pub match_type: PatternMatch,
+    #[serde(default)]
+    pub pins: Vec<PinnedDocument>,
+    #[serde(default)]
+    pub hide: Vec<String>,
+    #[serde(default)]
+    pub filters: Option<String>,
+}
+
+fn normalized_words(text: &str) -> Vec<String> {
+    text.split_whitespace().map(str::to_lowercase).collect()
+}
+
+impl QueryRule {
+    /// Returns `true` if the query matches the pattern of this rule,
+    /// the case and the whitespaces are ignored.
+    pub fn matches(&self, query: &str) -> bool {
+        let pattern = normalized_words(&self.pattern);
+        let query = normalized_words(query);
+
+        if pattern.is_empty() {
+            return false;
+        }
+
+        match self.match_type {
+            PatternMatch::Exact => pattern == query,
+            PatternMatch::Contains => query.windows(pattern.len()).any(|w| w == pattern.as_slice()),
+        }
+    }
+}
+
+/// Returns the rule to apply to the query, the rules matching
+/// exactly are preferred, then the first matching rule is returned.
+pub fn matching_rule<'a>(rules: &'a [QueryRule], query: &str) -> Option<&'a QueryRule> {
+    let mut matching = rules.iter().filter(|rule| rule.matches(query));
+    let first = matching.next()?;
+
+    match first.match_type {
+        PatternMatch::Exact => Some(first),
+        PatternMatch::Contains => matching
+            .find(|rule| rule.match_type == PatternMatch::Exact)
+            .or(Some(first)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: &str, match_type: PatternMatch) -> QueryRule {
+        QueryRule {
+            pattern: pattern.to_owned(),
+            match_type,
+            pins: Vec::new(),
+            hide: Vec::new(),
+            filters: None,
+        }
+    }
+
+    #[test]
+    fn exact_and_contains() {
+        let exact = rule("Black Friday", PatternMatch::Exact);
+        assert!(exact.matches("black friday"));
+        assert!(exact.matches("  black   FRIDAY "));
+        assert!(!exact.matches("black friday deals"));
+
+        let contains = rule("black friday", PatternMatch::Contains);
+        assert!(contains.matches("black friday"));
+        assert!(contains.matches("black friday deals"));
+        assert!(contains.matches("the black friday"));
+        assert!(!contains.matches("friday black"));
+        assert!(!contains.matches("black fridays"));
+    }
+
+    #[test]
+    fn exact_rules_first() {
+        let rules = vec![
+            rule("friday", PatternMatch::Contains),
+            rule("black friday", PatternMatch::Exact),
+            rule("", PatternMatch::Contains),
+        ];
+
+        let found = matching_rule(&rules, "black friday").unwrap();
+        assert_eq!(found.match_type, PatternMatch::Exact);
+
+        let found = matching_rule(&rules, "friday deals").unwrap();
+        assert_eq!(found.pattern, "friday");
+
+        assert!(matching_rule(&rules, "cyber monday").is_none());
+    }
+}
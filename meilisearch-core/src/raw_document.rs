@@ -36,6 +36,19 @@ impl RawDocument {
         unsafe { &self.matches.matches.distance.get_unchecked(r.start..r.end) }
     }
 
+    pub fn is_phonetic(&self) -> &[bool] {
+        let r = self.matches.range;
+        // it is safe because construction/modifications
+        // can only be done in this module
+        unsafe {
+            &self
+                .matches
+                .matches
+                .is_phonetic
+                .get_unchecked(r.start..r.end)
+        }
+    }
+
     pub fn attribute(&self) -> &[u16] {
         let r = self.matches.range;
         // it is safe because construction/modifications
@@ -62,6 +75,45 @@ impl RawDocument {
         // can only be done in this module
         unsafe { &self.matches.matches.is_exact.get_unchecked(r.start..r.end) }
     }
+
+    pub fn is_exact_form(&self) -> &[bool] {
+        let r = self.matches.range;
+        // it is safe because construction/modifications
+        // can only be done in this module
+        unsafe {
+            &self
+                .matches
+                .matches
+                .is_exact_form
+                .get_unchecked(r.start..r.end)
+        }
+    }
+
+    pub fn documents_frequency(&self) -> &[u32] {
+        let r = self.matches.range;
+        // it is safe because construction/modifications
+        // can only be done in this module
+        unsafe {
+            &self
+                .matches
+                .matches
+                .documents_frequency
+                .get_unchecked(r.start..r.end)
+        }
+    }
+
+    pub fn synonym_penalty(&self) -> &[u8] {
+        let r = self.matches.range;
+        // it is safe because construction/modifications
+        // can only be done in this module
+        unsafe {
+            &self
+                .matches
+                .matches
+                .synonym_penalty
+                .get_unchecked(r.start..r.end)
+        }
+    }
 }
 
 impl fmt::Debug for RawDocument {
@@ -78,6 +130,11 @@ impl fmt::Debug for RawDocument {
             "distance",
             self.distance()
         ))?;
+        f.write_fmt(format_args!(
+            "{:>15}: {:^5?},\r\n",
+            "is_phonetic",
+            self.is_phonetic()
+        ))?;
         f.write_fmt(format_args!(
             "{:>15}: {:^5?},\r\n",
             "attribute",
@@ -93,6 +150,21 @@ impl fmt::Debug for RawDocument {
             "is_exact",
             self.is_exact()
         ))?;
+        f.write_fmt(format_args!(
+            "{:>15}: {:^5?},\r\n",
+            "is_exact_form",
+            self.is_exact_form()
+        ))?;
+        f.write_fmt(format_args!(
+            "{:>15}: {:^5?},\r\n",
+            "documents_frequency",
+            self.documents_frequency()
+        ))?;
+        f.write_fmt(format_args!(
+            "{:>15}: {:^5?},\r\n",
+            "synonym_penalty",
+            self.synonym_penalty()
+        ))?;
         f.write_str("}")?;
         Ok(())
     }
@@ -158,9 +230,13 @@ pub struct SharedMatches {
 struct Matches {
     query_index: Vec<u32>,
     distance: Vec<u8>,
+    is_phonetic: Vec<bool>,
     attribute: Vec<u16>,
     word_index: Vec<u16>,
     is_exact: Vec<bool>,
+    is_exact_form: Vec<bool>,
+    documents_frequency: Vec<u32>,
+    synonym_penalty: Vec<u8>,
 }
 
 impl Matches {
@@ -168,9 +244,13 @@ impl Matches {
         Matches {
             query_index: Vec::with_capacity(cap),
             distance: Vec::with_capacity(cap),
+            is_phonetic: Vec::with_capacity(cap),
             attribute: Vec::with_capacity(cap),
             word_index: Vec::with_capacity(cap),
             is_exact: Vec::with_capacity(cap),
+            is_exact_form: Vec::with_capacity(cap),
+            documents_frequency: Vec::with_capacity(cap),
+            synonym_penalty: Vec::with_capacity(cap),
         }
     }
 
@@ -178,9 +258,13 @@ impl Matches {
         for (_, match_) in matches {
             self.query_index.push(match_.query_index);
             self.distance.push(match_.distance);
+            self.is_phonetic.push(match_.is_phonetic);
             self.attribute.push(match_.attribute);
             self.word_index.push(match_.word_index);
             self.is_exact.push(match_.is_exact);
+            self.is_exact_form.push(match_.is_exact_form);
+            self.documents_frequency.push(match_.documents_frequency);
+            self.synonym_penalty.push(match_.synonym_penalty);
         }
     }
 }
@@ -1,28 +1,81 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::convert::TryFrom;
+use std::str;
 
-use crate::{DocIndex, DocumentId};
+use crate::phonetic::phonetic_key;
+use crate::{Analyzer, DocIndex, DocumentId};
 use deunicode::deunicode_with_tofu;
 use meilisearch_schema::SchemaAttr;
 use meilisearch_tokenizer::{is_cjk, SeqTokenizer, Token, Tokenizer};
 use sdset::SetBuf;
 
 const WORD_LENGTH_LIMIT: usize = 80;
+pub const INFIX_MIN_LENGTH: usize = 3;
+
+// The original forms of the words, accents and case included, are stored in their
+// own postings lists, under keys made of the normalized word, a zero byte and the
+// original form. Only the forms that differ from the normalized words are stored,
+// the positions where a normalized word does not appear as is in the text are
+// stored under the normalized word itself.
+const ORIGINAL_FORM_SEPARATOR: u8 = 0;
 
 type Word = Vec<u8>; // TODO make it be a SmallVec
 
 pub struct RawIndexer {
     word_limit: usize, // the maximum number of indexed words
     stop_words: fst::Set,
+    analyzer: Analyzer,
+    attributes_analyzers: HashMap<u16, Analyzer>,
+    infix_attributes: HashSet<u16>,
+    phonetic_attributes: HashSet<u16>,
     words_doc_indexes: BTreeMap<Word, Vec<DocIndex>>,
+    original_forms_doc_indexes: BTreeMap<Word, Vec<DocIndex>>,
     docs_words: HashMap<DocumentId, Vec<Word>>,
 }
 
 pub struct Indexed {
     pub words_doc_indexes: BTreeMap<Word, SetBuf<DocIndex>>,
+    pub infix_words_doc_indexes: BTreeMap<Word, SetBuf<DocIndex>>,
+    pub phonetic_words_doc_indexes: BTreeMap<Word, SetBuf<DocIndex>>,
+    pub original_forms_doc_indexes: BTreeMap<Word, SetBuf<DocIndex>>,
     pub docs_words: HashMap<DocumentId, fst::Set>,
 }
 
+/// Returns the original forms postings list key of the original form of a word,
+/// the form in which it appears in the text, before any normalization.
+pub fn original_form_key(word: &str) -> Vec<u8> {
+    let lower = word.to_lowercase();
+    let mut key = Vec::with_capacity(lower.len() + word.len() + 1);
+    key.extend_from_slice(lower.as_bytes());
+    key.push(ORIGINAL_FORM_SEPARATOR);
+    key.extend_from_slice(word.as_bytes());
+    key
+}
+
+/// Returns the original forms postings list key of the positions where
+/// a normalized word does not appear as is in the text, where its form differs.
+pub fn altered_forms_key(word: &str) -> Vec<u8> {
+    word.as_bytes().to_vec()
+}
+
+/// Returns the range of the original forms postings lists keys of a
+/// normalized word, its altered forms key and the keys of its original forms.
+pub fn original_forms_keys_range(word: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let start = word.to_vec();
+    let mut end = word.to_vec();
+    end.push(ORIGINAL_FORM_SEPARATOR + 1);
+    (start, end)
+}
+
+/// Returns the suffixes of a word that are indexed to be able to find
+/// this word by searching for a part of it, the word itself is not returned.
+pub fn word_suffixes(word: &str) -> impl Iterator<Item = &str> {
+    word.char_indices()
+        .skip(1)
+        .map(move |(i, _)| &word[i..])
+        .take_while(|suffix| suffix.chars().count() >= INFIX_MIN_LENGTH)
+}
+
 impl RawIndexer {
     pub fn new(stop_words: fst::Set) -> RawIndexer {
         RawIndexer::with_word_limit(stop_words, 1000)
@@ -32,30 +85,77 @@ impl RawIndexer {
         RawIndexer {
             word_limit: limit,
             stop_words,
+            analyzer: Analyzer::default(),
+            attributes_analyzers: HashMap::new(),
+            infix_attributes: HashSet::new(),
+            phonetic_attributes: HashSet::new(),
             words_doc_indexes: BTreeMap::new(),
+            original_forms_doc_indexes: BTreeMap::new(),
             docs_words: HashMap::new(),
         }
     }
 
+    pub fn with_analyzer(stop_words: fst::Set, analyzer: Analyzer) -> RawIndexer {
+        RawIndexer {
+            analyzer,
+            ..RawIndexer::new(stop_words)
+        }
+    }
+
+    /// Defines the analyzer of an attribute, replacing the
+    /// default analyzer for the texts of this attribute.
+    pub fn set_attribute_analyzer(&mut self, attr: SchemaAttr, analyzer: Analyzer) {
+        self.attributes_analyzers.insert(attr.0, analyzer);
+    }
+
+    /// Declares the attributes for which the suffixes of the words must also be indexed.
+    pub fn set_infix_attributes<I>(&mut self, attributes: I)
+    where
+        I: IntoIterator<Item = SchemaAttr>,
+    {
+        self.infix_attributes = attributes.into_iter().map(|attr| attr.0).collect();
+    }
+
+    /// Declares the attributes for which the phonetic keys of the words must also be indexed.
+    pub fn set_phonetic_attributes<I>(&mut self, attributes: I)
+    where
+        I: IntoIterator<Item = SchemaAttr>,
+    {
+        self.phonetic_attributes = attributes.into_iter().map(|attr| attr.0).collect();
+    }
+
     pub fn index_text(&mut self, id: DocumentId, attr: SchemaAttr, text: &str) -> usize {
         let mut number_of_words = 0;
+        let mut last_word_index = None;
+
+        let analyzer = self.attributes_analyzers.get(&attr.0).unwrap_or(&self.analyzer);
+        let options = analyzer.tokenizer_options();
+        for token in Tokenizer::with_options(text, options) {
+            let word_index = token.word_index;
+            let stop_word = is_stop_word(&self.stop_words, analyzer, token.word);
 
-        for token in Tokenizer::new(text) {
             let must_continue = index_token(
                 token,
                 id,
                 attr,
                 self.word_limit,
                 &self.stop_words,
+                analyzer,
                 &mut self.words_doc_indexes,
+                &mut self.original_forms_doc_indexes,
                 &mut self.docs_words,
             );
 
-            number_of_words += 1;
-
             if !must_continue {
                 break;
             }
+
+            // the sub words are emitted at the position of their whole word
+            // and the stop words are not indexed, only the words are counted
+            if !stop_word && last_word_index != Some(word_index) {
+                number_of_words += 1;
+                last_word_index = Some(word_index);
+            }
         }
 
         number_of_words
@@ -66,14 +166,18 @@ impl RawIndexer {
         I: IntoIterator<Item = &'a str>,
     {
         let iter = iter.into_iter();
-        for token in SeqTokenizer::new(iter) {
+        let analyzer = self.attributes_analyzers.get(&attr.0).unwrap_or(&self.analyzer);
+        let options = analyzer.tokenizer_options();
+        for token in SeqTokenizer::with_options(iter, options) {
             let must_continue = index_token(
                 token,
                 id,
                 attr,
                 self.word_limit,
                 &self.stop_words,
+                analyzer,
                 &mut self.words_doc_indexes,
+                &mut self.original_forms_doc_indexes,
                 &mut self.docs_words,
             );
 
@@ -84,6 +188,24 @@ impl RawIndexer {
     }
 
     pub fn build(self) -> Indexed {
+        let original_forms_doc_indexes = self
+            .original_forms_doc_indexes
+            .into_iter()
+            .map(|(word, indexes)| (word, SetBuf::from_dirty(indexes)))
+            .collect();
+
+        let infix_words_doc_indexes = derived_words_doc_indexes(
+            &self.words_doc_indexes,
+            &self.infix_attributes,
+            |word| word_suffixes(word).map(String::from).collect(),
+        );
+
+        let phonetic_words_doc_indexes = derived_words_doc_indexes(
+            &self.words_doc_indexes,
+            &self.phonetic_attributes,
+            |word| phonetic_key(word).into_iter().collect(),
+        );
+
         let words_doc_indexes = self
             .words_doc_indexes
             .into_iter()
@@ -102,31 +224,83 @@ impl RawIndexer {
 
         Indexed {
             words_doc_indexes,
+            infix_words_doc_indexes,
+            phonetic_words_doc_indexes,
+            original_forms_doc_indexes,
             docs_words,
         }
     }
 }
 
+// computes the postings lists of the words derived from the words
+// of the given attributes, e.g. their suffixes or their phonetic keys
+fn derived_words_doc_indexes<F>(
+    words_doc_indexes: &BTreeMap<Word, Vec<DocIndex>>,
+    attributes: &HashSet<u16>,
+    derive: F,
+) -> BTreeMap<Word, SetBuf<DocIndex>>
+where
+    F: Fn(&str) -> Vec<String>,
+{
+    if attributes.is_empty() {
+        return BTreeMap::new();
+    }
+
+    let mut derived_words_doc_indexes = BTreeMap::new();
+    for (word, indexes) in words_doc_indexes {
+        let indexes: Vec<_> = indexes
+            .iter()
+            .filter(|di| attributes.contains(&di.attribute))
+            .cloned()
+            .collect();
+
+        let word = match str::from_utf8(word) {
+            Ok(word) if !indexes.is_empty() => word,
+            _ => continue,
+        };
+
+        for derived in derive(word) {
+            derived_words_doc_indexes
+                .entry(derived.into_bytes())
+                .or_insert_with(Vec::new)
+                .extend_from_slice(&indexes);
+        }
+    }
+
+    derived_words_doc_indexes
+        .into_iter()
+        .map(|(word, indexes)| (word, SetBuf::from_dirty(indexes)))
+        .collect()
+}
+
+fn is_stop_word(stop_words: &fst::Set, analyzer: &Analyzer, word: &str) -> bool {
+    let lower = word.to_lowercase();
+    stop_words.contains(&lower) || analyzer.stop_words.contains(&lower)
+}
+
 fn index_token(
     token: Token,
     id: DocumentId,
     attr: SchemaAttr,
     word_limit: usize,
     stop_words: &fst::Set,
+    analyzer: &Analyzer,
     words_doc_indexes: &mut BTreeMap<Word, Vec<DocIndex>>,
+    original_forms_doc_indexes: &mut BTreeMap<Word, Vec<DocIndex>>,
     docs_words: &mut HashMap<DocumentId, Vec<Word>>,
 ) -> bool {
     if token.word_index >= word_limit {
         return false;
     }
 
+    let original = token.word;
     let lower = token.word.to_lowercase();
     let token = Token {
         word: &lower,
         ..token
     };
 
-    if !stop_words.contains(&token.word) {
+    if !is_stop_word(stop_words, analyzer, original) {
         match token_to_docindex(id, attr, token) {
             Some(docindex) => {
                 let word = Vec::from(token.word);
@@ -138,18 +312,48 @@ fn index_token(
                         .push(docindex);
                     docs_words.entry(id).or_insert_with(Vec::new).push(word);
 
+                    // the original form is kept to rank the documents where the word
+                    // appears with the accents and case of the query, when it differs
+                    if original != lower {
+                        let key = original_form_key(original);
+                        if key.len() <= WORD_LENGTH_LIMIT {
+                            push_doc_index(original_forms_doc_indexes, key, docindex);
+                        }
+                        let altered = altered_forms_key(&lower);
+                        push_doc_index(original_forms_doc_indexes, altered, docindex);
+                    }
+
                     if !lower.contains(is_cjk) {
                         let unidecoded = deunicode_with_tofu(&lower, "");
                         if unidecoded != lower && !unidecoded.is_empty() {
+                            let altered = altered_forms_key(&unidecoded);
                             let word = Vec::from(unidecoded);
                             if word.len() <= WORD_LENGTH_LIMIT {
-                                words_doc_indexes
-                                    .entry(word.clone())
-                                    .or_insert_with(Vec::new)
-                                    .push(docindex);
+                                push_doc_index(words_doc_indexes, word.clone(), docindex);
+                                push_doc_index(original_forms_doc_indexes, altered, docindex);
                                 docs_words.entry(id).or_insert_with(Vec::new).push(word);
                             }
                         }
+
+                        // the stem is indexed at the same position as the original word,
+                        // the exact criterion will rank the original word higher
+                        if let Some(stem) = analyzer.stem(&lower) {
+                            let unidecoded = deunicode_with_tofu(&stem, "");
+                            let mut stems = vec![stem];
+                            if !unidecoded.is_empty() && unidecoded != stems[0] {
+                                stems.push(unidecoded);
+                            }
+
+                            for stem in stems {
+                                let altered = altered_forms_key(&stem);
+                                let word = Vec::from(stem);
+                                if word.len() <= WORD_LENGTH_LIMIT {
+                                    push_doc_index(words_doc_indexes, word.clone(), docindex);
+                                    push_doc_index(original_forms_doc_indexes, altered, docindex);
+                                    docs_words.entry(id).or_insert_with(Vec::new).push(word);
+                                }
+                            }
+                        }
                     }
                 }
             }
@@ -160,6 +364,17 @@ fn index_token(
     true
 }
 
+fn push_doc_index(
+    words_doc_indexes: &mut BTreeMap<Word, Vec<DocIndex>>,
+    word: Word,
+    docindex: DocIndex,
+) {
+    words_doc_indexes
+        .entry(word)
+        .or_insert_with(Vec::new)
+        .push(docindex);
+}
+
 fn token_to_docindex(id: DocumentId, attr: SchemaAttr, token: Token) -> Option<DocIndex> {
     let word_index = u16::try_from(token.word_index).ok()?;
     let char_index = u16::try_from(token.char_index).ok()?;
@@ -179,6 +394,7 @@ fn token_to_docindex(id: DocumentId, attr: SchemaAttr, token: Token) -> Option<D
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Language;
 
     #[test]
     fn strange_apostrophe() {
@@ -268,4 +484,247 @@ mod tests {
             .get(&"🇯🇵".to_owned().into_bytes())
             .is_some());
     }
+
+    #[test]
+    fn stemmed_words() {
+        let analyzer = Analyzer {
+            stemmer: Some(Language::English),
+            ..Analyzer::default()
+        };
+        let mut indexer = RawIndexer::with_analyzer(fst::Set::default(), analyzer);
+
+        let docid = DocumentId(0);
+        let attr = SchemaAttr(0);
+        let text = "The runners are running";
+        indexer.index_text(docid, attr, text);
+
+        let Indexed {
+            words_doc_indexes, ..
+        } = indexer.build();
+
+        assert!(words_doc_indexes.get(&b"running"[..]).is_some());
+        assert!(words_doc_indexes.get(&b"runners"[..]).is_some());
+
+        let run = words_doc_indexes.get(&b"run"[..]).unwrap();
+        let running = words_doc_indexes.get(&b"running"[..]).unwrap();
+        assert_eq!(run, running);
+
+        assert!(words_doc_indexes.get(&b"runner"[..]).is_some());
+    }
+
+    #[test]
+    #[cfg(feature = "chinese-dictionary")]
+    fn segmented_cjk_words() {
+        let analyzer = Analyzer {
+            cjk_segmentation: true,
+            ..Analyzer::default()
+        };
+        let mut indexer = RawIndexer::with_analyzer(fst::Set::default(), analyzer);
+
+        let docid = DocumentId(0);
+        let attr = SchemaAttr(0);
+        let text = "我们在北京大学学习";
+        indexer.index_text(docid, attr, text);
+
+        let Indexed {
+            words_doc_indexes, ..
+        } = indexer.build();
+
+        assert!(words_doc_indexes.get(&"北京大学".to_owned().into_bytes()).is_some());
+        assert!(words_doc_indexes.get(&"学习".to_owned().into_bytes()).is_some());
+        assert!(words_doc_indexes.get(&"北".to_owned().into_bytes()).is_none());
+    }
+
+    #[test]
+    fn kept_together_words() {
+        let analyzer = Analyzer {
+            keep_emails: true,
+            keep_together: vec!["c++".to_string()],
+            ..Analyzer::default()
+        };
+        let mut indexer = RawIndexer::with_analyzer(fst::Set::default(), analyzer);
+
+        let docid = DocumentId(0);
+        let attr = SchemaAttr(0);
+        let text = "Write C++ to john.doe@example.com";
+        indexer.index_text(docid, attr, text);
+
+        let Indexed {
+            words_doc_indexes, ..
+        } = indexer.build();
+
+        assert!(words_doc_indexes.get(&b"c++"[..]).is_some());
+        assert!(words_doc_indexes.get(&b"john.doe@example.com"[..]).is_some());
+        assert!(words_doc_indexes.get(&b"john"[..]).is_none());
+    }
+
+    #[test]
+    fn sub_words() {
+        let analyzer = Analyzer {
+            split_sub_words: true,
+            ..Analyzer::default()
+        };
+        let mut indexer = RawIndexer::with_analyzer(fst::Set::default(), analyzer);
+
+        let docid = DocumentId(0);
+        let attr = SchemaAttr(0);
+        let text = "getUserById returns";
+        indexer.index_text(docid, attr, text);
+
+        let Indexed {
+            words_doc_indexes, ..
+        } = indexer.build();
+
+        let whole = &words_doc_indexes.get(&b"getuserbyid"[..]).unwrap()[0];
+        let user = &words_doc_indexes.get(&b"user"[..]).unwrap()[0];
+        let id = &words_doc_indexes.get(&b"id"[..]).unwrap()[0];
+        let returns = &words_doc_indexes.get(&b"returns"[..]).unwrap()[0];
+
+        assert_eq!(whole.word_index, 0);
+        assert_eq!(user.word_index, 0);
+        assert_eq!(user.char_index, 3);
+        assert_eq!(id.word_index, 0);
+        assert_eq!(returns.word_index, 1);
+    }
+
+    #[test]
+    fn sub_words_count() {
+        let stop_words = sdset::SetBuf::from_dirty(vec!["the"]);
+        let stop_words = fst::Set::from_iter(stop_words).unwrap();
+        let analyzer = Analyzer {
+            split_sub_words: true,
+            ..Analyzer::default()
+        };
+        let mut indexer = RawIndexer::with_analyzer(stop_words, analyzer);
+
+        let docid = DocumentId(0);
+        assert_eq!(indexer.index_text(docid, SchemaAttr(0), "PlayStation"), 1);
+        assert_eq!(indexer.index_text(docid, SchemaAttr(1), "The Matrix"), 1);
+        assert_eq!(indexer.index_text(docid, SchemaAttr(2), "getUserById returns"), 2);
+    }
+
+    #[test]
+    fn attributes_analyzers() {
+        let mut indexer = RawIndexer::new(fst::Set::default());
+
+        let english = Analyzer {
+            stemmer: Some(Language::English),
+            stop_words: vec!["the".to_string()].into_iter().collect(),
+            ..Analyzer::default()
+        };
+        indexer.set_attribute_analyzer(SchemaAttr(0), english);
+        indexer.set_attribute_analyzer(SchemaAttr(1), Analyzer::default().with_language(Language::French));
+
+        let docid = DocumentId(0);
+        indexer.index_text(docid, SchemaAttr(0), "the running dogs");
+        indexer.index_text(docid, SchemaAttr(1), "the continuellement");
+        indexer.index_text(docid, SchemaAttr(2), "running");
+
+        let Indexed {
+            words_doc_indexes, ..
+        } = indexer.build();
+
+        let the = words_doc_indexes.get(&b"the"[..]).unwrap();
+        assert_eq!(the.len(), 1);
+        assert_eq!(the[0].attribute, 1);
+
+        let run = words_doc_indexes.get(&b"run"[..]).unwrap();
+        assert_eq!(run.len(), 1);
+        assert_eq!(run[0].attribute, 0);
+
+        assert!(words_doc_indexes.get(&b"continuel"[..]).is_some());
+    }
+
+    #[test]
+    fn infix_words() {
+        let mut indexer = RawIndexer::new(fst::Set::default());
+        indexer.set_infix_attributes(vec![SchemaAttr(1)]);
+
+        let docid = DocumentId(0);
+        indexer.index_text(docid, SchemaAttr(0), "telephone");
+        indexer.index_text(docid, SchemaAttr(1), "smartphone");
+
+        let Indexed {
+            infix_words_doc_indexes, ..
+        } = indexer.build();
+
+        let phone = infix_words_doc_indexes.get(&b"phone"[..]).unwrap();
+        assert_eq!(phone.len(), 1);
+        assert_eq!(phone[0].attribute, 1);
+
+        assert!(infix_words_doc_indexes.get(&b"artphone"[..]).is_some());
+        assert!(infix_words_doc_indexes.get(&b"smartphone"[..]).is_none());
+        assert!(infix_words_doc_indexes.get(&b"ne"[..]).is_none());
+        assert!(infix_words_doc_indexes.get(&b"ephone"[..]).is_none());
+    }
+
+    #[test]
+    fn phonetic_words() {
+        let mut indexer = RawIndexer::new(fst::Set::default());
+        indexer.set_phonetic_attributes(vec![SchemaAttr(0)]);
+
+        let docid = DocumentId(0);
+        indexer.index_text(docid, SchemaAttr(0), "Kathryn Smith");
+        indexer.index_text(docid, SchemaAttr(1), "Philip");
+
+        let Indexed {
+            phonetic_words_doc_indexes, ..
+        } = indexer.build();
+
+        let kathryn = phonetic_words_doc_indexes.get(&b"K0RN"[..]).unwrap();
+        assert_eq!(kathryn.len(), 1);
+        assert_eq!(kathryn[0].word_index, 0);
+
+        assert!(phonetic_words_doc_indexes.get(&b"SM0"[..]).is_some());
+        assert!(phonetic_words_doc_indexes.get(&b"FLP"[..]).is_none());
+    }
+
+    #[test]
+    fn original_forms() {
+        let mut indexer = RawIndexer::new(fst::Set::default());
+
+        let docid = DocumentId(0);
+        let long = "Pneumonoultramicroscopicsilicovolcanoconiosis";
+        indexer.index_text(docid, SchemaAttr(0), "Résumé of the US team");
+        indexer.index_text(docid, SchemaAttr(1), long);
+
+        let Indexed {
+            words_doc_indexes,
+            original_forms_doc_indexes: forms,
+            docs_words,
+            ..
+        } = indexer.build();
+
+        let resume = forms.get(&original_form_key("Résumé")).unwrap();
+        assert_eq!(resume.len(), 1);
+        assert_eq!(resume[0].word_index, 0);
+
+        assert!(forms.get(&original_form_key("US")).is_some());
+        assert!(forms.get(&original_form_key("us")).is_none());
+        assert!(forms.get(&original_form_key("resume")).is_none());
+        assert!(forms.get(&original_form_key("team")).is_none());
+
+        // the positions where the normalized words differ from the text
+        assert!(forms.get(&altered_forms_key("us")).is_some());
+        assert!(forms.get(&altered_forms_key("résumé")).is_some());
+        assert!(forms.get(&altered_forms_key("resume")).is_some());
+        assert!(forms.get(&altered_forms_key("team")).is_none());
+
+        // the original forms too long to be indexed are not stored
+        let lower = long.to_lowercase();
+        assert!(forms.get(&original_form_key(long)).is_none());
+        assert!(forms.get(&altered_forms_key(&lower)).is_some());
+
+        // the original forms are not indexed words
+        assert!(words_doc_indexes.get(&original_form_key("US")).is_none());
+        assert!(words_doc_indexes.get(&b"us"[..]).is_some());
+        assert!(!docs_words[&docid].contains(&original_form_key("US")));
+    }
+
+    #[test]
+    fn suffixes() {
+        let suffixes: Vec<_> = word_suffixes("phone").collect();
+        assert_eq!(suffixes, vec!["hone", "one"]);
+        assert_eq!(word_suffixes("éte").count(), 0);
+    }
 }
@@ -1,5 +1,6 @@
 use std::str::FromStr;
 
+use chrono::{DateTime, NaiveDate};
 use ordered_float::OrderedFloat;
 use serde::ser;
 use serde::Serialize;
@@ -9,6 +10,17 @@ use crate::Number;
 
 pub struct ConvertToNumber;
 
+// Parses an RFC 3339 date (e.g. "2019-10-21T16:44:08Z")
+// or a day (e.g. "2019-10-21") into a timestamp in seconds.
+fn parse_timestamp(value: &str) -> Option<i64> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Some(date.timestamp());
+    }
+
+    let day = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+    Some(day.and_hms(0, 0, 0).timestamp())
+}
+
 impl ser::Serializer for ConvertToNumber {
     type Ok = Number;
     type Error = SerializerError;
@@ -69,7 +81,14 @@ impl ser::Serializer for ConvertToNumber {
     }
 
     fn serialize_str(self, value: &str) -> Result<Self::Ok, Self::Error> {
-        Ok(Number::from_str(value)?)
+        match Number::from_str(value) {
+            Ok(number) => Ok(number),
+            // the dates are ranked by their timestamp
+            Err(error) => match parse_timestamp(value) {
+                Some(timestamp) => Ok(Number::Signed(timestamp)),
+                None => Err(error.into()),
+            },
+        }
     }
 
     fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok, Self::Error> {
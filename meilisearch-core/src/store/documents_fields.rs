@@ -1,14 +1,25 @@
+use std::collections::HashMap;
+
 use heed::types::{ByteSlice, OwnedType};
 use crate::database::MainT;
 use heed::Result as ZResult;
 use meilisearch_schema::SchemaAttr;
+use roaring::RoaringTreemap;
+use serde_json::Value;
 
 use super::DocumentAttrKey;
 use crate::DocumentId;
 
+/// The version of the filter values, the indexes without a
+/// version have not indexed the values of their documents fields.
+pub const FILTER_VALUES_VERSION: u32 = 1;
+
+/// The documents fields along with the documents ids of each value
+/// of each attribute, the values the documents can be filtered on.
 #[derive(Copy, Clone)]
 pub struct DocumentsFields {
     pub(crate) documents_fields: heed::Database<OwnedType<DocumentAttrKey>, ByteSlice>,
+    pub(crate) filter_values: heed::Database<ByteSlice, ByteSlice>,
 }
 
 impl DocumentsFields {
@@ -34,7 +45,8 @@ impl DocumentsFields {
     }
 
     pub fn clear(self, writer: &mut heed::RwTxn<MainT>) -> ZResult<()> {
-        self.documents_fields.clear(writer)
+        self.documents_fields.clear(writer)?;
+        self.filter_values.clear(writer)
     }
 
     pub fn document_attribute<'txn>(
@@ -57,6 +69,161 @@ impl DocumentsFields {
         let iter = self.documents_fields.range(reader, &(start..=end))?;
         Ok(DocumentFieldsIter { iter })
     }
+
+    pub fn all_documents_fields<'txn>(
+        self,
+        reader: &'txn heed::RoTxn<MainT>,
+    ) -> ZResult<AllDocumentsFieldsIter<'txn>> {
+        let iter = self.documents_fields.iter(reader)?;
+        Ok(AllDocumentsFieldsIter { iter })
+    }
+
+    /// Returns the documents having the given value in this attribute, the
+    /// value must be normalized the way `filter_values` normalizes them.
+    pub fn filter_value_documents(
+        self,
+        reader: &heed::RoTxn<MainT>,
+        attribute: SchemaAttr,
+        value: &str,
+    ) -> ZResult<Option<RoaringTreemap>> {
+        let key = filter_value_key(attribute, value);
+        match self.filter_values.get(reader, &key)? {
+            Some(bytes) => decode_docids(bytes).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Associates the documents with the values of their fields,
+    /// the fields of these documents must already be stored.
+    pub fn put_filter_values(
+        self,
+        writer: &mut heed::RwTxn<MainT>,
+        documents_ids: &[DocumentId],
+    ) -> ZResult<()> {
+        let mut values = HashMap::new();
+        for document_id in documents_ids {
+            for result in self.document_fields(writer, *document_id)? {
+                let (attribute, bytes) = result?;
+                insert_filter_values(&mut values, *document_id, attribute, bytes);
+            }
+        }
+
+        self.merge_filter_values(writer, values)
+    }
+
+    /// Dissociates the documents from the values of their fields,
+    /// the fields of these documents must still be stored.
+    pub fn del_filter_values(
+        self,
+        writer: &mut heed::RwTxn<MainT>,
+        documents_ids: &[DocumentId],
+    ) -> ZResult<()> {
+        let mut values = HashMap::new();
+        for document_id in documents_ids {
+            for result in self.document_fields(writer, *document_id)? {
+                let (attribute, bytes) = result?;
+                insert_filter_values(&mut values, *document_id, attribute, bytes);
+            }
+        }
+
+        for (key, documents) in values {
+            if let Some(bytes) = self.filter_values.get(writer, &key)? {
+                let mut docids = decode_docids(bytes)?;
+                docids.difference_with(&documents);
+
+                if docids.is_empty() {
+                    self.filter_values.delete(writer, &key)?;
+                } else {
+                    self.filter_values
+                        .put(writer, &key, &encode_docids(&docids))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Associates all the documents with the values of their fields, it is used to
+    /// index the values of the documents stored by the previous versions.
+    pub fn put_all_filter_values(self, writer: &mut heed::RwTxn<MainT>) -> ZResult<()> {
+        let mut values = HashMap::new();
+        for result in self.all_documents_fields(writer)? {
+            let (document_id, attribute, bytes) = result?;
+            insert_filter_values(&mut values, document_id, attribute, bytes);
+        }
+
+        self.merge_filter_values(writer, values)
+    }
+
+    fn merge_filter_values(
+        self,
+        writer: &mut heed::RwTxn<MainT>,
+        values: HashMap<Vec<u8>, RoaringTreemap>,
+    ) -> ZResult<()> {
+        for (key, documents) in values {
+            let docids = match self.filter_values.get(writer, &key)? {
+                Some(bytes) => {
+                    let mut docids = decode_docids(bytes)?;
+                    docids.union_with(&documents);
+                    docids
+                }
+                None => documents,
+            };
+
+            self.filter_values
+                .put(writer, &key, &encode_docids(&docids))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the values a filter can be equal to for a field to match, the
+/// strings are lowercased, the booleans are either `true` or `false` and
+/// the strings of the arrays are kept as they are.
+pub fn filter_values(bytes: &[u8]) -> Vec<String> {
+    match serde_json::from_slice(bytes) {
+        Ok(Value::String(s)) => vec![s.to_lowercase()],
+        Ok(Value::Bool(b)) => vec![b.to_string()],
+        Ok(Value::Array(a)) => a
+            .into_iter()
+            .filter_map(|v| match v {
+                Value::String(s) => Some(s),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn insert_filter_values(
+    values: &mut HashMap<Vec<u8>, RoaringTreemap>,
+    document_id: DocumentId,
+    attribute: SchemaAttr,
+    bytes: &[u8],
+) {
+    for value in filter_values(bytes) {
+        values
+            .entry(filter_value_key(attribute, &value))
+            .or_insert_with(RoaringTreemap::new)
+            .insert(document_id.0);
+    }
+}
+
+fn filter_value_key(attribute: SchemaAttr, value: &str) -> Vec<u8> {
+    let mut key = attribute.0.to_be_bytes().to_vec();
+    key.extend_from_slice(value.as_bytes());
+    key
+}
+
+fn encode_docids(docids: &RoaringTreemap) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(docids.serialized_size());
+    docids.serialize_into(&mut bytes).unwrap();
+    bytes
+}
+
+fn decode_docids(bytes: &[u8]) -> ZResult<RoaringTreemap> {
+    RoaringTreemap::deserialize_from(bytes).map_err(|_| heed::Error::Decoding)
 }
 
 pub struct DocumentFieldsIter<'txn> {
@@ -77,3 +244,23 @@ impl<'txn> Iterator for DocumentFieldsIter<'txn> {
         }
     }
 }
+
+pub struct AllDocumentsFieldsIter<'txn> {
+    iter: heed::RoIter<'txn, OwnedType<DocumentAttrKey>, ByteSlice>,
+}
+
+impl<'txn> Iterator for AllDocumentsFieldsIter<'txn> {
+    type Item = ZResult<(DocumentId, SchemaAttr, &'txn [u8])>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.iter.next() {
+            Some(Ok((key, bytes))) => {
+                let docid = DocumentId(key.docid.get());
+                let attr = SchemaAttr(key.attr.get());
+                Some(Ok((docid, attr, bytes)))
+            }
+            Some(Err(e)) => Some(Err(e)),
+            None => None,
+        }
+    }
+}
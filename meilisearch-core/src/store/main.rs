@@ -1,25 +1,45 @@
+use crate::analyzer::AnalyzerV0;
 use crate::database::MainT;
-use crate::RankedMap;
+use crate::{Analyzer, Language, QueryRule, RankedMap};
 use chrono::{DateTime, Utc};
 use heed::types::{ByteSlice, OwnedType, SerdeBincode, Str};
 use heed::Result as ZResult;
-use meilisearch_schema::Schema;
-use std::collections::HashMap;
+use meilisearch_schema::{Schema, SchemaV0};
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 
+const ANALYZER_KEY: &str = "analyzer";
+const ANALYZER_VERSION_KEY: &str = "analyzer-version";
 const CREATED_AT_KEY: &str = "created-at";
 const CUSTOMS_KEY: &str = "customs-key";
 const FIELDS_FREQUENCY_KEY: &str = "fields-frequency";
+const FILTER_VALUES_VERSION_KEY: &str = "filter-values-version";
+const INFIX_WORDS_KEY: &str = "infix-words";
+const LANGUAGE_ANALYZERS_KEY: &str = "language-analyzers";
 const NAME_KEY: &str = "name";
 const NUMBER_OF_DOCUMENTS_KEY: &str = "number-of-documents";
+const PHONETIC_WORDS_KEY: &str = "phonetic-words";
+const POSTINGS_LISTS_VERSION_KEY: &str = "postings-lists-version";
+const QUERY_RULES_KEY: &str = "query-rules";
 const RANKED_MAP_KEY: &str = "ranked-map";
 const SCHEMA_KEY: &str = "schema";
+const SCHEMA_VERSION_KEY: &str = "schema-version";
 const STOP_WORDS_KEY: &str = "stop-words";
 const SYNONYMS_KEY: &str = "synonyms";
 const UPDATED_AT_KEY: &str = "updated-at";
 const WORDS_KEY: &str = "words";
 
+/// The version of the binary layout of the stored schema, the indexes
+/// without a version store their schema in the layout of `SchemaV0`.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The version of the binary layout of the stored analyzers, the indexes
+/// without a version store their analyzer in the layout of `AnalyzerV0`.
+pub const ANALYZER_VERSION: u32 = 1;
+
 pub type FreqsMap = HashMap<String, usize>;
+pub type LanguageAnalyzers = BTreeMap<Language, Analyzer>;
+pub type QueryRules = Vec<QueryRule>;
 type SerdeFreqsMap = SerdeBincode<FreqsMap>;
 type SerdeDatetime = SerdeBincode<DateTime<Utc>>;
 
@@ -62,6 +82,48 @@ impl Main {
         self.main.get::<_, Str, SerdeDatetime>(reader, UPDATED_AT_KEY)
     }
 
+    pub fn put_analyzer(self, writer: &mut heed::RwTxn<MainT>, analyzer: &Analyzer) -> ZResult<()> {
+        self.main
+            .put::<_, Str, SerdeBincode<Analyzer>>(writer, ANALYZER_KEY, analyzer)
+    }
+
+    pub fn analyzer(self, reader: &heed::RoTxn<MainT>) -> ZResult<Option<Analyzer>> {
+        self.main
+            .get::<_, Str, SerdeBincode<Analyzer>>(reader, ANALYZER_KEY)
+    }
+
+    /// Reads an analyzer stored by a previous version, in the layout of `AnalyzerV0`.
+    pub fn analyzer_v0(self, reader: &heed::RoTxn<MainT>) -> ZResult<Option<Analyzer>> {
+        let analyzer = self
+            .main
+            .get::<_, Str, SerdeBincode<AnalyzerV0>>(reader, ANALYZER_KEY)?;
+        Ok(analyzer.map(Analyzer::from))
+    }
+
+    pub fn put_analyzer_version(self, writer: &mut heed::RwTxn<MainT>, version: u32) -> ZResult<()> {
+        self.main
+            .put::<_, Str, OwnedType<u32>>(writer, ANALYZER_VERSION_KEY, &version)
+    }
+
+    pub fn analyzer_version(self, reader: &heed::RoTxn<MainT>) -> ZResult<Option<u32>> {
+        self.main
+            .get::<_, Str, OwnedType<u32>>(reader, ANALYZER_VERSION_KEY)
+    }
+
+    pub fn put_language_analyzers(
+        self,
+        writer: &mut heed::RwTxn<MainT>,
+        analyzers: &LanguageAnalyzers,
+    ) -> ZResult<()> {
+        self.main
+            .put::<_, Str, SerdeBincode<LanguageAnalyzers>>(writer, LANGUAGE_ANALYZERS_KEY, analyzers)
+    }
+
+    pub fn language_analyzers(self, reader: &heed::RoTxn<MainT>) -> ZResult<Option<LanguageAnalyzers>> {
+        self.main
+            .get::<_, Str, SerdeBincode<LanguageAnalyzers>>(reader, LANGUAGE_ANALYZERS_KEY)
+    }
+
     pub fn put_words_fst(self, writer: &mut heed::RwTxn<MainT>, fst: &fst::Set) -> ZResult<()> {
         let bytes = fst.as_fst().as_bytes();
         self.main.put::<_, Str, ByteSlice>(writer, WORDS_KEY, bytes)
@@ -79,6 +141,40 @@ impl Main {
         }
     }
 
+    pub fn put_infix_words_fst(self, writer: &mut heed::RwTxn<MainT>, fst: &fst::Set) -> ZResult<()> {
+        let bytes = fst.as_fst().as_bytes();
+        self.main.put::<_, Str, ByteSlice>(writer, INFIX_WORDS_KEY, bytes)
+    }
+
+    pub fn infix_words_fst(self, reader: &heed::RoTxn<MainT>) -> ZResult<Option<fst::Set>> {
+        match self.main.get::<_, Str, ByteSlice>(reader, INFIX_WORDS_KEY)? {
+            Some(bytes) => {
+                let len = bytes.len();
+                let bytes = Arc::new(bytes.to_owned());
+                let fst = fst::raw::Fst::from_shared_bytes(bytes, 0, len).unwrap();
+                Ok(Some(fst::Set::from(fst)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub fn put_phonetic_words_fst(self, writer: &mut heed::RwTxn<MainT>, fst: &fst::Set) -> ZResult<()> {
+        let bytes = fst.as_fst().as_bytes();
+        self.main.put::<_, Str, ByteSlice>(writer, PHONETIC_WORDS_KEY, bytes)
+    }
+
+    pub fn phonetic_words_fst(self, reader: &heed::RoTxn<MainT>) -> ZResult<Option<fst::Set>> {
+        match self.main.get::<_, Str, ByteSlice>(reader, PHONETIC_WORDS_KEY)? {
+            Some(bytes) => {
+                let len = bytes.len();
+                let bytes = Arc::new(bytes.to_owned());
+                let fst = fst::raw::Fst::from_shared_bytes(bytes, 0, len).unwrap();
+                Ok(Some(fst::Set::from(fst)))
+            }
+            None => Ok(None),
+        }
+    }
+
     pub fn put_schema(self, writer: &mut heed::RwTxn<MainT>, schema: &Schema) -> ZResult<()> {
         self.main
             .put::<_, Str, SerdeBincode<Schema>>(writer, SCHEMA_KEY, schema)
@@ -89,6 +185,24 @@ impl Main {
             .get::<_, Str, SerdeBincode<Schema>>(reader, SCHEMA_KEY)
     }
 
+    /// Reads a schema stored by a previous version, in the layout of `SchemaV0`.
+    pub fn schema_v0(self, reader: &heed::RoTxn<MainT>) -> ZResult<Option<Schema>> {
+        let schema = self
+            .main
+            .get::<_, Str, SerdeBincode<SchemaV0>>(reader, SCHEMA_KEY)?;
+        Ok(schema.map(Schema::from))
+    }
+
+    pub fn put_schema_version(self, writer: &mut heed::RwTxn<MainT>, version: u32) -> ZResult<()> {
+        self.main
+            .put::<_, Str, OwnedType<u32>>(writer, SCHEMA_VERSION_KEY, &version)
+    }
+
+    pub fn schema_version(self, reader: &heed::RoTxn<MainT>) -> ZResult<Option<u32>> {
+        self.main
+            .get::<_, Str, OwnedType<u32>>(reader, SCHEMA_VERSION_KEY)
+    }
+
     pub fn put_ranked_map(self, writer: &mut heed::RwTxn<MainT>, ranked_map: &RankedMap) -> ZResult<()> {
         self.main
             .put::<_, Str, SerdeBincode<RankedMap>>(writer, RANKED_MAP_KEY, &ranked_map)
@@ -154,6 +268,34 @@ impl Main {
         }
     }
 
+    pub fn put_postings_lists_version(
+        self,
+        writer: &mut heed::RwTxn<MainT>,
+        version: u32,
+    ) -> ZResult<()> {
+        self.main
+            .put::<_, Str, OwnedType<u32>>(writer, POSTINGS_LISTS_VERSION_KEY, &version)
+    }
+
+    pub fn postings_lists_version(self, reader: &heed::RoTxn<MainT>) -> ZResult<Option<u32>> {
+        self.main
+            .get::<_, Str, OwnedType<u32>>(reader, POSTINGS_LISTS_VERSION_KEY)
+    }
+
+    pub fn put_filter_values_version(
+        self,
+        writer: &mut heed::RwTxn<MainT>,
+        version: u32,
+    ) -> ZResult<()> {
+        self.main
+            .put::<_, Str, OwnedType<u32>>(writer, FILTER_VALUES_VERSION_KEY, &version)
+    }
+
+    pub fn filter_values_version(self, reader: &heed::RoTxn<MainT>) -> ZResult<Option<u32>> {
+        self.main
+            .get::<_, Str, OwnedType<u32>>(reader, FILTER_VALUES_VERSION_KEY)
+    }
+
     pub fn put_fields_frequency(
         self,
         writer: &mut heed::RwTxn<MainT>,
@@ -181,4 +323,14 @@ impl Main {
     pub fn customs<'txn>(self, reader: &'txn heed::RoTxn<MainT>) -> ZResult<Option<&'txn [u8]>> {
         self.main.get::<_, Str, ByteSlice>(reader, CUSTOMS_KEY)
     }
+
+    pub fn put_query_rules(self, writer: &mut heed::RwTxn<MainT>, rules: &QueryRules) -> ZResult<()> {
+        self.main
+            .put::<_, Str, SerdeBincode<QueryRules>>(writer, QUERY_RULES_KEY, rules)
+    }
+
+    pub fn query_rules(self, reader: &heed::RoTxn<MainT>) -> ZResult<Option<QueryRules>> {
+        self.main
+            .get::<_, Str, SerdeBincode<QueryRules>>(reader, QUERY_RULES_KEY)
+    }
 }
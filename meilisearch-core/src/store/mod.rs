@@ -8,13 +8,16 @@ mod updates;
 mod updates_results;
 
 pub use self::docs_words::DocsWords;
-pub use self::documents_fields::{DocumentFieldsIter, DocumentsFields};
+pub use self::documents_fields::{
+    filter_values, AllDocumentsFieldsIter, DocumentFieldsIter, DocumentsFields,
+    FILTER_VALUES_VERSION,
+};
 pub use self::documents_fields_counts::{
     DocumentFieldsCountsIter, DocumentsFieldsCounts, DocumentsIdsIter,
 };
-pub use self::main::Main;
-pub use self::postings_lists::PostingsLists;
-pub use self::synonyms::Synonyms;
+pub use self::main::{Main, ANALYZER_VERSION, SCHEMA_VERSION};
+pub use self::postings_lists::{Postings, PostingsLists, POSTINGS_LISTS_VERSION};
+pub use self::synonyms::{synonym_penalty, synonym_weight, Synonyms, MAX_SYNONYM_PENALTY};
 pub use self::updates::Updates;
 pub use self::updates_results::UpdatesResults;
 
@@ -29,7 +32,9 @@ use crate::criterion::Criteria;
 use crate::database::{UpdateEvent, UpdateEventsEmitter};
 use crate::database::{MainT, UpdateT};
 use crate::serde::Deserializer;
-use crate::{query_builder::QueryBuilder, update, DocumentId, Error, MResult};
+use crate::{
+    query_builder::QueryBuilder, update, Analyzer, DocumentId, Error, Language, MResult, QueryRule,
+};
 
 type BEU64 = zerocopy::U64<byteorder::BigEndian>;
 type BEU16 = zerocopy::U16<byteorder::BigEndian>;
@@ -58,10 +63,26 @@ fn postings_lists_name(name: &str) -> String {
     format!("store-{}-postings-lists", name)
 }
 
+fn infix_postings_lists_name(name: &str) -> String {
+    format!("store-{}-infix-postings-lists", name)
+}
+
+fn phonetic_postings_lists_name(name: &str) -> String {
+    format!("store-{}-phonetic-postings-lists", name)
+}
+
+fn original_forms_postings_lists_name(name: &str) -> String {
+    format!("store-{}-original-forms-postings-lists", name)
+}
+
 fn documents_fields_name(name: &str) -> String {
     format!("store-{}-documents-fields", name)
 }
 
+fn filter_values_name(name: &str) -> String {
+    format!("store-{}-filter-values", name)
+}
+
 fn documents_fields_counts_name(name: &str) -> String {
     format!("store-{}-documents-fields-counts", name)
 }
@@ -86,6 +107,9 @@ fn updates_results_name(name: &str) -> String {
 pub struct Index {
     pub main: Main,
     pub postings_lists: PostingsLists,
+    pub infix_postings_lists: PostingsLists,
+    pub phonetic_postings_lists: PostingsLists,
+    pub original_forms_postings_lists: PostingsLists,
     pub documents_fields: DocumentsFields,
     pub documents_fields_counts: DocumentsFieldsCounts,
     pub synonyms: Synonyms,
@@ -145,11 +169,41 @@ impl Index {
         update::push_schema_update(writer, self.updates, self.updates_results, schema)
     }
 
+    pub fn analyzer_update(&self, writer: &mut heed::RwTxn<UpdateT>, analyzer: Analyzer) -> MResult<u64> {
+        let _ = self.updates_notifier.send(UpdateEvent::NewUpdate);
+        update::push_analyzer_update(writer, self.updates, self.updates_results, analyzer)
+    }
+
+    pub fn language_analyzer_update(
+        &self,
+        writer: &mut heed::RwTxn<UpdateT>,
+        language: Language,
+        analyzer: Analyzer,
+    ) -> MResult<u64> {
+        let _ = self.updates_notifier.send(UpdateEvent::NewUpdate);
+        update::push_language_analyzer_update(
+            writer,
+            self.updates,
+            self.updates_results,
+            language,
+            analyzer,
+        )
+    }
+
     pub fn customs_update(&self, writer: &mut heed::RwTxn<UpdateT>, customs: Vec<u8>) -> ZResult<u64> {
         let _ = self.updates_notifier.send(UpdateEvent::NewUpdate);
         update::push_customs_update(writer, self.updates, self.updates_results, customs)
     }
 
+    pub fn query_rules_update(
+        &self,
+        writer: &mut heed::RwTxn<UpdateT>,
+        rules: Vec<QueryRule>,
+    ) -> ZResult<u64> {
+        let _ = self.updates_notifier.send(UpdateEvent::NewUpdate);
+        update::push_query_rules_update(writer, self.updates, self.updates_results, rules)
+    }
+
     pub fn documents_addition<D>(&self) -> update::DocumentsAddition<D> {
         update::DocumentsAddition::new(
             self.updates,
@@ -218,6 +272,13 @@ impl Index {
         }
     }
 
+    pub fn last_processed_update_id(&self, reader: &heed::RoTxn<UpdateT>) -> MResult<Option<u64>> {
+        match self.updates_results.last_update(reader)? {
+            Some((id, _)) => Ok(Some(id)),
+            None => Ok(None),
+        }
+    }
+
     pub fn update_status(
         &self,
         reader: &heed::RoTxn<UpdateT>,
@@ -258,6 +319,9 @@ impl Index {
         QueryBuilder::new(
             self.main,
             self.postings_lists,
+            self.infix_postings_lists,
+            self.phonetic_postings_lists,
+            self.original_forms_postings_lists,
             self.documents_fields_counts,
             self.synonyms,
         )
@@ -270,6 +334,9 @@ impl Index {
         QueryBuilder::with_criteria(
             self.main,
             self.postings_lists,
+            self.infix_postings_lists,
+            self.phonetic_postings_lists,
+            self.original_forms_postings_lists,
             self.documents_fields_counts,
             self.synonyms,
             criteria,
@@ -286,7 +353,11 @@ pub fn create(
     // create all the store names
     let main_name = main_name(name);
     let postings_lists_name = postings_lists_name(name);
+    let infix_postings_lists_name = infix_postings_lists_name(name);
+    let phonetic_postings_lists_name = phonetic_postings_lists_name(name);
+    let original_forms_postings_lists_name = original_forms_postings_lists_name(name);
     let documents_fields_name = documents_fields_name(name);
+    let filter_values_name = filter_values_name(name);
     let documents_fields_counts_name = documents_fields_counts_name(name);
     let synonyms_name = synonyms_name(name);
     let docs_words_name = docs_words_name(name);
@@ -296,7 +367,12 @@ pub fn create(
     // open all the stores
     let main = env.create_poly_database(Some(&main_name))?;
     let postings_lists = env.create_database(Some(&postings_lists_name))?;
+    let infix_postings_lists = env.create_database(Some(&infix_postings_lists_name))?;
+    let phonetic_postings_lists = env.create_database(Some(&phonetic_postings_lists_name))?;
+    let original_forms_postings_lists =
+        env.create_database(Some(&original_forms_postings_lists_name))?;
     let documents_fields = env.create_database(Some(&documents_fields_name))?;
+    let filter_values = env.create_database(Some(&filter_values_name))?;
     let documents_fields_counts = env.create_database(Some(&documents_fields_counts_name))?;
     let synonyms = env.create_database(Some(&synonyms_name))?;
     let docs_words = env.create_database(Some(&docs_words_name))?;
@@ -306,7 +382,19 @@ pub fn create(
     Ok(Index {
         main: Main { main },
         postings_lists: PostingsLists { postings_lists },
-        documents_fields: DocumentsFields { documents_fields },
+        infix_postings_lists: PostingsLists {
+            postings_lists: infix_postings_lists,
+        },
+        phonetic_postings_lists: PostingsLists {
+            postings_lists: phonetic_postings_lists,
+        },
+        original_forms_postings_lists: PostingsLists {
+            postings_lists: original_forms_postings_lists,
+        },
+        documents_fields: DocumentsFields {
+            documents_fields,
+            filter_values,
+        },
         documents_fields_counts: DocumentsFieldsCounts {
             documents_fields_counts,
         },
@@ -327,7 +415,11 @@ pub fn open(
     // create all the store names
     let main_name = main_name(name);
     let postings_lists_name = postings_lists_name(name);
+    let infix_postings_lists_name = infix_postings_lists_name(name);
+    let phonetic_postings_lists_name = phonetic_postings_lists_name(name);
+    let original_forms_postings_lists_name = original_forms_postings_lists_name(name);
     let documents_fields_name = documents_fields_name(name);
+    let filter_values_name = filter_values_name(name);
     let documents_fields_counts_name = documents_fields_counts_name(name);
     let synonyms_name = synonyms_name(name);
     let docs_words_name = docs_words_name(name);
@@ -343,10 +435,18 @@ pub fn open(
         Some(postings_lists) => postings_lists,
         None => return Ok(None),
     };
+    // the indexes created by the previous versions do not have infix, phonetic
+    // nor original forms postings lists, empty databases are created for them
+    let infix_postings_lists = env.create_database(Some(&infix_postings_lists_name))?;
+    let phonetic_postings_lists = env.create_database(Some(&phonetic_postings_lists_name))?;
+    let original_forms_postings_lists =
+        env.create_database(Some(&original_forms_postings_lists_name))?;
     let documents_fields = match env.open_database(Some(&documents_fields_name))? {
         Some(documents_fields) => documents_fields,
         None => return Ok(None),
     };
+    // the filter values are indexed by the upgrade of the previous indexes
+    let filter_values = env.create_database(Some(&filter_values_name))?;
     let documents_fields_counts = match env.open_database(Some(&documents_fields_counts_name))? {
         Some(documents_fields_counts) => documents_fields_counts,
         None => return Ok(None),
@@ -371,7 +471,19 @@ pub fn open(
     Ok(Some(Index {
         main: Main { main },
         postings_lists: PostingsLists { postings_lists },
-        documents_fields: DocumentsFields { documents_fields },
+        infix_postings_lists: PostingsLists {
+            postings_lists: infix_postings_lists,
+        },
+        phonetic_postings_lists: PostingsLists {
+            postings_lists: phonetic_postings_lists,
+        },
+        original_forms_postings_lists: PostingsLists {
+            postings_lists: original_forms_postings_lists,
+        },
+        documents_fields: DocumentsFields {
+            documents_fields,
+            filter_values,
+        },
         documents_fields_counts: DocumentsFieldsCounts {
             documents_fields_counts,
         },
@@ -383,6 +495,39 @@ pub fn open(
     }))
 }
 
+/// Converts the stores of an index written by a previous version to the current formats.
+pub fn upgrade(writer: &mut heed::RwTxn<MainT>, index: &Index) -> MResult<()> {
+    if index.main.postings_lists_version(writer)?.is_none() {
+        index.postings_lists.upgrade_from_v0(writer)?;
+        index
+            .main
+            .put_postings_lists_version(writer, POSTINGS_LISTS_VERSION)?;
+    }
+
+    if index.main.schema_version(writer)?.is_none() {
+        if let Some(schema) = index.main.schema_v0(writer)? {
+            index.main.put_schema(writer, &schema)?;
+        }
+        index.main.put_schema_version(writer, SCHEMA_VERSION)?;
+    }
+
+    if index.main.filter_values_version(writer)?.is_none() {
+        index.documents_fields.put_all_filter_values(writer)?;
+        index
+            .main
+            .put_filter_values_version(writer, FILTER_VALUES_VERSION)?;
+    }
+
+    if index.main.analyzer_version(writer)?.is_none() {
+        if let Some(analyzer) = index.main.analyzer_v0(writer)? {
+            index.main.put_analyzer(writer, &analyzer)?;
+        }
+        index.main.put_analyzer_version(writer, ANALYZER_VERSION)?;
+    }
+
+    Ok(())
+}
+
 pub fn clear(
     writer: &mut heed::RwTxn<MainT>,
     update_writer: &mut heed::RwTxn<UpdateT>,
@@ -391,6 +536,9 @@ pub fn clear(
     // clear all the stores
     index.main.clear(writer)?;
     index.postings_lists.clear(writer)?;
+    index.infix_postings_lists.clear(writer)?;
+    index.phonetic_postings_lists.clear(writer)?;
+    index.original_forms_postings_lists.clear(writer)?;
     index.documents_fields.clear(writer)?;
     index.documents_fields_counts.clear(writer)?;
     index.synonyms.clear(writer)?;
@@ -1,13 +1,30 @@
-use crate::DocIndex;
+use std::convert::TryFrom;
+use std::mem;
+use std::ops::Bound;
+
 use crate::database::MainT;
-use heed::types::{ByteSlice, CowSlice};
+use crate::{DocIndex, DocumentId};
+use heed::types::ByteSlice;
 use heed::Result as ZResult;
+use roaring::RoaringTreemap;
 use sdset::{Set, SetBuf};
-use std::borrow::Cow;
+use slice_group_by::GroupBy;
+
+/// The version of the format in which the postings lists are stored, the
+/// indexes without a version store the raw documents indexes of the words.
+pub const POSTINGS_LISTS_VERSION: u32 = 1;
+
+/// The postings of a word: the documents it appears in,
+/// stored as a bitmap, and its positions in these documents.
+#[derive(Debug, Default, Clone)]
+pub struct Postings {
+    pub docids: RoaringTreemap,
+    pub matches: SetBuf<DocIndex>,
+}
 
 #[derive(Copy, Clone)]
 pub struct PostingsLists {
-    pub(crate) postings_lists: heed::Database<ByteSlice, CowSlice<DocIndex>>,
+    pub(crate) postings_lists: heed::Database<ByteSlice, ByteSlice>,
 }
 
 impl PostingsLists {
@@ -17,7 +34,8 @@ impl PostingsLists {
         word: &[u8],
         words_indexes: &Set<DocIndex>,
     ) -> ZResult<()> {
-        self.postings_lists.put(writer, word, words_indexes)
+        let bytes = encode_postings(words_indexes);
+        self.postings_lists.put(writer, word, &bytes)
     }
 
     pub fn del_postings_list(self, writer: &mut heed::RwTxn<MainT>, word: &[u8]) -> ZResult<bool> {
@@ -28,15 +46,420 @@ impl PostingsLists {
         self.postings_lists.clear(writer)
     }
 
-    pub fn postings_list<'txn>(
+    /// Converts the postings lists stored as raw documents
+    /// indexes by the previous versions to the current format.
+    pub fn upgrade_from_v0(self, writer: &mut heed::RwTxn<MainT>) -> ZResult<()> {
+        let mut postings_lists = Vec::new();
+        for result in self.postings_lists.iter(&*writer)? {
+            let (word, bytes) = result?;
+            let matches = decode_v0_postings(bytes).ok_or(heed::Error::Decoding)?;
+            postings_lists.push((word.to_vec(), matches));
+        }
+
+        for (word, matches) in postings_lists {
+            self.put_postings_list(writer, &word, &matches)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn postings_list(
         self,
-        reader: &'txn heed::RoTxn<MainT>,
+        reader: &heed::RoTxn<MainT>,
         word: &[u8],
-    ) -> ZResult<Option<Cow<'txn, Set<DocIndex>>>> {
+    ) -> ZResult<Option<Postings>> {
         match self.postings_lists.get(reader, word)? {
-            Some(Cow::Borrowed(slice)) => Ok(Some(Cow::Borrowed(Set::new_unchecked(slice)))),
-            Some(Cow::Owned(vec)) => Ok(Some(Cow::Owned(SetBuf::new_unchecked(vec)))),
+            Some(bytes) => match decode_postings(bytes, None) {
+                Some(postings) => Ok(Some(postings)),
+                None => Err(heed::Error::Decoding),
+            },
             None => Ok(None),
         }
     }
+
+    /// Only decompresses the positions of the word in the candidates documents,
+    /// the documents ids of the postings list are all the documents of the word.
+    pub fn postings_list_within(
+        self,
+        reader: &heed::RoTxn<MainT>,
+        word: &[u8],
+        candidates: &RoaringTreemap,
+    ) -> ZResult<Option<Postings>> {
+        match self.postings_lists.get(reader, word)? {
+            Some(bytes) => match decode_postings(bytes, Some(candidates)) {
+                Some(postings) => Ok(Some(postings)),
+                None => Err(heed::Error::Decoding),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the words of the postings lists between `start`, included, and `end`, excluded.
+    pub fn words_range(
+        self,
+        reader: &heed::RoTxn<MainT>,
+        start: &[u8],
+        end: &[u8],
+    ) -> ZResult<Vec<Vec<u8>>> {
+        let range = (Bound::Included(start), Bound::Excluded(end));
+        let mut words = Vec::new();
+        for result in self.postings_lists.range(reader, &range)? {
+            let (word, _) = result?;
+            words.push(word.to_vec());
+        }
+        Ok(words)
+    }
+
+    /// Only retrieves the documents ids of the postings list, without
+    /// paying the cost of decompressing the positions of the word.
+    pub fn postings_docids(
+        self,
+        reader: &heed::RoTxn<MainT>,
+        word: &[u8],
+    ) -> ZResult<Option<RoaringTreemap>> {
+        match self.postings_lists.get(reader, word)? {
+            Some(bytes) => match decode_docids(bytes) {
+                Some((docids, _)) => Ok(Some(docids)),
+                None => Err(heed::Error::Decoding),
+            },
+            None => Ok(None),
+        }
+    }
+}
+
+// A postings list is stored as the length of the serialized documents ids bitmap,
+// the bitmap itself and then, for each document, the number of positions followed
+// by the positions. Positions are varint encoded and the attributes and word indexes
+// are delta encoded, this is possible because the positions are sorted.
+fn encode_postings(matches: &Set<DocIndex>) -> Vec<u8> {
+    let docids: RoaringTreemap = matches.iter().map(|di| di.document_id.0).collect();
+
+    let mut bytes = Vec::with_capacity(mem::size_of::<u32>() + docids.serialized_size());
+    bytes.extend_from_slice(&(docids.serialized_size() as u32).to_be_bytes());
+    docids.serialize_into(&mut bytes).unwrap();
+
+    for group in matches.linear_group_by_key(|di| di.document_id) {
+        write_varint(&mut bytes, group.len() as u64);
+
+        let mut previous: Option<&DocIndex> = None;
+        for di in group {
+            match previous {
+                Some(prev) if prev.attribute == di.attribute => {
+                    write_varint(&mut bytes, 0);
+                    write_varint(&mut bytes, u64::from(di.word_index - prev.word_index));
+                }
+                Some(prev) => {
+                    write_varint(&mut bytes, u64::from(di.attribute - prev.attribute));
+                    write_varint(&mut bytes, u64::from(di.word_index));
+                }
+                None => {
+                    write_varint(&mut bytes, u64::from(di.attribute));
+                    write_varint(&mut bytes, u64::from(di.word_index));
+                }
+            }
+
+            write_varint(&mut bytes, u64::from(di.char_index));
+            write_varint(&mut bytes, u64::from(di.char_length));
+            previous = Some(di);
+        }
+    }
+
+    bytes
+}
+
+fn decode_docids(bytes: &[u8]) -> Option<(RoaringTreemap, &[u8])> {
+    const LEN_SIZE: usize = mem::size_of::<u32>();
+
+    if bytes.len() < LEN_SIZE {
+        return None;
+    }
+
+    let (len, bytes) = bytes.split_at(LEN_SIZE);
+    let len = u32::from_be_bytes(<[u8; LEN_SIZE]>::try_from(len).ok()?) as usize;
+
+    if bytes.len() < len {
+        return None;
+    }
+
+    let (docids, bytes) = bytes.split_at(len);
+    let docids = RoaringTreemap::deserialize_from(docids).ok()?;
+
+    Some((docids, bytes))
+}
+
+// The positions of the documents that are not candidates are skipped without
+// being decoded, each position is made of four varints.
+fn decode_postings(bytes: &[u8], candidates: Option<&RoaringTreemap>) -> Option<Postings> {
+    let (docids, mut bytes) = decode_docids(bytes)?;
+    let mut matches = Vec::new();
+
+    let last_candidate = match candidates {
+        Some(candidates) => candidates.max(),
+        None => docids.max(),
+    };
+
+    for document_id in docids.iter() {
+        if last_candidate.map_or(true, |last| document_id > last) {
+            break;
+        }
+
+        let count = read_varint(&mut bytes)?;
+        if candidates.map_or(false, |c| !c.contains(document_id)) {
+            skip_varints(&mut bytes, count.checked_mul(4)?)?;
+            continue;
+        }
+
+        let document_id = DocumentId(document_id);
+
+        let mut previous: Option<DocIndex> = None;
+        for _ in 0..count {
+            let attribute_delta = read_u16(&mut bytes)?;
+            let (attribute, word_index) = match previous {
+                Some(prev) if attribute_delta == 0 => (
+                    prev.attribute,
+                    prev.word_index.checked_add(read_u16(&mut bytes)?)?,
+                ),
+                Some(prev) => (
+                    prev.attribute.checked_add(attribute_delta)?,
+                    read_u16(&mut bytes)?,
+                ),
+                None => (attribute_delta, read_u16(&mut bytes)?),
+            };
+
+            let di = DocIndex {
+                document_id,
+                attribute,
+                word_index,
+                char_index: read_u16(&mut bytes)?,
+                char_length: read_u16(&mut bytes)?,
+            };
+
+            matches.push(di);
+            previous = Some(di);
+        }
+    }
+
+    let matches = SetBuf::new_unchecked(matches);
+    Some(Postings { docids, matches })
+}
+
+// The previous versions stored the documents indexes as they are laid out
+// in memory, the fields in declaration order and in the native endianness.
+fn decode_v0_postings(bytes: &[u8]) -> Option<SetBuf<DocIndex>> {
+    const DOC_INDEX_SIZE: usize = mem::size_of::<DocIndex>();
+
+    if bytes.len() % DOC_INDEX_SIZE != 0 {
+        return None;
+    }
+
+    let mut matches = Vec::with_capacity(bytes.len() / DOC_INDEX_SIZE);
+    for chunk in bytes.chunks(DOC_INDEX_SIZE) {
+        let (document_id, chunk) = chunk.split_at(mem::size_of::<u64>());
+        let document_id = u64::from_ne_bytes(<[u8; 8]>::try_from(document_id).ok()?);
+        let read_u16 = |i: usize| u16::from_ne_bytes([chunk[i * 2], chunk[i * 2 + 1]]);
+
+        matches.push(DocIndex {
+            document_id: DocumentId(document_id),
+            attribute: read_u16(0),
+            word_index: read_u16(1),
+            char_index: read_u16(2),
+            char_length: read_u16(3),
+        });
+    }
+
+    SetBuf::new(matches).ok()
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Option<u64> {
+    let mut value = 0;
+    let mut shift = 0;
+
+    loop {
+        let (byte, remaining) = bytes.split_first()?;
+        *bytes = remaining;
+
+        if shift >= 64 {
+            return None;
+        }
+
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+
+        shift += 7;
+    }
+}
+
+fn skip_varints(bytes: &mut &[u8], count: u64) -> Option<()> {
+    let mut remaining = count;
+    let mut len = 0;
+
+    while remaining != 0 {
+        let byte = bytes.get(len)?;
+        if byte & 0x80 == 0 {
+            remaining -= 1;
+        }
+        len += 1;
+    }
+
+    *bytes = &bytes[len..];
+    Some(())
+}
+
+fn read_u16(bytes: &mut &[u8]) -> Option<u16> {
+    read_varint(bytes).and_then(|value| u16::try_from(value).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_decode_postings() {
+        let matches = SetBuf::from_dirty(vec![
+            DocIndex {
+                document_id: DocumentId(0),
+                attribute: 0,
+                word_index: 0,
+                char_index: 0,
+                char_length: 5,
+            },
+            DocIndex {
+                document_id: DocumentId(0),
+                attribute: 0,
+                word_index: 3,
+                char_index: 20,
+                char_length: 5,
+            },
+            DocIndex {
+                document_id: DocumentId(0),
+                attribute: 2,
+                word_index: 1,
+                char_index: 7,
+                char_length: 5,
+            },
+            DocIndex {
+                document_id: DocumentId(42),
+                attribute: 1,
+                word_index: 300,
+                char_index: 1500,
+                char_length: 5,
+            },
+            DocIndex {
+                document_id: DocumentId(u64::max_value()),
+                attribute: 0,
+                word_index: 0,
+                char_index: 0,
+                char_length: 5,
+            },
+        ]);
+
+        let bytes = encode_postings(&matches);
+        let postings = decode_postings(&bytes, None).unwrap();
+
+        assert_eq!(postings.matches, matches);
+        assert_eq!(postings.docids.len(), 3);
+        assert!(postings.docids.contains(42));
+
+        let (docids, _) = decode_docids(&bytes).unwrap();
+        assert_eq!(docids, postings.docids);
+    }
+
+    #[test]
+    fn decode_candidates_postings() {
+        let matches = SetBuf::from_dirty(vec![
+            DocIndex {
+                document_id: DocumentId(0),
+                attribute: 0,
+                word_index: 0,
+                char_index: 0,
+                char_length: 5,
+            },
+            DocIndex {
+                document_id: DocumentId(0),
+                attribute: 2,
+                word_index: 1,
+                char_index: 7,
+                char_length: 5,
+            },
+            DocIndex {
+                document_id: DocumentId(42),
+                attribute: 1,
+                word_index: 300,
+                char_index: 1500,
+                char_length: 5,
+            },
+            DocIndex {
+                document_id: DocumentId(43),
+                attribute: 0,
+                word_index: 4,
+                char_index: 30,
+                char_length: 5,
+            },
+            DocIndex {
+                document_id: DocumentId(44),
+                attribute: 0,
+                word_index: 0,
+                char_index: 0,
+                char_length: 5,
+            },
+        ]);
+
+        let bytes = encode_postings(&matches);
+        let candidates: RoaringTreemap = vec![0, 43, 100].into_iter().collect();
+        let postings = decode_postings(&bytes, Some(&candidates)).unwrap();
+
+        let expected: Vec<_> = matches
+            .iter()
+            .filter(|di| candidates.contains(di.document_id.0))
+            .cloned()
+            .collect();
+        assert_eq!(postings.matches.as_slice(), &expected[..]);
+        assert_eq!(postings.docids.len(), 4);
+
+        let candidates: RoaringTreemap = vec![1, 2].into_iter().collect();
+        let postings = decode_postings(&bytes, Some(&candidates)).unwrap();
+        assert!(postings.matches.is_empty());
+    }
+
+    #[test]
+    fn decode_v0() {
+        let matches = vec![
+            DocIndex {
+                document_id: DocumentId(0),
+                attribute: 0,
+                word_index: 3,
+                char_index: 20,
+                char_length: 5,
+            },
+            DocIndex {
+                document_id: DocumentId(42),
+                attribute: 1,
+                word_index: 300,
+                char_index: 1500,
+                char_length: 5,
+            },
+        ];
+
+        let mut bytes = Vec::new();
+        for di in &matches {
+            bytes.extend_from_slice(&di.document_id.0.to_ne_bytes());
+            bytes.extend_from_slice(&di.attribute.to_ne_bytes());
+            bytes.extend_from_slice(&di.word_index.to_ne_bytes());
+            bytes.extend_from_slice(&di.char_index.to_ne_bytes());
+            bytes.extend_from_slice(&di.char_length.to_ne_bytes());
+        }
+
+        assert_eq!(decode_v0_postings(&bytes).unwrap().as_slice(), &matches[..]);
+        assert!(decode_v0_postings(&bytes[1..]).is_none());
+    }
 }
@@ -1,8 +1,27 @@
 use heed::types::ByteSlice;
 use crate::database::MainT;
+use fst::Streamer;
 use heed::Result as ZResult;
 use std::sync::Arc;
 
+/// The greatest penalty of a synonym, the penalties of the synonyms
+/// are stored as the values of the alternatives fst, a synonym equivalent
+/// to the original word has no penalty.
+pub const MAX_SYNONYM_PENALTY: u64 = 100;
+
+/// Converts the weight of a synonym, between 0 and 1, into the
+/// penalty of matching a query word by this synonym.
+pub fn synonym_penalty(weight: f64) -> u64 {
+    let weight = if weight.is_nan() { 1.0 } else { weight.max(0.0).min(1.0) };
+    ((1.0 - weight) * MAX_SYNONYM_PENALTY as f64).round() as u64
+}
+
+/// Converts the penalty of a synonym back into its weight.
+pub fn synonym_weight(penalty: u64) -> f64 {
+    let penalty = penalty.min(MAX_SYNONYM_PENALTY);
+    1.0 - penalty as f64 / MAX_SYNONYM_PENALTY as f64
+}
+
 #[derive(Copy, Clone)]
 pub struct Synonyms {
     pub(crate) synonyms: heed::Database<ByteSlice, ByteSlice>,
@@ -19,6 +38,16 @@ impl Synonyms {
         self.synonyms.put(writer, word, bytes)
     }
 
+    pub fn put_weighted_synonyms(
+        self,
+        writer: &mut heed::RwTxn<MainT>,
+        word: &[u8],
+        synonyms: &fst::Map,
+    ) -> ZResult<()> {
+        let bytes = synonyms.as_fst().as_bytes();
+        self.synonyms.put(writer, word, bytes)
+    }
+
     pub fn del_synonyms(self, writer: &mut heed::RwTxn<MainT>, word: &[u8]) -> ZResult<bool> {
         self.synonyms.delete(writer, word)
     }
@@ -27,15 +56,53 @@ impl Synonyms {
         self.synonyms.clear(writer)
     }
 
-    pub fn synonyms(self, reader: &heed::RoTxn<MainT>, word: &[u8]) -> ZResult<Option<fst::Set>> {
+    fn synonyms_raw_fst(
+        self,
+        reader: &heed::RoTxn<MainT>,
+        word: &[u8],
+    ) -> ZResult<Option<fst::raw::Fst>> {
         match self.synonyms.get(reader, word)? {
             Some(bytes) => {
                 let len = bytes.len();
                 let bytes = Arc::new(bytes.to_owned());
                 let fst = fst::raw::Fst::from_shared_bytes(bytes, 0, len).unwrap();
-                Ok(Some(fst::Set::from(fst)))
+                Ok(Some(fst))
             }
             None => Ok(None),
         }
     }
+
+    pub fn synonyms(self, reader: &heed::RoTxn<MainT>, word: &[u8]) -> ZResult<Option<fst::Set>> {
+        Ok(self.synonyms_raw_fst(reader, word)?.map(fst::Set::from))
+    }
+
+    /// The alternatives of a word associated with their penalties.
+    pub fn synonyms_penalties(
+        self,
+        reader: &heed::RoTxn<MainT>,
+        word: &[u8],
+    ) -> ZResult<Option<fst::Map>> {
+        Ok(self.synonyms_raw_fst(reader, word)?.map(fst::Map::from))
+    }
+
+    /// The alternatives of a word associated with their weights.
+    pub fn weighted_synonyms(
+        self,
+        reader: &heed::RoTxn<MainT>,
+        word: &[u8],
+    ) -> ZResult<Option<Vec<(String, f64)>>> {
+        let synonyms = match self.synonyms_penalties(reader, word)? {
+            Some(synonyms) => synonyms,
+            None => return Ok(None),
+        };
+
+        let mut weighted = Vec::with_capacity(synonyms.len());
+        let mut stream = synonyms.stream();
+        while let Some((alternative, penalty)) = stream.next() {
+            let alternative = String::from_utf8_lossy(alternative).into_owned();
+            weighted.push((alternative, synonym_weight(penalty)));
+        }
+
+        Ok(Some(weighted))
+    }
 }
@@ -0,0 +1,110 @@
+use crate::database::{MainT, UpdateT};
+use crate::update::documents_addition::reindex_all_documents;
+use crate::update::{next_update_id, Update};
+use crate::{store, Analyzer, Error, Language, MResult};
+
+pub fn apply_analyzer_update(
+    writer: &mut heed::RwTxn<MainT>,
+    analyzer: &Analyzer,
+    main_store: store::Main,
+    documents_fields_store: store::DocumentsFields,
+    documents_fields_counts_store: store::DocumentsFieldsCounts,
+    postings_lists_store: store::PostingsLists,
+    infix_postings_lists_store: store::PostingsLists,
+    phonetic_postings_lists_store: store::PostingsLists,
+    original_forms_postings_lists_store: store::PostingsLists,
+    docs_words_store: store::DocsWords,
+) -> MResult<()> {
+    let old_analyzer = main_store.analyzer(writer)?.unwrap_or_default();
+    main_store.put_analyzer(writer, analyzer)?;
+
+    // the words indexed depend on the analyzer
+    if old_analyzer != *analyzer {
+        reindex_all_documents(
+            writer,
+            main_store,
+            documents_fields_store,
+            documents_fields_counts_store,
+            postings_lists_store,
+            infix_postings_lists_store,
+            phonetic_postings_lists_store,
+            original_forms_postings_lists_store,
+            docs_words_store,
+        )?
+    }
+
+    Ok(())
+}
+
+pub fn apply_language_analyzer_update(
+    writer: &mut heed::RwTxn<MainT>,
+    language: Language,
+    analyzer: &Analyzer,
+    main_store: store::Main,
+    documents_fields_store: store::DocumentsFields,
+    documents_fields_counts_store: store::DocumentsFieldsCounts,
+    postings_lists_store: store::PostingsLists,
+    infix_postings_lists_store: store::PostingsLists,
+    phonetic_postings_lists_store: store::PostingsLists,
+    original_forms_postings_lists_store: store::PostingsLists,
+    docs_words_store: store::DocsWords,
+) -> MResult<()> {
+    let schema = match main_store.schema(writer)? {
+        Some(schema) => schema,
+        None => return Err(Error::SchemaMissing),
+    };
+
+    let mut analyzers = main_store.language_analyzers(writer)?.unwrap_or_default();
+    let old_analyzer = analyzers.insert(language, analyzer.clone());
+    main_store.put_language_analyzers(writer, &analyzers)?;
+
+    // only the attributes in this language are indexed with this analyzer
+    let has_language_attributes = schema
+        .iter()
+        .any(|(_, _, props)| props.language() == Some(language));
+
+    if has_language_attributes && old_analyzer.as_ref() != Some(analyzer) {
+        reindex_all_documents(
+            writer,
+            main_store,
+            documents_fields_store,
+            documents_fields_counts_store,
+            postings_lists_store,
+            infix_postings_lists_store,
+            phonetic_postings_lists_store,
+            original_forms_postings_lists_store,
+            docs_words_store,
+        )?
+    }
+
+    Ok(())
+}
+
+pub fn push_analyzer_update(
+    writer: &mut heed::RwTxn<UpdateT>,
+    updates_store: store::Updates,
+    updates_results_store: store::UpdatesResults,
+    analyzer: Analyzer,
+) -> MResult<u64> {
+    let last_update_id = next_update_id(writer, updates_store, updates_results_store)?;
+
+    let update = Update::analyzer(analyzer);
+    updates_store.put_update(writer, last_update_id, &update)?;
+
+    Ok(last_update_id)
+}
+
+pub fn push_language_analyzer_update(
+    writer: &mut heed::RwTxn<UpdateT>,
+    updates_store: store::Updates,
+    updates_results_store: store::UpdatesResults,
+    language: Language,
+    analyzer: Analyzer,
+) -> MResult<u64> {
+    let last_update_id = next_update_id(writer, updates_store, updates_results_store)?;
+
+    let update = Update::language_analyzer(language, analyzer);
+    updates_store.put_update(writer, last_update_id, &update)?;
+
+    Ok(last_update_id)
+}
@@ -8,14 +8,22 @@ pub fn apply_clear_all(
     documents_fields_store: store::DocumentsFields,
     documents_fields_counts_store: store::DocumentsFieldsCounts,
     postings_lists_store: store::PostingsLists,
+    infix_postings_lists_store: store::PostingsLists,
+    phonetic_postings_lists_store: store::PostingsLists,
+    original_forms_postings_lists_store: store::PostingsLists,
     docs_words_store: store::DocsWords,
 ) -> MResult<()> {
     main_store.put_words_fst(writer, &fst::Set::default())?;
+    main_store.put_infix_words_fst(writer, &fst::Set::default())?;
+    main_store.put_phonetic_words_fst(writer, &fst::Set::default())?;
     main_store.put_ranked_map(writer, &RankedMap::default())?;
     main_store.put_number_of_documents(writer, |_| 0)?;
     documents_fields_store.clear(writer)?;
     documents_fields_counts_store.clear(writer)?;
     postings_lists_store.clear(writer)?;
+    infix_postings_lists_store.clear(writer)?;
+    phonetic_postings_lists_store.clear(writer)?;
+    original_forms_postings_lists_store.clear(writer)?;
     docs_words_store.clear(writer)?;
 
     Ok(())
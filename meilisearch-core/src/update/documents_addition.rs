@@ -1,16 +1,18 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use fst::{set::OpBuilder, SetBuilder};
-use sdset::{duo::Union, SetOperation};
+use meilisearch_schema::Schema;
+use sdset::{duo::Union, SetBuf, SetOperation};
 use serde::{Deserialize, Serialize};
 
+use crate::analyzer::language_analyzer;
 use crate::database::{MainT, UpdateT};
 use crate::database::{UpdateEvent, UpdateEventsEmitter};
 use crate::raw_indexer::RawIndexer;
 use crate::serde::{extract_document_id, serialize_value, Deserializer, Serializer};
 use crate::store;
 use crate::update::{apply_documents_deletion, next_update_id, Update};
-use crate::{Error, MResult, RankedMap};
+use crate::{DocIndex, Error, MResult, RankedMap};
 
 pub struct DocumentsAddition<D> {
     updates_store: store::Updates,
@@ -108,6 +110,9 @@ pub fn apply_documents_addition<'a, 'b>(
     documents_fields_store: store::DocumentsFields,
     documents_fields_counts_store: store::DocumentsFieldsCounts,
     postings_lists_store: store::PostingsLists,
+    infix_postings_lists_store: store::PostingsLists,
+    phonetic_postings_lists_store: store::PostingsLists,
+    original_forms_postings_lists_store: store::PostingsLists,
     docs_words_store: store::DocsWords,
     addition: Vec<HashMap<String, serde_json::Value>>,
 ) -> MResult<()> {
@@ -132,15 +137,18 @@ pub fn apply_documents_addition<'a, 'b>(
 
     // 2. remove the documents posting lists
     let number_of_inserted_documents = documents_additions.len();
-    let documents_ids = documents_additions.iter().map(|(id, _)| *id).collect();
+    let documents_ids: Vec<_> = documents_additions.iter().map(|(id, _)| *id).collect();
     apply_documents_deletion(
         writer,
         main_store,
         documents_fields_store,
         documents_fields_counts_store,
         postings_lists_store,
+        infix_postings_lists_store,
+        phonetic_postings_lists_store,
+        original_forms_postings_lists_store,
         docs_words_store,
-        documents_ids,
+        documents_ids.clone(),
     )?;
 
     let mut ranked_map = match main_store.ranked_map(writer)? {
@@ -148,13 +156,8 @@ pub fn apply_documents_addition<'a, 'b>(
         None => RankedMap::default(),
     };
 
-    let stop_words = match main_store.stop_words_fst(writer)? {
-        Some(stop_words) => stop_words,
-        None => fst::Set::default(),
-    };
-
     // 3. index the documents fields in the stores
-    let mut indexer = RawIndexer::new(stop_words);
+    let mut indexer = raw_indexer(writer, main_store, &schema)?;
 
     for (document_id, document) in documents_additions {
         let serializer = Serializer {
@@ -170,10 +173,16 @@ pub fn apply_documents_addition<'a, 'b>(
         document.serialize(serializer)?;
     }
 
+    // 4. associate the documents with the values they can be filtered on
+    documents_fields_store.put_filter_values(writer, &documents_ids)?;
+
     write_documents_addition_index(
         writer,
         main_store,
         postings_lists_store,
+        infix_postings_lists_store,
+        phonetic_postings_lists_store,
+        original_forms_postings_lists_store,
         docs_words_store,
         &ranked_map,
         number_of_inserted_documents,
@@ -187,6 +196,9 @@ pub fn apply_documents_partial_addition<'a, 'b>(
     documents_fields_store: store::DocumentsFields,
     documents_fields_counts_store: store::DocumentsFieldsCounts,
     postings_lists_store: store::PostingsLists,
+    infix_postings_lists_store: store::PostingsLists,
+    phonetic_postings_lists_store: store::PostingsLists,
+    original_forms_postings_lists_store: store::PostingsLists,
     docs_words_store: store::DocsWords,
     addition: Vec<HashMap<String, serde_json::Value>>,
 ) -> MResult<()> {
@@ -228,15 +240,18 @@ pub fn apply_documents_partial_addition<'a, 'b>(
 
     // 2. remove the documents posting lists
     let number_of_inserted_documents = documents_additions.len();
-    let documents_ids = documents_additions.iter().map(|(id, _)| *id).collect();
+    let documents_ids: Vec<_> = documents_additions.iter().map(|(id, _)| *id).collect();
     apply_documents_deletion(
         writer,
         main_store,
         documents_fields_store,
         documents_fields_counts_store,
         postings_lists_store,
+        infix_postings_lists_store,
+        phonetic_postings_lists_store,
+        original_forms_postings_lists_store,
         docs_words_store,
-        documents_ids,
+        documents_ids.clone(),
     )?;
 
     let mut ranked_map = match main_store.ranked_map(writer)? {
@@ -244,13 +259,8 @@ pub fn apply_documents_partial_addition<'a, 'b>(
         None => RankedMap::default(),
     };
 
-    let stop_words = match main_store.stop_words_fst(writer)? {
-        Some(stop_words) => stop_words,
-        None => fst::Set::default(),
-    };
-
     // 3. index the documents fields in the stores
-    let mut indexer = RawIndexer::new(stop_words);
+    let mut indexer = raw_indexer(writer, main_store, &schema)?;
 
     for (document_id, document) in documents_additions {
         let serializer = Serializer {
@@ -266,10 +276,16 @@ pub fn apply_documents_partial_addition<'a, 'b>(
         document.serialize(serializer)?;
     }
 
+    // 4. associate the documents with the values they can be filtered on
+    documents_fields_store.put_filter_values(writer, &documents_ids)?;
+
     write_documents_addition_index(
         writer,
         main_store,
         postings_lists_store,
+        infix_postings_lists_store,
+        phonetic_postings_lists_store,
+        original_forms_postings_lists_store,
         docs_words_store,
         &ranked_map,
         number_of_inserted_documents,
@@ -283,6 +299,9 @@ pub fn reindex_all_documents(
     documents_fields_store: store::DocumentsFields,
     documents_fields_counts_store: store::DocumentsFieldsCounts,
     postings_lists_store: store::PostingsLists,
+    infix_postings_lists_store: store::PostingsLists,
+    phonetic_postings_lists_store: store::PostingsLists,
+    original_forms_postings_lists_store: store::PostingsLists,
     docs_words_store: store::DocsWords,
 ) -> MResult<()> {
     let schema = match main_store.schema(writer)? {
@@ -301,20 +320,20 @@ pub fn reindex_all_documents(
 
     // 2. remove the documents posting lists
     main_store.put_words_fst(writer, &fst::Set::default())?;
+    main_store.put_infix_words_fst(writer, &fst::Set::default())?;
+    main_store.put_phonetic_words_fst(writer, &fst::Set::default())?;
     main_store.put_ranked_map(writer, &ranked_map)?;
     main_store.put_number_of_documents(writer, |_| 0)?;
     postings_lists_store.clear(writer)?;
+    infix_postings_lists_store.clear(writer)?;
+    phonetic_postings_lists_store.clear(writer)?;
+    original_forms_postings_lists_store.clear(writer)?;
     docs_words_store.clear(writer)?;
 
     // 3. re-index chunks of documents (otherwise we make the borrow checker unhappy)
     for documents_ids in documents_ids_to_reindex.chunks(100) {
-        let stop_words = match main_store.stop_words_fst(writer)? {
-            Some(stop_words) => stop_words,
-            None => fst::Set::default(),
-        };
-
         let number_of_inserted_documents = documents_ids.len();
-        let mut indexer = RawIndexer::new(stop_words);
+        let mut indexer = raw_indexer(writer, main_store, &schema)?;
         let mut ram_store = HashMap::new();
 
         for document_id in documents_ids {
@@ -344,6 +363,9 @@ pub fn reindex_all_documents(
             writer,
             main_store,
             postings_lists_store,
+            infix_postings_lists_store,
+            phonetic_postings_lists_store,
+            original_forms_postings_lists_store,
             docs_words_store,
             &ranked_map,
             number_of_inserted_documents,
@@ -358,35 +380,88 @@ pub fn write_documents_addition_index(
     writer: &mut heed::RwTxn<MainT>,
     main_store: store::Main,
     postings_lists_store: store::PostingsLists,
+    infix_postings_lists_store: store::PostingsLists,
+    phonetic_postings_lists_store: store::PostingsLists,
+    original_forms_postings_lists_store: store::PostingsLists,
     docs_words_store: store::DocsWords,
     ranked_map: &RankedMap,
     number_of_inserted_documents: usize,
     indexer: RawIndexer,
 ) -> MResult<()> {
     let indexed = indexer.build();
+
+    let words = main_store.words_fst(writer)?;
+    let words = merge_postings_lists(writer, postings_lists_store, words, indexed.words_doc_indexes)?;
+
+    // the original forms of the words are only retrieved
+    // by their keys, they are not part of the words FST
+    merge_postings_lists(
+        writer,
+        original_forms_postings_lists_store,
+        None,
+        indexed.original_forms_doc_indexes,
+    )?;
+
+    if !indexed.infix_words_doc_indexes.is_empty() {
+        let infix_words = main_store.infix_words_fst(writer)?;
+        let infix_words = merge_postings_lists(
+            writer,
+            infix_postings_lists_store,
+            infix_words,
+            indexed.infix_words_doc_indexes,
+        )?;
+        main_store.put_infix_words_fst(writer, &infix_words)?;
+    }
+
+    if !indexed.phonetic_words_doc_indexes.is_empty() {
+        let phonetic_words = main_store.phonetic_words_fst(writer)?;
+        let phonetic_words = merge_postings_lists(
+            writer,
+            phonetic_postings_lists_store,
+            phonetic_words,
+            indexed.phonetic_words_doc_indexes,
+        )?;
+        main_store.put_phonetic_words_fst(writer, &phonetic_words)?;
+    }
+
+    for (id, words) in indexed.docs_words {
+        docs_words_store.put_doc_words(writer, id, &words)?;
+    }
+
+    main_store.put_words_fst(writer, &words)?;
+    main_store.put_ranked_map(writer, ranked_map)?;
+    main_store.put_number_of_documents(writer, |old| old + number_of_inserted_documents as u64)?;
+
+    Ok(())
+}
+
+// merges the new postings lists with the stored ones
+// and returns the union of the old words with the new ones
+fn merge_postings_lists(
+    writer: &mut heed::RwTxn<MainT>,
+    postings_lists_store: store::PostingsLists,
+    words: Option<fst::Set>,
+    words_doc_indexes: BTreeMap<Vec<u8>, SetBuf<DocIndex>>,
+) -> MResult<fst::Set> {
     let mut delta_words_builder = SetBuilder::memory();
 
-    for (word, delta_set) in indexed.words_doc_indexes {
+    for (word, delta_set) in words_doc_indexes {
         delta_words_builder.insert(&word).unwrap();
 
         let set = match postings_lists_store.postings_list(writer, &word)? {
-            Some(set) => Union::new(&set, &delta_set).into_set_buf(),
+            Some(postings) => Union::new(&postings.matches, &delta_set).into_set_buf(),
             None => delta_set,
         };
 
         postings_lists_store.put_postings_list(writer, &word, &set)?;
     }
 
-    for (id, words) in indexed.docs_words {
-        docs_words_store.put_doc_words(writer, id, &words)?;
-    }
-
     let delta_words = delta_words_builder
         .into_inner()
         .and_then(fst::Set::from_bytes)
         .unwrap();
 
-    let words = match main_store.words_fst(writer)? {
+    let words = match words {
         Some(words) => {
             let op = OpBuilder::new()
                 .add(words.stream())
@@ -403,9 +478,41 @@ pub fn write_documents_addition_index(
         None => delta_words,
     };
 
-    main_store.put_words_fst(writer, &words)?;
-    main_store.put_ranked_map(writer, ranked_map)?;
-    main_store.put_number_of_documents(writer, |old| old + number_of_inserted_documents as u64)?;
+    Ok(words)
+}
 
-    Ok(())
+// creates an indexer with the stop words and the analyzers of the index,
+// the attributes of the schema in a language use the analyzer of this language
+fn raw_indexer(
+    reader: &heed::RoTxn<MainT>,
+    main_store: store::Main,
+    schema: &Schema,
+) -> MResult<RawIndexer> {
+    let stop_words = match main_store.stop_words_fst(reader)? {
+        Some(stop_words) => stop_words,
+        None => fst::Set::default(),
+    };
+
+    let analyzer = main_store.analyzer(reader)?.unwrap_or_default();
+    let mut indexer = RawIndexer::with_analyzer(stop_words, analyzer);
+
+    let mut infix_attributes = Vec::new();
+    let mut phonetic_attributes = Vec::new();
+    for (_, attr, props) in schema.iter() {
+        if props.is_infix() {
+            infix_attributes.push(attr);
+        }
+        if props.is_phonetic() {
+            phonetic_attributes.push(attr);
+        }
+        if let Some(language) = props.language() {
+            let analyzer = language_analyzer(reader, main_store, Some(language))?;
+            indexer.set_attribute_analyzer(attr, analyzer);
+        }
+    }
+
+    indexer.set_infix_attributes(infix_attributes);
+    indexer.set_phonetic_attributes(phonetic_attributes);
+
+    Ok(indexer)
 }
@@ -1,4 +1,5 @@
 use std::collections::{BTreeSet, HashMap, HashSet};
+use std::str;
 
 use fst::{SetBuilder, Streamer};
 use meilisearch_schema::Schema;
@@ -6,6 +7,8 @@ use sdset::{duo::DifferenceByKey, SetBuf, SetOperation};
 
 use crate::database::{MainT, UpdateT};
 use crate::database::{UpdateEvent, UpdateEventsEmitter};
+use crate::phonetic::phonetic_key;
+use crate::raw_indexer::{original_forms_keys_range, word_suffixes};
 use crate::serde::extract_document_id;
 use crate::store;
 use crate::update::{next_update_id, Update};
@@ -89,11 +92,17 @@ pub fn apply_documents_deletion(
     documents_fields_store: store::DocumentsFields,
     documents_fields_counts_store: store::DocumentsFieldsCounts,
     postings_lists_store: store::PostingsLists,
+    infix_postings_lists_store: store::PostingsLists,
+    phonetic_postings_lists_store: store::PostingsLists,
+    original_forms_postings_lists_store: store::PostingsLists,
     docs_words_store: store::DocsWords,
     deletion: Vec<DocumentId>,
 ) -> MResult<()> {
     let idset = SetBuf::from_dirty(deletion);
 
+    // the values of the documents fields must be read before the fields are deleted
+    documents_fields_store.del_filter_values(writer, idset.as_slice())?;
+
     let schema = match main_store.schema(writer)? {
         Some(schema) => schema,
         None => return Err(Error::SchemaMissing),
@@ -118,7 +127,14 @@ pub fn apply_documents_deletion(
         )
         .collect();
 
+    // the suffixes and the phonetic keys of the words are
+    // only indexed for the infix and phonetic attributes
+    let has_infix_attributes = schema.iter().any(|(_, _, prop)| prop.is_infix());
+    let has_phonetic_attributes = schema.iter().any(|(_, _, prop)| prop.is_phonetic());
+
     let mut words_document_ids = HashMap::new();
+    let mut infix_words_document_ids = HashMap::new();
+    let mut phonetic_words_document_ids = HashMap::new();
     for id in idset {
         // remove all the ranked attributes from the ranked_map
         for ranked_attr in &ranked_attrs {
@@ -128,6 +144,26 @@ pub fn apply_documents_deletion(
         if let Some(words) = docs_words_store.doc_words(writer, id)? {
             let mut stream = words.stream();
             while let Some(word) = stream.next() {
+                if let Ok(word) = str::from_utf8(word) {
+                    if has_infix_attributes {
+                        for suffix in word_suffixes(word) {
+                            infix_words_document_ids
+                                .entry(suffix.as_bytes().to_vec())
+                                .or_insert_with(Vec::new)
+                                .push(id);
+                        }
+                    }
+
+                    if has_phonetic_attributes {
+                        if let Some(key) = phonetic_key(word) {
+                            phonetic_words_document_ids
+                                .entry(key.into_bytes())
+                                .or_insert_with(Vec::new)
+                                .push(id);
+                        }
+                    }
+                }
+
                 let word = word.to_vec();
                 words_document_ids
                     .entry(word)
@@ -137,13 +173,25 @@ pub fn apply_documents_deletion(
         }
     }
 
+    // the original forms of the words are stored under keys starting with the words
+    let mut original_forms_document_ids = HashMap::new();
+    for (word, document_ids) in &words_document_ids {
+        let (start, end) = original_forms_keys_range(word);
+        for key in original_forms_postings_lists_store.words_range(writer, &start, &end)? {
+            original_forms_document_ids
+                .entry(key)
+                .or_insert_with(Vec::new)
+                .extend_from_slice(document_ids);
+        }
+    }
+
     let mut deleted_documents = HashSet::new();
     let mut removed_words = BTreeSet::new();
     for (word, document_ids) in words_document_ids {
         let document_ids = SetBuf::from_dirty(document_ids);
 
-        if let Some(doc_indexes) = postings_lists_store.postings_list(writer, &word)? {
-            let op = DifferenceByKey::new(&doc_indexes, &document_ids, |d| d.document_id, |id| *id);
+        if let Some(postings) = postings_lists_store.postings_list(writer, &word)? {
+            let op = DifferenceByKey::new(&postings.matches, &document_ids, |d| d.document_id, |id| *id);
             let doc_indexes = op.into_set_buf();
 
             if !doc_indexes.is_empty() {
@@ -162,13 +210,73 @@ pub fn apply_documents_deletion(
         }
     }
 
+    let removed_infix_words =
+        remove_postings_lists(writer, infix_postings_lists_store, infix_words_document_ids)?;
+    let removed_phonetic_words =
+        remove_postings_lists(writer, phonetic_postings_lists_store, phonetic_words_document_ids)?;
+    remove_postings_lists(
+        writer,
+        original_forms_postings_lists_store,
+        original_forms_document_ids,
+    )?;
+
     let deleted_documents_len = deleted_documents.len() as u64;
     for id in deleted_documents {
         docs_words_store.del_doc_words(writer, id)?;
     }
 
+    let words = main_store.words_fst(writer)?;
+    let words = remove_words(words, removed_words);
+    main_store.put_words_fst(writer, &words)?;
+
+    if !removed_infix_words.is_empty() {
+        let infix_words = main_store.infix_words_fst(writer)?;
+        let infix_words = remove_words(infix_words, removed_infix_words);
+        main_store.put_infix_words_fst(writer, &infix_words)?;
+    }
+
+    if !removed_phonetic_words.is_empty() {
+        let phonetic_words = main_store.phonetic_words_fst(writer)?;
+        let phonetic_words = remove_words(phonetic_words, removed_phonetic_words);
+        main_store.put_phonetic_words_fst(writer, &phonetic_words)?;
+    }
+
+    main_store.put_ranked_map(writer, &ranked_map)?;
+    main_store.put_number_of_documents(writer, |old| old - deleted_documents_len)?;
+
+    Ok(())
+}
+
+// removes the documents from the postings lists of the given words
+// and returns the words that are no more associated to any document
+fn remove_postings_lists(
+    writer: &mut heed::RwTxn<MainT>,
+    postings_lists_store: store::PostingsLists,
+    words_document_ids: HashMap<Vec<u8>, Vec<DocumentId>>,
+) -> MResult<BTreeSet<Vec<u8>>> {
+    let mut removed_words = BTreeSet::new();
+    for (word, document_ids) in words_document_ids {
+        let document_ids = SetBuf::from_dirty(document_ids);
+
+        if let Some(postings) = postings_lists_store.postings_list(writer, &word)? {
+            let op = DifferenceByKey::new(&postings.matches, &document_ids, |d| d.document_id, |id| *id);
+            let doc_indexes = op.into_set_buf();
+
+            if !doc_indexes.is_empty() {
+                postings_lists_store.put_postings_list(writer, &word, &doc_indexes)?;
+            } else {
+                postings_lists_store.del_postings_list(writer, &word)?;
+                removed_words.insert(word);
+            }
+        }
+    }
+
+    Ok(removed_words)
+}
+
+fn remove_words(words: Option<fst::Set>, removed_words: BTreeSet<Vec<u8>>) -> fst::Set {
     let removed_words = fst::Set::from_iter(removed_words).unwrap();
-    let words = match main_store.words_fst(writer)? {
+    match words {
         Some(words_set) => {
             let op = fst::set::OpBuilder::new()
                 .add(words_set.stream())
@@ -183,11 +291,5 @@ pub fn apply_documents_deletion(
                 .unwrap()
         }
         None => fst::Set::default(),
-    };
-
-    main_store.put_words_fst(writer, &words)?;
-    main_store.put_ranked_map(writer, &ranked_map)?;
-    main_store.put_number_of_documents(writer, |old| old - deleted_documents_len)?;
-
-    Ok(())
+    }
 }
@@ -1,19 +1,26 @@
+mod analyzer_update;
 mod clear_all;
 mod customs_update;
 mod documents_addition;
 mod documents_deletion;
+mod query_rules_update;
 mod schema_update;
 mod stop_words_addition;
 mod stop_words_deletion;
 mod synonyms_addition;
 mod synonyms_deletion;
 
+pub use self::analyzer_update::{
+    apply_analyzer_update, apply_language_analyzer_update, push_analyzer_update,
+    push_language_analyzer_update,
+};
 pub use self::clear_all::{apply_clear_all, push_clear_all};
 pub use self::customs_update::{apply_customs_update, push_customs_update};
 pub use self::documents_addition::{
     apply_documents_addition, apply_documents_partial_addition, DocumentsAddition,
 };
 pub use self::documents_deletion::{apply_documents_deletion, DocumentsDeletion};
+pub use self::query_rules_update::{apply_query_rules_update, push_query_rules_update};
 pub use self::schema_update::{apply_schema_update, push_schema_update};
 pub use self::stop_words_addition::{apply_stop_words_addition, StopWordsAddition};
 pub use self::stop_words_deletion::{apply_stop_words_deletion, StopWordsDeletion};
@@ -27,9 +34,9 @@ use std::time::Instant;
 use chrono::{DateTime, Utc};
 use heed::Result as ZResult;
 use log::debug;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
-use crate::{store, DocumentId, MResult};
+use crate::{store, Analyzer, DocumentId, Language, MResult, QueryRule};
 use crate::database::{MainT, UpdateT};
 use meilisearch_schema::Schema;
 
@@ -54,6 +61,20 @@ impl Update {
         }
     }
 
+    fn analyzer(data: Analyzer) -> Update {
+        Update {
+            data: UpdateData::Analyzer(data),
+            enqueued_at: Utc::now(),
+        }
+    }
+
+    fn language_analyzer(language: Language, data: Analyzer) -> Update {
+        Update {
+            data: UpdateData::LanguageAnalyzer(language, data),
+            enqueued_at: Utc::now(),
+        }
+    }
+
     fn customs(data: Vec<u8>) -> Update {
         Update {
             data: UpdateData::Customs(data),
@@ -61,6 +82,13 @@ impl Update {
         }
     }
 
+    fn query_rules(data: Vec<QueryRule>) -> Update {
+        Update {
+            data: UpdateData::QueryRules(data),
+            enqueued_at: Utc::now(),
+        }
+    }
+
     fn documents_addition(data: Vec<HashMap<String, serde_json::Value>>) -> Update {
         Update {
             data: UpdateData::DocumentsAddition(data),
@@ -82,7 +110,7 @@ impl Update {
         }
     }
 
-    fn synonyms_addition(data: BTreeMap<String, Vec<String>>) -> Update {
+    fn synonyms_addition(data: BTreeMap<String, BTreeMap<String, u64>>) -> Update {
         Update {
             data: UpdateData::SynonymsAddition(data),
             enqueued_at: Utc::now(),
@@ -115,22 +143,66 @@ impl Update {
 pub enum UpdateData {
     ClearAll,
     Schema(Schema),
+    Analyzer(Analyzer),
+    LanguageAnalyzer(Language, Analyzer),
     Customs(Vec<u8>),
+    QueryRules(Vec<QueryRule>),
     DocumentsAddition(Vec<HashMap<String, serde_json::Value>>),
     DocumentsPartial(Vec<HashMap<String, serde_json::Value>>),
     DocumentsDeletion(Vec<DocumentId>),
-    SynonymsAddition(BTreeMap<String, Vec<String>>),
+    SynonymsAddition(
+        #[serde(deserialize_with = "deserialize_synonyms")] BTreeMap<String, BTreeMap<String, u64>>,
+    ),
     SynonymsDeletion(BTreeMap<String, Option<Vec<String>>>),
     StopWordsAddition(BTreeSet<String>),
     StopWordsDeletion(BTreeSet<String>),
 }
 
+// The synonyms additions enqueued by the previous versions
+// listed the alternatives of the words without any penalty.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Alternatives {
+    Penalized(BTreeMap<String, u64>),
+    Equivalent(Vec<String>),
+}
+
+fn deserialize_synonyms<'de, D>(
+    deserializer: D,
+) -> Result<BTreeMap<String, BTreeMap<String, u64>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let synonyms = BTreeMap::<String, Alternatives>::deserialize(deserializer)?;
+    let synonyms = synonyms
+        .into_iter()
+        .map(|(word, alternatives)| {
+            let alternatives = match alternatives {
+                Alternatives::Penalized(alternatives) => alternatives,
+                Alternatives::Equivalent(alternatives) => {
+                    alternatives.into_iter().map(|alt| (alt, 0)).collect()
+                }
+            };
+            (word, alternatives)
+        })
+        .collect();
+
+    Ok(synonyms)
+}
+
 impl UpdateData {
     pub fn update_type(&self) -> UpdateType {
         match self {
             UpdateData::ClearAll => UpdateType::ClearAll,
             UpdateData::Schema(_) => UpdateType::Schema,
+            UpdateData::Analyzer(_) => UpdateType::Analyzer,
+            UpdateData::LanguageAnalyzer(language, _) => UpdateType::LanguageAnalyzer {
+                language: *language,
+            },
             UpdateData::Customs(_) => UpdateType::Customs,
+            UpdateData::QueryRules(rules) => UpdateType::QueryRules {
+                number: rules.len(),
+            },
             UpdateData::DocumentsAddition(addition) => UpdateType::DocumentsAddition {
                 number: addition.len(),
             },
@@ -161,7 +233,10 @@ impl UpdateData {
 pub enum UpdateType {
     ClearAll,
     Schema,
+    Analyzer,
+    LanguageAnalyzer { language: Language },
     Customs,
+    QueryRules { number: usize },
     DocumentsAddition { number: usize },
     DocumentsPartial { number: usize },
     DocumentsDeletion { number: usize },
@@ -275,6 +350,9 @@ pub fn update_task<'a, 'b>(
                 index.documents_fields,
                 index.documents_fields_counts,
                 index.postings_lists,
+                index.infix_postings_lists,
+                index.phonetic_postings_lists,
+                index.original_forms_postings_lists,
                 index.docs_words,
             );
 
@@ -291,6 +369,48 @@ pub fn update_task<'a, 'b>(
                 index.documents_fields,
                 index.documents_fields_counts,
                 index.postings_lists,
+                index.infix_postings_lists,
+                index.phonetic_postings_lists,
+                index.original_forms_postings_lists,
+                index.docs_words,
+            );
+
+            (update_type, result, start.elapsed())
+        }
+        UpdateData::Analyzer(analyzer) => {
+            let start = Instant::now();
+
+            let update_type = UpdateType::Analyzer;
+            let result = apply_analyzer_update(
+                writer,
+                &analyzer,
+                index.main,
+                index.documents_fields,
+                index.documents_fields_counts,
+                index.postings_lists,
+                index.infix_postings_lists,
+                index.phonetic_postings_lists,
+                index.original_forms_postings_lists,
+                index.docs_words,
+            );
+
+            (update_type, result, start.elapsed())
+        }
+        UpdateData::LanguageAnalyzer(language, analyzer) => {
+            let start = Instant::now();
+
+            let update_type = UpdateType::LanguageAnalyzer { language };
+            let result = apply_language_analyzer_update(
+                writer,
+                language,
+                &analyzer,
+                index.main,
+                index.documents_fields,
+                index.documents_fields_counts,
+                index.postings_lists,
+                index.infix_postings_lists,
+                index.phonetic_postings_lists,
+                index.original_forms_postings_lists,
                 index.docs_words,
             );
 
@@ -304,6 +424,16 @@ pub fn update_task<'a, 'b>(
 
             (update_type, result, start.elapsed())
         }
+        UpdateData::QueryRules(rules) => {
+            let start = Instant::now();
+
+            let update_type = UpdateType::QueryRules {
+                number: rules.len(),
+            };
+            let result = apply_query_rules_update(writer, index.main, rules).map_err(Into::into);
+
+            (update_type, result, start.elapsed())
+        }
         UpdateData::DocumentsAddition(documents) => {
             let start = Instant::now();
 
@@ -317,6 +447,9 @@ pub fn update_task<'a, 'b>(
                 index.documents_fields,
                 index.documents_fields_counts,
                 index.postings_lists,
+                index.infix_postings_lists,
+                index.phonetic_postings_lists,
+                index.original_forms_postings_lists,
                 index.docs_words,
                 documents,
             );
@@ -336,6 +469,9 @@ pub fn update_task<'a, 'b>(
                 index.documents_fields,
                 index.documents_fields_counts,
                 index.postings_lists,
+                index.infix_postings_lists,
+                index.phonetic_postings_lists,
+                index.original_forms_postings_lists,
                 index.docs_words,
                 documents,
             );
@@ -355,6 +491,9 @@ pub fn update_task<'a, 'b>(
                 index.documents_fields,
                 index.documents_fields_counts,
                 index.postings_lists,
+                index.infix_postings_lists,
+                index.phonetic_postings_lists,
+                index.original_forms_postings_lists,
                 index.docs_words,
                 documents,
             );
@@ -408,6 +547,9 @@ pub fn update_task<'a, 'b>(
                 index.documents_fields,
                 index.documents_fields_counts,
                 index.postings_lists,
+                index.infix_postings_lists,
+                index.phonetic_postings_lists,
+                index.original_forms_postings_lists,
                 index.docs_words,
                 stop_words,
             );
@@ -432,3 +574,30 @@ pub fn update_task<'a, 'b>(
 
     Ok(status)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn previous_synonyms_addition() {
+        // the alternatives were listed without any penalty
+        let data = r#"{ "SynonymsAddition": { "hello": ["bonjour", "salut"] } }"#;
+        let data: UpdateData = serde_json::from_str(data).unwrap();
+
+        let mut alternatives = BTreeMap::new();
+        alternatives.insert("bonjour".to_owned(), 0);
+        alternatives.insert("salut".to_owned(), 0);
+        assert_matches!(data, UpdateData::SynonymsAddition(synonyms) => {
+            assert_eq!(synonyms.get("hello"), Some(&alternatives));
+        });
+
+        let data = r#"{ "SynonymsAddition": { "hello": { "bonjour": 0, "salut": 20 } } }"#;
+        let data: UpdateData = serde_json::from_str(data).unwrap();
+
+        alternatives.insert("salut".to_owned(), 20);
+        assert_matches!(data, UpdateData::SynonymsAddition(synonyms) => {
+            assert_eq!(synonyms.get("hello"), Some(&alternatives));
+        });
+    }
+}
@@ -0,0 +1,28 @@
+use heed::Result as ZResult;
+
+use crate::database::{MainT, UpdateT};
+use crate::store;
+use crate::update::{next_update_id, Update};
+use crate::QueryRule;
+
+pub fn apply_query_rules_update(
+    writer: &mut heed::RwTxn<MainT>,
+    main_store: store::Main,
+    rules: Vec<QueryRule>,
+) -> ZResult<()> {
+    main_store.put_query_rules(writer, &rules)
+}
+
+pub fn push_query_rules_update(
+    writer: &mut heed::RwTxn<UpdateT>,
+    updates_store: store::Updates,
+    updates_results_store: store::UpdatesResults,
+    rules: Vec<QueryRule>,
+) -> ZResult<u64> {
+    let last_update_id = next_update_id(writer, updates_store, updates_results_store)?;
+
+    let update = Update::query_rules(rules);
+    updates_store.put_update(writer, last_update_id, &update)?;
+
+    Ok(last_update_id)
+}
@@ -12,6 +12,9 @@ pub fn apply_schema_update(
     documents_fields_store: store::DocumentsFields,
     documents_fields_counts_store: store::DocumentsFieldsCounts,
     postings_lists_store: store::PostingsLists,
+    infix_postings_lists_store: store::PostingsLists,
+    phonetic_postings_lists_store: store::PostingsLists,
+    original_forms_postings_lists_store: store::PostingsLists,
     docs_words_store: store::DocsWords,
 ) -> MResult<()> {
     use UnsupportedOperation::{
@@ -33,6 +36,15 @@ pub fn apply_schema_update(
                     if new.ranked != old.ranked {
                         need_full_reindexing = true;
                     }
+                    if new.infix != old.infix {
+                        need_full_reindexing = true;
+                    }
+                    if new.phonetic != old.phonetic {
+                        need_full_reindexing = true;
+                    }
+                    if new.language != old.language {
+                        need_full_reindexing = true;
+                    }
                 }
                 Diff::NewAttr { pos, .. } => {
                     // new attribute not at the end of the schema
@@ -54,6 +66,9 @@ pub fn apply_schema_update(
             documents_fields_store,
             documents_fields_counts_store,
             postings_lists_store,
+            infix_postings_lists_store,
+            phonetic_postings_lists_store,
+            original_forms_postings_lists_store,
             docs_words_store,
         )?
     }
@@ -67,6 +67,9 @@ pub fn apply_stop_words_deletion(
     documents_fields_store: store::DocumentsFields,
     documents_fields_counts_store: store::DocumentsFieldsCounts,
     postings_lists_store: store::PostingsLists,
+    infix_postings_lists_store: store::PostingsLists,
+    phonetic_postings_lists_store: store::PostingsLists,
+    original_forms_postings_lists_store: store::PostingsLists,
     docs_words_store: store::DocsWords,
     deletion: BTreeSet<String>,
 ) -> MResult<()> {
@@ -107,6 +110,9 @@ pub fn apply_stop_words_deletion(
         documents_fields_store,
         documents_fields_counts_store,
         postings_lists_store,
+        infix_postings_lists_store,
+        phonetic_postings_lists_store,
+        original_forms_postings_lists_store,
         docs_words_store,
     )?;
 
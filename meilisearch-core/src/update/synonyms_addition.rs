@@ -1,7 +1,6 @@
 use std::collections::BTreeMap;
 
-use fst::{set::OpBuilder, SetBuilder};
-use sdset::SetBuf;
+use fst::{set::OpBuilder, MapBuilder, SetBuilder};
 
 use crate::database::{MainT, UpdateT};
 use crate::automaton::normalize_str;
@@ -13,7 +12,7 @@ pub struct SynonymsAddition {
     updates_store: store::Updates,
     updates_results_store: store::UpdatesResults,
     updates_notifier: UpdateEventsEmitter,
-    synonyms: BTreeMap<String, Vec<String>>,
+    synonyms: BTreeMap<String, BTreeMap<String, u64>>,
 }
 
 impl SynonymsAddition {
@@ -35,12 +34,26 @@ impl SynonymsAddition {
         S: AsRef<str>,
         T: AsRef<str>,
         I: IntoIterator<Item = T>,
+    {
+        let alternatives = alternatives.into_iter().map(|s| (s, 1.0));
+        self.add_weighted_synonym(synonym, alternatives)
+    }
+
+    /// Adds alternatives with a weight between 0 and 1, the documents matching
+    /// by the alternatives of lower weights are ranked after the others.
+    pub fn add_weighted_synonym<S, T, I>(&mut self, synonym: S, alternatives: I)
+    where
+        S: AsRef<str>,
+        T: AsRef<str>,
+        I: IntoIterator<Item = (T, f64)>,
     {
         let synonym = normalize_str(synonym.as_ref());
-        let alternatives = alternatives.into_iter().map(|s| s.as_ref().to_lowercase());
+        let alternatives = alternatives
+            .into_iter()
+            .map(|(s, weight)| (s.as_ref().to_lowercase(), store::synonym_penalty(weight)));
         self.synonyms
             .entry(synonym)
-            .or_insert_with(Vec::new)
+            .or_insert_with(BTreeMap::new)
             .extend(alternatives);
     }
 
@@ -60,7 +73,7 @@ pub fn push_synonyms_addition(
     writer: &mut heed::RwTxn<UpdateT>,
     updates_store: store::Updates,
     updates_results_store: store::UpdatesResults,
-    addition: BTreeMap<String, Vec<String>>,
+    addition: BTreeMap<String, BTreeMap<String, u64>>,
 ) -> MResult<u64> {
     let last_update_id = next_update_id(writer, updates_store, updates_results_store)?;
 
@@ -74,22 +87,22 @@ pub fn apply_synonyms_addition(
     writer: &mut heed::RwTxn<MainT>,
     main_store: store::Main,
     synonyms_store: store::Synonyms,
-    addition: BTreeMap<String, Vec<String>>,
+    addition: BTreeMap<String, BTreeMap<String, u64>>,
 ) -> MResult<()> {
     let mut synonyms_builder = SetBuilder::memory();
 
     for (word, alternatives) in addition {
         synonyms_builder.insert(&word).unwrap();
 
+        // the penalties of the alternatives are stored as the values of the fst
         let alternatives = {
-            let alternatives = SetBuf::from_dirty(alternatives);
-            let mut alternatives_builder = SetBuilder::memory();
+            let mut alternatives_builder = MapBuilder::memory();
             alternatives_builder.extend_iter(alternatives).unwrap();
             let bytes = alternatives_builder.into_inner().unwrap();
-            fst::Set::from_bytes(bytes).unwrap()
+            fst::Map::from_bytes(bytes).unwrap()
         };
 
-        synonyms_store.put_synonyms(writer, word.as_bytes(), &alternatives)?;
+        synonyms_store.put_weighted_synonyms(writer, word.as_bytes(), &alternatives)?;
     }
 
     let delta_synonyms = synonyms_builder
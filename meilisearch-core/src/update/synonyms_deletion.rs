@@ -1,7 +1,7 @@
 use std::collections::BTreeMap;
 use std::iter::FromIterator;
 
-use fst::{set::OpBuilder, SetBuilder};
+use fst::{set::OpBuilder, MapBuilder, SetBuilder, Streamer};
 use sdset::SetBuf;
 
 use crate::database::{MainT, UpdateT};
@@ -88,7 +88,8 @@ pub fn apply_synonyms_deletion(
     for (synonym, alternatives) in deletion {
         match alternatives {
             Some(alternatives) => {
-                let prev_alternatives = synonyms_store.synonyms(writer, synonym.as_bytes())?;
+                let prev_alternatives =
+                    synonyms_store.synonyms_penalties(writer, synonym.as_bytes())?;
                 let prev_alternatives = match prev_alternatives {
                     Some(alternatives) => alternatives,
                     None => continue,
@@ -101,18 +102,19 @@ pub fn apply_synonyms_deletion(
                     builder.into_inner().and_then(fst::Set::from_bytes).unwrap()
                 };
 
-                let op = OpBuilder::new()
-                    .add(prev_alternatives.stream())
-                    .add(delta_alternatives.stream())
-                    .difference();
-
+                // the remaining alternatives keep their penalties
                 let (alternatives, empty_alternatives) = {
-                    let mut builder = SetBuilder::memory();
-                    let len = builder.get_ref().len();
-                    builder.extend_stream(op).unwrap();
-                    let is_empty = len == builder.get_ref().len();
+                    let mut builder = MapBuilder::memory();
+                    let mut is_empty = true;
+                    let mut stream = prev_alternatives.stream();
+                    while let Some((alternative, penalty)) = stream.next() {
+                        if !delta_alternatives.contains(alternative) {
+                            builder.insert(alternative, penalty).unwrap();
+                            is_empty = false;
+                        }
+                    }
                     let bytes = builder.into_inner().unwrap();
-                    let alternatives = fst::Set::from_bytes(bytes).unwrap();
+                    let alternatives = fst::Map::from_bytes(bytes).unwrap();
 
                     (alternatives, is_empty)
                 };
@@ -120,7 +122,11 @@ pub fn apply_synonyms_deletion(
                 if empty_alternatives {
                     delete_whole_synonym_builder.insert(synonym.as_bytes())?;
                 } else {
-                    synonyms_store.put_synonyms(writer, synonym.as_bytes(), &alternatives)?;
+                    synonyms_store.put_weighted_synonyms(
+                        writer,
+                        synonym.as_bytes(),
+                        &alternatives,
+                    )?;
                 }
             }
             None => {
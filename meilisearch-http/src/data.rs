@@ -10,6 +10,8 @@ use sysinfo::Pid;
 
 use crate::option::Opt;
 use crate::routes::index::index_update_callback;
+use crate::routes::setting;
+use crate::search_cache::SearchCache;
 
 const LAST_UPDATE_KEY: &str = "last-update";
 
@@ -34,6 +36,7 @@ pub struct DataInner {
     pub db_path: String,
     pub api_key: Option<String>,
     pub server_pid: Pid,
+    pub search_cache: Option<Arc<SearchCache>>,
 }
 
 impl DataInner {
@@ -105,14 +108,17 @@ impl Data {
         let db_path = opt.db_path.clone();
         let api_key = opt.api_key.clone();
         let server_pid = sysinfo::get_current_pid().unwrap();
+        let search_cache = opt.search_cache_size.and_then(SearchCache::new).map(Arc::new);
 
         let db = Arc::new(Database::open_or_create(opt.db_path.clone()).unwrap());
+        upgrade_customs(&db);
 
         let inner_data = DataInner {
             db: db.clone(),
             db_path,
             api_key,
             server_pid,
+            search_cache,
         };
 
         let data = Data {
@@ -127,3 +133,28 @@ impl Data {
         data
     }
 }
+
+/// Rewrites the settings stored by previous versions in the current format.
+fn upgrade_customs(db: &Database) {
+    for index_uid in db.indexes_uids() {
+        let index = match db.open_index(&index_uid) {
+            Some(index) => index,
+            None => continue,
+        };
+
+        let result = db
+            .main_write_txn()
+            .map_err(MError::Zlmdb)
+            .and_then(|mut writer| {
+                setting::upgrade_customs(&mut writer, &index)?;
+                writer.commit().map_err(MError::Zlmdb)
+            });
+
+        if let Err(e) = result {
+            error!(
+                "Impossible to upgrade the settings of index {}: {}",
+                index_uid, e
+            );
+        }
+    }
+}
@@ -2,10 +2,12 @@ use crate::routes::setting::{RankingOrdering, SettingBody};
 use indexmap::IndexMap;
 use log::error;
 use meilisearch_core::criterion::*;
-use meilisearch_core::Highlight;
-use meilisearch_core::{Index, RankedMap};
+use meilisearch_core::{Highlight, MatchKind};
+use meilisearch_core::serde::compute_document_id;
+use meilisearch_core::{matching_rule, Index, Language, RankedMap};
 use meilisearch_core::MainT;
 use meilisearch_schema::{Schema, SchemaAttr};
+use roaring::RoaringTreemap;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::cmp::Ordering;
@@ -25,6 +27,7 @@ pub enum Error {
     AttributeNotFoundOnSchema(String),
     MissingFilterValue,
     UnknownFilteredAttribute,
+    InvalidRankingRule(String, String),
     Internal(String),
 }
 
@@ -53,6 +56,9 @@ impl fmt::Display for Error {
             UnknownFilteredAttribute => {
                 f.write_str("a filter is specifying an unknown schema attribute")
             }
+            InvalidRankingRule(rule, err) => {
+                write!(f, "the ranking rule {} is invalid; {}", rule, err)
+            }
             Internal(err) => write!(f, "internal error; {}", err),
         }
     }
@@ -82,6 +88,8 @@ impl IndexSearchExt for Index {
             filters: None,
             timeout: Duration::from_millis(30),
             matches: false,
+            language: None,
+            excluded_documents: HashSet::new(),
         }
     }
 }
@@ -98,6 +106,8 @@ pub struct SearchBuilder<'a> {
     filters: Option<String>,
     timeout: Duration,
     matches: bool,
+    language: Option<Language>,
+    excluded_documents: HashSet<meilisearch_core::DocumentId>,
 }
 
 impl<'a> SearchBuilder<'a> {
@@ -158,6 +168,16 @@ impl<'a> SearchBuilder<'a> {
         self
     }
 
+    pub fn language(&mut self, value: Language) -> &SearchBuilder {
+        self.language = Some(value);
+        self
+    }
+
+    pub fn exclude_document(&mut self, value: meilisearch_core::DocumentId) -> &SearchBuilder {
+        self.excluded_documents.insert(value);
+        self
+    }
+
     pub fn search(&self, reader: &heed::RoTxn<MainT>) -> Result<SearchResult, Error> {
         let schema = self.index.main.schema(reader);
         let schema = schema.map_err(|e| Error::Internal(e.to_string()))?;
@@ -170,14 +190,20 @@ impl<'a> SearchBuilder<'a> {
         let ranked_map = ranked_map.map_err(|e| Error::Internal(e.to_string()))?;
         let ranked_map = ranked_map.unwrap_or_default();
 
+        let settings = self.settings(reader)?;
+
         let start = Instant::now();
 
         // Change criteria
-        let mut query_builder = match self.get_criteria(reader, &ranked_map, &schema)? {
+        let mut query_builder = match self.get_criteria(reader, &settings, &ranked_map, &schema)? {
             Some(criteria) => self.index.query_builder_with_criteria(criteria),
             None => self.index.query_builder(),
         };
 
+        if let Some(language) = self.language {
+            query_builder.with_language(language);
+        }
+
         // Filter searchable fields
         if let Some(fields) = &self.attributes_to_search_in {
             for attribute in fields.iter().filter_map(|f| schema.attribute(f)) {
@@ -185,90 +211,114 @@ impl<'a> SearchBuilder<'a> {
             }
         }
 
-        if let Some(filters) = &self.filters {
-            let mut split = filters.split(':');
-            match (split.next(), split.next()) {
-                (Some(_), None) | (Some(_), Some("")) => return Err(Error::MissingFilterValue),
-                (Some(attr), Some(value)) => {
-                    let ref_reader = reader;
-                    let ref_index = &self.index;
-                    let value = value.trim().to_lowercase();
-
-                    let attr = match schema.attribute(attr) {
-                        Some(attr) => attr,
-                        None => return Err(Error::UnknownFilteredAttribute),
-                    };
-
-                    query_builder.with_filter(move |id| {
-                        let attr = attr;
-                        let index = ref_index;
-                        let reader = ref_reader;
-
-                        match index.document_attribute::<Value>(reader, id, attr) {
-                            Ok(Some(Value::String(s))) => s.to_lowercase() == value,
-                            Ok(Some(Value::Bool(b))) => {
-                                (value == "true" && b) || (value == "false" && !b)
-                            }
-                            Ok(Some(Value::Array(a))) => {
-                                a.into_iter().any(|s| s.as_str() == Some(&value))
-                            }
-                            _ => false,
-                        }
-                    });
-                }
-                (_, _) => (),
-            }
+        let query_rules = self.index.main.query_rules(reader);
+        let query_rules = query_rules.map_err(|e| Error::Internal(e.to_string()))?;
+        let query_rules = query_rules.unwrap_or_default();
+        let query_rule = matching_rule(&query_rules, &self.query);
+
+        let mut filters = Vec::new();
+        if let Some(value) = &self.filters {
+            filters.push(parse_filter(&schema, value)?);
         }
 
-        query_builder.with_fetch_timeout(self.timeout);
+        if let Some(value) = query_rule.and_then(|rule| rule.filters.as_ref()) {
+            filters.push(parse_filter(&schema, value)?);
+        }
 
-        let docs =
-            query_builder.query(reader, &self.query, self.offset..(self.offset + self.limit));
+        let filtered = if filters.is_empty() {
+            None
+        } else {
+            Some(filters_candidates(self.index, reader, &filters)?)
+        };
 
-        let mut hits = Vec::with_capacity(self.limit);
-        for doc in docs.map_err(|e| Error::SearchDocuments(e.to_string()))? {
-            // retrieve the content of document in kv store
-            let mut fields: Option<HashSet<&str>> = None;
-            if let Some(attributes_to_retrieve) = &self.attributes_to_retrieve {
-                let mut set = HashSet::new();
-                for field in attributes_to_retrieve {
-                    set.insert(field.as_str());
+        let mut hidden = self.excluded_documents.clone();
+        let mut pinned = Vec::new();
+        if let Some(rule) = query_rule {
+            hidden.extend(rule.hide.iter().map(compute_document_id));
+
+            let mut pins: Vec<_> = rule.pins.iter().collect();
+            pins.sort_by_key(|pin| pin.position);
+
+            for pin in pins {
+                let id = compute_document_id(&pin.id);
+                if hidden.contains(&id) || pinned.iter().any(|(_, pinned_id)| *pinned_id == id) {
+                    continue;
                 }
-                fields = Some(set);
-            }
 
-            let document: IndexMap<String, Value> = self
-                .index
-                .document(reader, fields.as_ref(), doc.id)
-                .map_err(|e| Error::RetrieveDocument(doc.id.0, e.to_string()))?
-                .ok_or(Error::DocumentNotFound(doc.id.0))?;
+                // the pins of documents that do not match the filters are ignored
+                if filtered.as_ref().map_or(false, |c| !c.contains(id.0)) {
+                    continue;
+                }
 
-            let mut formatted = document.clone();
-            let mut matches = doc.highlights.clone();
+                // the pins of documents that are not in the index are ignored
+                let mut fields_counts = self
+                    .index
+                    .documents_fields_counts
+                    .document_fields_counts(reader, id)
+                    .map_err(|e| Error::Internal(e.to_string()))?;
+                if fields_counts.next().is_none() {
+                    continue;
+                }
 
-            // Crops fields if needed
-            if let Some(fields) = &self.attributes_to_crop {
-                crop_document(&mut formatted, &mut matches, &schema, fields);
+                // two documents pinned at the same position are placed one after the other
+                let position = match pinned.last() {
+                    Some((last, _)) => pin.position.max(last + 1),
+                    None => pin.position,
+                };
+                pinned.push((position, id));
             }
+        }
 
-            // Transform to readable matches
-            let matches = calculate_matches(matches, self.attributes_to_retrieve.clone(), &schema);
+        // the hidden and pinned documents are removed from the candidates before the
+        // documents are sorted, the query does not return them in place of other hits
+        if filtered.is_some() || !hidden.is_empty() || !pinned.is_empty() {
+            let mut candidates = match filtered {
+                Some(candidates) => candidates,
+                None => all_documents(self.index, reader)?,
+            };
 
-            if !self.matches {
-                if let Some(attributes_to_highlight) = &self.attributes_to_highlight {
-                    formatted = calculate_highlights(&formatted, &matches, attributes_to_highlight);
-                }
+            for id in hidden.iter().chain(pinned.iter().map(|(_, id)| id)) {
+                candidates.remove(id.0);
             }
 
-            let matches_info = if self.matches { Some(matches) } else { None };
+            query_builder.with_candidates(candidates);
+        }
 
-            let hit = SearchHit {
-                document,
-                formatted,
-                matches_info,
-            };
+        query_builder.with_fetch_timeout(self.timeout);
 
-            hits.push(hit);
+        // the pinned documents take the place of the documents found by the query
+        let start = self.offset;
+        let end = self.offset + self.limit;
+        let pinned_before = pinned.iter().filter(|(p, _)| *p < start).count();
+        let pinned_within = pinned
+            .iter()
+            .filter(|(p, _)| *p >= start && *p < end)
+            .count();
+        let range = (start - pinned_before)..(end - pinned_before - pinned_within);
+
+        let query_start = Instant::now();
+        let docs = query_builder.query(reader, &self.query, range);
+        let docs = docs.map_err(|e| Error::SearchDocuments(e.to_string()))?;
+
+        // the results of a search that ran out of time can be incomplete and the ones
+        // of a search ranked by a decay from the current time change over time
+        let timed_out = query_start.elapsed() > self.timeout;
+        let cacheable = !timed_out && !depends_on_search_time(&settings);
+
+        let mut found = docs
+            .into_iter()
+            .map(|doc| (doc.id, doc.highlights, doc.query_words));
+        let mut documents = Vec::with_capacity(self.limit);
+        for position in start..end {
+            match pinned.iter().find(|(p, _)| *p == position) {
+                Some((_, id)) => documents.push((*id, Vec::new(), Vec::new())),
+                None => documents.extend(found.next()),
+            }
+        }
+
+        let mut hits = Vec::with_capacity(self.limit);
+        for (id, highlights, query_words) in documents {
+            hits.push(self.search_hit(reader, &schema, id, highlights, &query_words)?);
         }
 
         let time_ms = start.elapsed().as_millis() as usize;
@@ -279,54 +329,167 @@ impl<'a> SearchBuilder<'a> {
             limit: self.limit,
             processing_time_ms: time_ms,
             query: self.query.to_string(),
+            cacheable,
         };
 
         Ok(results)
     }
 
+    /// Searches for the query in a single document, the document is the
+    /// only candidate of the search and is therefore not ranked.
+    pub fn search_in_document(
+        &self,
+        reader: &heed::RoTxn<MainT>,
+        document_id: meilisearch_core::DocumentId,
+    ) -> Result<SearchHit, Error> {
+        let schema = self.index.main.schema(reader);
+        let schema = schema.map_err(|e| Error::Internal(e.to_string()))?;
+        let schema = match schema {
+            Some(schema) => schema,
+            None => return Err(Error::Internal(String::from("missing schema"))),
+        };
+
+        let mut query_builder = self
+            .index
+            .query_builder_with_criteria(CriteriaBuilder::new().build());
+
+        if let Some(language) = self.language {
+            query_builder.with_language(language);
+        }
+
+        if let Some(fields) = &self.attributes_to_search_in {
+            for attribute in fields.iter().filter_map(|f| schema.attribute(f)) {
+                query_builder.add_searchable_attribute(attribute.0);
+            }
+        }
+
+        let mut candidates = RoaringTreemap::new();
+        candidates.insert(document_id.0);
+        query_builder.with_candidates(candidates);
+        query_builder.with_fetch_timeout(self.timeout);
+
+        let docs = query_builder.query(reader, &self.query, 0..1);
+        let docs = docs.map_err(|e| Error::SearchDocuments(e.to_string()))?;
+
+        // a document that does not match the query has nothing to highlight
+        let (highlights, query_words) = docs
+            .into_iter()
+            .next()
+            .map(|doc| (doc.highlights, doc.query_words))
+            .unwrap_or_default();
+
+        self.search_hit(reader, &schema, document_id, highlights, &query_words)
+    }
+
+    fn search_hit(
+        &self,
+        reader: &heed::RoTxn<MainT>,
+        schema: &Schema,
+        id: meilisearch_core::DocumentId,
+        highlights: Vec<Highlight>,
+        query_words: &[String],
+    ) -> Result<SearchHit, Error> {
+        // retrieve the content of document in kv store
+        let mut fields: Option<HashSet<&str>> = None;
+        if let Some(attributes_to_retrieve) = &self.attributes_to_retrieve {
+            let mut set = HashSet::new();
+            for field in attributes_to_retrieve {
+                set.insert(field.as_str());
+            }
+            fields = Some(set);
+        }
+
+        let document: IndexMap<String, Value> = self
+            .index
+            .document(reader, fields.as_ref(), id)
+            .map_err(|e| Error::RetrieveDocument(id.0, e.to_string()))?
+            .ok_or(Error::DocumentNotFound(id.0))?;
+
+        let mut formatted = document.clone();
+        let mut matches = highlights;
+
+        // Crops fields if needed
+        if let Some(fields) = &self.attributes_to_crop {
+            crop_document(&mut formatted, &mut matches, schema, fields);
+        }
+
+        // Transform to readable matches
+        let matches = calculate_matches(
+            matches,
+            query_words,
+            self.attributes_to_retrieve.clone(),
+            schema,
+        );
+
+        if !self.matches {
+            if let Some(attributes_to_highlight) = &self.attributes_to_highlight {
+                formatted = calculate_highlights(&formatted, &matches, attributes_to_highlight);
+            }
+        }
+
+        let matches_info = if self.matches { Some(matches) } else { None };
+
+        Ok(SearchHit {
+            document,
+            formatted,
+            matches_info,
+        })
+    }
+
+    fn settings(&self, reader: &heed::RoTxn<MainT>) -> Result<SettingBody, Error> {
+        let customs = self.index.main.customs(reader);
+        match customs.map_err(|e| Error::Internal(e.to_string()))? {
+            Some(bytes) => {
+                SettingBody::from_customs(bytes).map_err(|e| Error::Internal(e.to_string()))
+            }
+            None => Ok(SettingBody::default()),
+        }
+    }
+
     pub fn get_criteria(
         &self,
         reader: &heed::RoTxn<MainT>,
+        settings: &SettingBody,
         ranked_map: &'a RankedMap,
         schema: &Schema,
     ) -> Result<Option<Criteria<'a>>, Error> {
-        let current_settings = match self.index.main.customs(reader).unwrap() {
-            Some(bytes) => bincode::deserialize(bytes).unwrap(),
-            None => SettingBody::default(),
-        };
-
-        let ranking_rules = &current_settings.ranking_rules;
-        let ranking_order = &current_settings.ranking_order;
+        let ranking_rules = &settings.ranking_rules;
+        let ranking_order = &settings.ranking_order;
 
         if let Some(ranking_rules) = ranking_rules {
-            let mut builder = CriteriaBuilder::with_capacity(7 + ranking_rules.len());
+            let mut builder = CriteriaBuilder::with_capacity(8 + ranking_rules.len());
             if let Some(ranking_rules_order) = ranking_order {
                 for rule in ranking_rules_order {
                     match rule.as_str() {
                         "_sum_of_typos" => builder.push(SumOfTypos),
+                        "_synonym_penalty" => builder.push(SynonymPenalty),
+                        "_phonetic" => builder.push(Phonetic),
                         "_number_of_words" => builder.push(NumberOfWords),
                         "_word_proximity" => builder.push(WordsProximity),
+                        "_whole_attribute" => builder.push(WholeAttribute),
                         "_sum_of_words_attribute" => builder.push(SumOfWordsAttribute),
                         "_sum_of_words_position" => builder.push(SumOfWordsPosition),
                         "_exact" => builder.push(Exact),
+                        "_exact_form" => builder.push(ExactForm),
+                        "_boost" => {
+                            let boost = Formula::boost(ranked_map, schema);
+                            builder.push(boost.map_err(|e| invalid_ranking_rule(rule, e))?);
+                        }
+                        "_words_rarity" => {
+                            let number_of_documents = self
+                                .index
+                                .main
+                                .number_of_documents(reader)
+                                .map_err(|e| Error::Internal(e.to_string()))?;
+                            builder.push(WordsRarity::new(number_of_documents))
+                        }
                         _ => {
                             let order = match ranking_rules.get(rule.as_str()) {
                                 Some(o) => o,
                                 None => continue,
                             };
 
-                            let custom_ranking = match order {
-                                RankingOrdering::Asc => {
-                                    SortByAttr::lower_is_better(&ranked_map, &schema, &rule)
-                                        .unwrap()
-                                }
-                                RankingOrdering::Dsc => {
-                                    SortByAttr::higher_is_better(&ranked_map, &schema, &rule)
-                                        .unwrap()
-                                }
-                            };
-
-                            builder.push(custom_ranking);
+                            builder.push(custom_ranking(ranked_map, schema, rule, order)?);
                         }
                     }
                 }
@@ -340,15 +503,7 @@ impl<'a> SearchBuilder<'a> {
                 builder.push(SumOfWordsPosition);
                 builder.push(Exact);
                 for (rule, order) in ranking_rules.iter() {
-                    let custom_ranking = match order {
-                        RankingOrdering::Asc => {
-                            SortByAttr::lower_is_better(&ranked_map, &schema, &rule).unwrap()
-                        }
-                        RankingOrdering::Dsc => {
-                            SortByAttr::higher_is_better(&ranked_map, &schema, &rule).unwrap()
-                        }
-                    };
-                    builder.push(custom_ranking);
+                    builder.push(custom_ranking(ranked_map, schema, rule, order)?);
                 }
                 builder.push(DocumentId);
                 return Ok(Some(builder.build()));
@@ -359,18 +514,148 @@ impl<'a> SearchBuilder<'a> {
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, PartialOrd, Serialize, Deserialize)]
+/// Returns whether the ranking rules rank the documents by a
+/// decay from the time of the search and not from a fixed origin.
+fn depends_on_search_time(settings: &SettingBody) -> bool {
+    let ranking_rules = match &settings.ranking_rules {
+        Some(ranking_rules) => ranking_rules,
+        None => return false,
+    };
+
+    ranking_rules.values().any(|order| match order {
+        RankingOrdering::Decay(rule) => rule.origin.is_none(),
+        _ => false,
+    })
+}
+
+fn parse_filter(schema: &Schema, filter: &str) -> Result<(SchemaAttr, String), Error> {
+    let mut split = filter.split(':');
+    match (split.next(), split.next()) {
+        (Some(attr), Some(value)) if !value.is_empty() => {
+            let attr = match schema.attribute(attr) {
+                Some(attr) => attr,
+                None => return Err(Error::UnknownFilteredAttribute),
+            };
+            Ok((attr, value.trim().to_lowercase()))
+        }
+        (_, _) => Err(Error::MissingFilterValue),
+    }
+}
+
+/// Returns the documents matching all the filters, the documents associated
+/// with the value of each filter at indexing time are intersected as bitmaps.
+fn filters_candidates(
+    index: &Index,
+    reader: &heed::RoTxn<MainT>,
+    filters: &[(SchemaAttr, String)],
+) -> Result<RoaringTreemap, Error> {
+    let mut candidates: Option<RoaringTreemap> = None;
+
+    for (attr, value) in filters {
+        let documents = index
+            .documents_fields
+            .filter_value_documents(reader, *attr, value)
+            .map_err(|e| Error::Internal(e.to_string()))?;
+        let documents = documents.unwrap_or_else(RoaringTreemap::new);
+
+        match &mut candidates {
+            Some(candidates) => candidates.intersect_with(&documents),
+            None => candidates = Some(documents),
+        }
+    }
+
+    Ok(candidates.unwrap_or_else(RoaringTreemap::new))
+}
+
+/// Returns all the documents of the index, the candidates
+/// of a search from which documents must be removed.
+fn all_documents(index: &Index, reader: &heed::RoTxn<MainT>) -> Result<RoaringTreemap, Error> {
+    let documents_ids = index
+        .documents_fields_counts
+        .documents_ids(reader)
+        .map_err(|e| Error::Internal(e.to_string()))?;
+
+    let mut documents = RoaringTreemap::new();
+    for result in documents_ids {
+        let document_id = result.map_err(|e| Error::Internal(e.to_string()))?;
+        documents.insert(document_id.0);
+    }
+
+    Ok(documents)
+}
+
+/// Returns the criterion of a custom ranking rule, the rules that cannot
+/// be applied to the schema, e.g. on an attribute that is not ranked, are
+/// rejected when the settings are updated but the schema can change since.
+pub fn custom_ranking<'a>(
+    ranked_map: &'a RankedMap,
+    schema: &Schema,
+    attribute: &str,
+    order: &RankingOrdering,
+) -> Result<Box<dyn Criterion + 'a>, Error> {
+    let invalid = |err: SortByAttrError| invalid_ranking_rule(attribute, err);
+
+    let criterion: Box<dyn Criterion + 'a> = match order {
+        RankingOrdering::Asc => {
+            let criterion = SortByAttr::lower_is_better(ranked_map, schema, attribute);
+            Box::new(criterion.map_err(invalid)?)
+        }
+        RankingOrdering::Dsc => {
+            let criterion = SortByAttr::higher_is_better(ranked_map, schema, attribute);
+            Box::new(criterion.map_err(invalid)?)
+        }
+        RankingOrdering::Decay(rule) => {
+            let criterion = TimeDecay::new(ranked_map, schema, attribute, rule.to_decay());
+            Box::new(criterion.map_err(invalid)?)
+        }
+        RankingOrdering::Formula(formula) => {
+            let criterion = Formula::new(ranked_map, schema, formula);
+            Box::new(criterion.map_err(|e| invalid_ranking_rule(attribute, e))?)
+        }
+    };
+
+    Ok(criterion)
+}
+
+fn invalid_ranking_rule(rule: &str, error: impl fmt::Display) -> Error {
+    Error::InvalidRankingRule(rule.to_string(), error.to_string())
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchType {
+    Exact,
+    Prefix,
+    Typo,
+    Phonetic,
+    Synonym,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct MatchPosition {
     pub start: usize,
     pub length: usize,
+    pub query_word: String,
+    pub match_type: MatchType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub typos: Option<u8>,
 }
 
+/// The matches are ordered by position, all the fields are
+/// compared to be consistent with the equality.
 impl Ord for MatchPosition {
     fn cmp(&self, other: &Self) -> Ordering {
-        match self.start.cmp(&other.start) {
-            Ordering::Equal => self.length.cmp(&other.length),
-            _ => self.start.cmp(&other.start),
-        }
+        let key = |m: &Self| (m.start, m.length, m.match_type, m.typos);
+        key(self)
+            .cmp(&key(other))
+            .then_with(|| self.query_word.cmp(&other.query_word))
+    }
+}
+
+impl PartialOrd for MatchPosition {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
 }
 
@@ -396,6 +681,10 @@ pub struct SearchResult {
     pub limit: usize,
     pub processing_time_ms: usize,
     pub query: String,
+    /// Whether the result can be returned again to the same search
+    /// as long as the index has not been updated.
+    #[serde(skip)]
+    pub cacheable: bool,
     // pub parsed_query: String,
     // pub params: Option<String>,
 }
@@ -455,6 +744,7 @@ fn crop_document(
 
 fn calculate_matches(
     matches: Vec<Highlight>,
+    query_words: &[String],
     attributes_to_retrieve: Option<HashSet<String>>,
     schema: &Schema,
 ) -> MatchesInfos {
@@ -468,23 +758,29 @@ fn calculate_matches(
                 continue;
             }
         };
-        if let Some(pos) = matches_result.get_mut(&attribute) {
-            pos.push(MatchPosition {
-                start: m.char_index as usize,
-                length: m.char_length as usize,
-            });
-        } else {
-            let mut positions = Vec::new();
-            positions.push(MatchPosition {
-                start: m.char_index as usize,
-                length: m.char_length as usize,
-            });
-            matches_result.insert(attribute, positions);
-        }
+        let (match_type, typos) = match m.kind {
+            MatchKind::Exact => (MatchType::Exact, None),
+            MatchKind::Prefix => (MatchType::Prefix, None),
+            MatchKind::Typo(typos) => (MatchType::Typo, Some(typos)),
+            MatchKind::Phonetic => (MatchType::Phonetic, None),
+            MatchKind::Synonym => (MatchType::Synonym, None),
+        };
+        let position = MatchPosition {
+            start: m.char_index as usize,
+            length: m.char_length as usize,
+            query_word: query_words
+                .get(m.query_index as usize)
+                .cloned()
+                .unwrap_or_default(),
+            match_type,
+            typos,
+        };
+        matches_result.entry(attribute).or_default().push(position);
     }
+    // a word matched by several query words is reported with the best match
     for (_, val) in matches_result.iter_mut() {
         val.sort_unstable();
-        val.dedup();
+        val.dedup_by(|a, b| a.start == b.start && a.length == b.length);
     }
     matches_result
 }
@@ -530,6 +826,148 @@ fn calculate_highlights(
 mod tests {
     use super::*;
 
+    use std::sync::mpsc;
+
+    use meilisearch_core::{Database, PinnedDocument, ProcessedUpdateResult, QueryRule};
+    use meilisearch_schema::{SchemaBuilder, DISPLAYED, INDEXED};
+
+    /// Creates an index of four movies titled "Alien", the first two are horror
+    /// movies and the last two are dramas, then applies the given query rule.
+    fn alien_movies(database: &Database, rule: QueryRule) -> Index {
+        let (sender, receiver) = mpsc::sync_channel(100);
+        let update_fn = move |_name: &str, update: ProcessedUpdateResult| {
+            sender.send(update.update_id).unwrap()
+        };
+        let index = database.create_index("movies").unwrap();
+        database.set_update_callback(Box::new(update_fn));
+
+        let mut builder = SchemaBuilder::with_identifier("id");
+        builder.new_attribute("id", DISPLAYED);
+        builder.new_attribute("title", DISPLAYED | INDEXED);
+        builder.new_attribute("genre", DISPLAYED);
+        let schema = builder.build();
+
+        let mut update_writer = database.update_write_txn().unwrap();
+        let _update_id = index.schema_update(&mut update_writer, schema).unwrap();
+        update_writer.commit().unwrap();
+
+        let mut additions = index.documents_addition();
+        let genres = &[
+            ("1", "horror"),
+            ("2", "horror"),
+            ("3", "drama"),
+            ("4", "drama"),
+        ];
+        for (id, genre) in genres {
+            let document = serde_json::json!({ "id": id, "title": "Alien", "genre": genre });
+            additions.update_document(document);
+        }
+
+        let mut update_writer = database.update_write_txn().unwrap();
+        let _update_id = additions.finalize(&mut update_writer).unwrap();
+        let rules = vec![rule];
+        let update_id = index.query_rules_update(&mut update_writer, rules).unwrap();
+        update_writer.commit().unwrap();
+
+        // block until the transactions are processed
+        let _ = receiver.iter().find(|id| *id == update_id);
+
+        index
+    }
+
+    fn alien_rule() -> QueryRule {
+        QueryRule {
+            pattern: "alien".to_string(),
+            match_type: Default::default(),
+            pins: Vec::new(),
+            hide: Vec::new(),
+            filters: None,
+        }
+    }
+
+    fn hits_ids(result: &SearchResult) -> Vec<&str> {
+        result
+            .hits
+            .iter()
+            .map(|hit| hit.document["id"].as_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn pinned_documents_respect_filters() {
+        let dir = tempfile::tempdir().unwrap();
+        let database = Database::open_or_create(dir.path()).unwrap();
+
+        let mut rule = alien_rule();
+        let id = "3".to_string();
+        rule.pins.push(PinnedDocument { id, position: 0 });
+        let index = alien_movies(&database, rule);
+
+        let reader = database.main_read_txn().unwrap();
+
+        let result = index
+            .new_search("alien".to_string())
+            .search(&reader)
+            .unwrap();
+        assert_eq!(hits_ids(&result).len(), 4);
+        assert_eq!(hits_ids(&result)[0], "3");
+
+        let mut search = index.new_search("alien".to_string());
+        search.filters("genre:horror".to_string());
+        let result = search.search(&reader).unwrap();
+        let mut ids = hits_ids(&result);
+        ids.sort_unstable();
+        assert_eq!(ids, vec!["1", "2"]);
+    }
+
+    #[test]
+    fn hidden_documents_do_not_shorten_results() {
+        let dir = tempfile::tempdir().unwrap();
+        let database = Database::open_or_create(dir.path()).unwrap();
+
+        let mut rule = alien_rule();
+        rule.hide.push("1".to_string());
+        let id = "4".to_string();
+        rule.pins.push(PinnedDocument { id, position: 0 });
+        let index = alien_movies(&database, rule);
+
+        let reader = database.main_read_txn().unwrap();
+
+        let mut search = index.new_search("alien".to_string());
+        search.limit(3);
+        let result = search.search(&reader).unwrap();
+        let ids = hits_ids(&result);
+        assert_eq!(ids.len(), 3);
+        assert_eq!(ids[0], "4");
+        assert!(!ids.contains(&"1"));
+
+        let mut search = index.new_search("alien".to_string());
+        search.filters("genre:horror".to_string());
+        let result = search.search(&reader).unwrap();
+        assert_eq!(hits_ids(&result), vec!["2"]);
+    }
+
+    #[test]
+    fn rule_filters_apply_to_pins() {
+        let dir = tempfile::tempdir().unwrap();
+        let database = Database::open_or_create(dir.path()).unwrap();
+
+        let mut rule = alien_rule();
+        rule.filters = Some("genre:drama".to_string());
+        let id = "1".to_string();
+        rule.pins.push(PinnedDocument { id, position: 0 });
+        let index = alien_movies(&database, rule);
+
+        let reader = database.main_read_txn().unwrap();
+
+        let mut search = index.new_search("alien".to_string());
+        search.limit(2);
+        let result = search.search(&reader).unwrap();
+        let mut ids = hits_ids(&result);
+        ids.sort_unstable();
+        assert_eq!(ids, vec!["3", "4"]);
+    }
+
     #[test]
     fn calculate_highlights() {
         let data = r#"{
@@ -548,6 +986,9 @@ mod tests {
         m.push(MatchPosition {
             start: 0,
             length: 9,
+            query_word: "fondation".to_string(),
+            match_type: MatchType::Exact,
+            typos: None,
         });
         matches.insert("title".to_string(), m);
 
@@ -555,6 +996,9 @@ mod tests {
         m.push(MatchPosition {
             start: 510,
             length: 9,
+            query_word: "fondation".to_string(),
+            match_type: MatchType::Exact,
+            typos: None,
         });
         matches.insert("description".to_string(), m);
         let result = super::calculate_highlights(&document, &matches, &attributes_to_highlight);
@@ -4,5 +4,6 @@ pub mod helpers;
 pub mod models;
 pub mod option;
 pub mod routes;
+pub mod search_cache;
 
 pub use self::data::Data;
@@ -0,0 +1,164 @@
+//! Parsing of the synonyms and stop words files used by Solr and Elasticsearch.
+
+use crate::routes::synonym::{Alternative, Synonym};
+
+// Splits a list of words separated by commas, a comma
+// can be part of a word when it is escaped by a backslash.
+fn split_list(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => word.extend(chars.next()),
+            ',' => {
+                words.push(word.clone());
+                word.clear();
+            }
+            c => word.push(c),
+        }
+    }
+    words.push(word);
+
+    words
+        .into_iter()
+        .map(|w| w.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+/// Parses a synonyms file in the Solr format, also supported by Elasticsearch.
+///
+/// The equivalent words are separated by commas (`ipod, i-pod, i pod`) and are
+/// imported as multi-way synonyms, the explicit mappings (`i-pod, i pod => ipod`)
+/// are imported as one-way synonyms. The lines starting with `#` are ignored.
+pub fn parse_synonyms_file(text: &str) -> Result<Vec<Synonym>, String> {
+    let mut synonyms = Vec::new();
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut sides = line.splitn(2, "=>");
+        match (sides.next(), sides.next()) {
+            (Some(inputs), Some(outputs)) => {
+                if outputs.contains("=>") {
+                    return Err(format!("line {}: more than one `=>`", number + 1));
+                }
+
+                let inputs = split_list(inputs);
+                let outputs = split_list(outputs);
+                if inputs.is_empty() || outputs.is_empty() {
+                    return Err(format!("line {}: a side of the `=>` is empty", number + 1));
+                }
+
+                let outputs: Vec<_> = outputs.into_iter().map(Alternative::Word).collect();
+                for input in inputs {
+                    let synonyms_of_input = outputs.clone();
+                    synonyms.push(Synonym::OneWay {
+                        input,
+                        synonyms: synonyms_of_input,
+                    });
+                }
+            }
+            (Some(words), None) => {
+                let words = split_list(words);
+                if words.len() > 1 {
+                    let words = words.into_iter().map(Alternative::Word).collect();
+                    synonyms.push(Synonym::MultiWay { synonyms: words });
+                }
+            }
+            (None, _) => (),
+        }
+    }
+
+    Ok(synonyms)
+}
+
+/// Parses a stop words file, one or more words by line. The lines starting
+/// with `#` and the text following a `|`, the Snowball comments, are ignored.
+pub fn parse_stop_words_file(text: &str) -> Vec<String> {
+    let mut stop_words = Vec::new();
+
+    for line in text.lines() {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+
+        let line = line.split('|').next().unwrap_or_default();
+        stop_words.extend(line.split_whitespace().map(str::to_owned));
+    }
+
+    stop_words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(words: &[&str]) -> Vec<Alternative> {
+        words.iter().map(|w| Alternative::Word(w.to_string())).collect()
+    }
+
+    #[test]
+    fn synonyms_file() {
+        let text = r#"
+            # equivalent synonyms
+            ipod, i-pod, i pod
+            foo\,bar, baz
+
+            # explicit mappings
+            i-pad, i pad => ipad
+            sea biscuit => seabiscuit, sea biscit
+            lonely
+        "#;
+
+        let synonyms = parse_synonyms_file(text).unwrap();
+        let expected = vec![
+            Synonym::MultiWay {
+                synonyms: words(&["ipod", "i-pod", "i pod"]),
+            },
+            Synonym::MultiWay {
+                synonyms: words(&["foo,bar", "baz"]),
+            },
+            Synonym::OneWay {
+                input: "i-pad".to_string(),
+                synonyms: words(&["ipad"]),
+            },
+            Synonym::OneWay {
+                input: "i pad".to_string(),
+                synonyms: words(&["ipad"]),
+            },
+            Synonym::OneWay {
+                input: "sea biscuit".to_string(),
+                synonyms: words(&["seabiscuit", "sea biscit"]),
+            },
+        ];
+
+        assert_eq!(synonyms, expected);
+    }
+
+    #[test]
+    fn invalid_synonyms_file() {
+        assert!(parse_synonyms_file("a => b => c").is_err());
+        assert!(parse_synonyms_file("a, b\n => c").is_err());
+    }
+
+    #[test]
+    fn stop_words_file() {
+        let text = "
+            # english stop words
+            a
+            about above
+            | a Snowball comment
+            after | another comment
+        ";
+
+        let stop_words = parse_stop_words_file(text);
+        assert_eq!(stop_words, vec!["a", "about", "above", "after"]);
+    }
+}
@@ -1,3 +1,4 @@
+pub mod import;
 pub mod schema;
 pub mod token;
 pub mod update_operation;
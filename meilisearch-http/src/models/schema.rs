@@ -1,7 +1,7 @@
 use std::collections::HashSet;
 
 use indexmap::IndexMap;
-use meilisearch_schema::{Schema, SchemaBuilder, SchemaProps};
+use meilisearch_schema::{Language, Schema, SchemaBuilder, SchemaProps};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
@@ -11,6 +11,9 @@ pub enum FieldProperties {
     Indexed,
     Displayed,
     Ranked,
+    Infix,
+    Phonetic,
+    Language(Language),
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
@@ -30,6 +33,15 @@ impl From<Schema> for SchemaBody {
             if props.is_ranked() {
                 old_properties.insert(FieldProperties::Ranked);
             }
+            if props.is_infix() {
+                old_properties.insert(FieldProperties::Infix);
+            }
+            if props.is_phonetic() {
+                old_properties.insert(FieldProperties::Phonetic);
+            }
+            if let Some(language) = props.language() {
+                old_properties.insert(FieldProperties::Language(language));
+            }
         }
         let old_properties = map
             .entry(value.identifier_name().to_string())
@@ -48,11 +60,17 @@ impl Into<Schema> for SchemaBody {
             let mut indexed = false;
             let mut displayed = false;
             let mut ranked = false;
+            let mut infix = false;
+            let mut phonetic = false;
+            let mut language = None;
             for property in properties {
                 match property {
                     FieldProperties::Indexed => indexed = true,
                     FieldProperties::Displayed => displayed = true,
                     FieldProperties::Ranked => ranked = true,
+                    FieldProperties::Infix => infix = true,
+                    FieldProperties::Phonetic => phonetic = true,
+                    FieldProperties::Language(lang) => language = Some(lang),
                     FieldProperties::Identifier => identifier = field.clone(),
                 }
             }
@@ -62,6 +80,9 @@ impl Into<Schema> for SchemaBody {
                     indexed,
                     displayed,
                     ranked,
+                    infix,
+                    phonetic,
+                    language,
                 },
             );
         }
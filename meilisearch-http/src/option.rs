@@ -17,4 +17,8 @@ pub struct Opt {
     /// Do not send analytics to Meili.
     #[structopt(long, env = "MEILI_NO_ANALYTICS")]
     pub no_analytics: bool,
+
+    /// The number of search responses kept in cache, the cache is disabled if not specified or zero.
+    #[structopt(long, env = "MEILI_SEARCH_CACHE_SIZE")]
+    pub search_cache_size: Option<usize>,
 }
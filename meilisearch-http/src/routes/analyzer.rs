@@ -0,0 +1,106 @@
+use http::StatusCode;
+use meilisearch_core::{language_analyzer, Analyzer, Language};
+use serde_json::Value;
+use tide::response::IntoResponse;
+use tide::{Context, Response};
+
+use crate::error::{ResponseError, SResult};
+use crate::helpers::tide::ContextExt;
+use crate::models::token::ACL::*;
+use crate::routes::document::IndexUpdateResponse;
+use crate::Data;
+
+/// Decodes the analyzer sent in the body of a request, the
+/// fields that are not given take their default value.
+fn body_analyzer(body: Value) -> SResult<Analyzer> {
+    let analyzer = serde_json::to_value(Analyzer::default());
+    let mut analyzer = analyzer.map_err(ResponseError::internal)?;
+
+    match (analyzer.as_object_mut(), body) {
+        (Some(fields), Value::Object(body)) => fields.extend(body),
+        _ => return Err(ResponseError::bad_request("the analyzer must be an object")),
+    }
+
+    serde_json::from_value(analyzer).map_err(ResponseError::bad_request)
+}
+
+pub async fn get(ctx: Context<Data>) -> SResult<Response> {
+    ctx.is_allowed(SettingsRead)?;
+    let index = ctx.index()?;
+
+    let db = &ctx.state().db;
+    let reader = db.main_read_txn().map_err(ResponseError::internal)?;
+
+    let analyzer = index
+        .main
+        .analyzer(&reader)
+        .map_err(ResponseError::internal)?
+        .unwrap_or_default();
+
+    Ok(tide::response::json(analyzer))
+}
+
+pub async fn update(mut ctx: Context<Data>) -> SResult<Response> {
+    ctx.is_allowed(SettingsWrite)?;
+    let index = ctx.index()?;
+
+    let body: Value = ctx.body_json().await.map_err(ResponseError::bad_request)?;
+    let analyzer = body_analyzer(body)?;
+
+    let db = &ctx.state().db;
+    let mut writer = db.update_write_txn().map_err(ResponseError::internal)?;
+
+    let update_id = index
+        .analyzer_update(&mut writer, analyzer)
+        .map_err(ResponseError::internal)?;
+
+    writer.commit().map_err(ResponseError::internal)?;
+
+    let response_body = IndexUpdateResponse { update_id };
+    Ok(tide::response::json(response_body)
+        .with_status(StatusCode::ACCEPTED)
+        .into_response())
+}
+
+fn url_language(ctx: &Context<Data>) -> SResult<Language> {
+    let code = ctx.url_param("lang")?;
+    Language::from_code(&code)
+        .ok_or_else(|| ResponseError::bad_request(format!("unknown language {}", code)))
+}
+
+pub async fn get_language(ctx: Context<Data>) -> SResult<Response> {
+    ctx.is_allowed(SettingsRead)?;
+    let index = ctx.index()?;
+    let language = url_language(&ctx)?;
+
+    let db = &ctx.state().db;
+    let reader = db.main_read_txn().map_err(ResponseError::internal)?;
+
+    let analyzer = language_analyzer(&reader, index.main, Some(language))
+        .map_err(ResponseError::internal)?;
+
+    Ok(tide::response::json(analyzer))
+}
+
+pub async fn update_language(mut ctx: Context<Data>) -> SResult<Response> {
+    ctx.is_allowed(SettingsWrite)?;
+    let index = ctx.index()?;
+    let language = url_language(&ctx)?;
+
+    let body: Value = ctx.body_json().await.map_err(ResponseError::bad_request)?;
+    let analyzer = body_analyzer(body)?;
+
+    let db = &ctx.state().db;
+    let mut writer = db.update_write_txn().map_err(ResponseError::internal)?;
+
+    let update_id = index
+        .language_analyzer_update(&mut writer, language, analyzer)
+        .map_err(ResponseError::internal)?;
+
+    writer.commit().map_err(ResponseError::internal)?;
+
+    let response_body = IndexUpdateResponse { update_id };
+    Ok(tide::response::json(response_body)
+        .with_status(StatusCode::ACCEPTED)
+        .into_response())
+}
@@ -1,4 +1,5 @@
 use std::collections::{BTreeSet, HashSet};
+use std::str;
 
 use http::StatusCode;
 use indexmap::IndexMap;
@@ -9,6 +10,7 @@ use tide::response::IntoResponse;
 use tide::{Context, Response};
 
 use crate::error::{ResponseError, SResult};
+use crate::helpers::meilisearch::IndexSearchExt;
 use crate::helpers::tide::ContextExt;
 use crate::models::token::ACL::*;
 use crate::Data;
@@ -117,6 +119,78 @@ pub async fn get_all_documents(ctx: Context<Data>) -> SResult<Response> {
     Ok(tide::response::json(response_body))
 }
 
+/// The number of words of a document used to search for the similar documents.
+const SIMILAR_DOCUMENTS_WORDS: usize = 10;
+
+pub async fn get_similar_documents(ctx: Context<Data>) -> SResult<Response> {
+    ctx.is_allowed(DocumentsRead)?;
+
+    let index = ctx.index()?;
+    let identifier = ctx.identifier()?;
+    let document_id = meilisearch_core::serde::compute_document_id(identifier.clone());
+    let query: BrowseQuery = ctx.url_query().unwrap_or(BrowseQuery::default());
+
+    let db = &ctx.state().db;
+    let reader = db.main_read_txn().map_err(ResponseError::internal)?;
+
+    let words = index
+        .docs_words
+        .doc_words(&reader, document_id)
+        .map_err(ResponseError::internal)?
+        .ok_or(ResponseError::document_not_found(&identifier))?;
+
+    // the most discriminative words are the ones found in the fewest other
+    // documents, the words only found in this document can not be used
+    let mut candidates = Vec::new();
+    for word in words.stream().into_bytes() {
+        let word = match str::from_utf8(&word) {
+            Ok(word) => word,
+            Err(_) => continue,
+        };
+
+        let frequency = index
+            .postings_lists
+            .postings_docids(&reader, word.as_bytes())
+            .map_err(ResponseError::internal)?
+            .map_or(0, |docids| docids.len());
+
+        if frequency > 1 {
+            candidates.push((frequency, word.to_owned()));
+        }
+    }
+
+    candidates.sort_unstable();
+    candidates.truncate(SIMILAR_DOCUMENTS_WORDS);
+
+    // the query ends with a space for the last word not to be a prefix
+    let mut similar_query = String::new();
+    for (_, word) in candidates {
+        similar_query.push_str(&word);
+        similar_query.push(' ');
+    }
+
+    let mut search_builder = index.new_search(similar_query);
+    search_builder.exclude_document(document_id);
+
+    if let Some(offset) = query.offset {
+        search_builder.offset(offset);
+    }
+    if let Some(limit) = query.limit {
+        search_builder.limit(limit);
+    }
+    if let Some(attributes_to_retrieve) = query.attributes_to_retrieve {
+        for attr in attributes_to_retrieve.split(',') {
+            search_builder.add_retrievable_field(attr.to_string());
+        }
+    }
+
+    let response = search_builder
+        .search(&reader)
+        .map_err(ResponseError::internal)?;
+
+    Ok(tide::response::json(response))
+}
+
 fn infered_schema(document: &IndexMap<String, Value>) -> Option<meilisearch_schema::Schema> {
     use meilisearch_schema::{SchemaBuilder, DISPLAYED, INDEXED};
 
@@ -398,6 +398,10 @@ pub async fn delete_index(ctx: Context<Data>) -> SResult<StatusCode> {
         .delete_index(&index_uid)
         .map_err(ResponseError::internal)?;
 
+    if let Some(search_cache) = &ctx.state().search_cache {
+        search_cache.invalidate_index(&index_uid);
+    }
+
     if found {
         Ok(StatusCode::NO_CONTENT)
     } else {
@@ -410,6 +414,10 @@ pub fn index_update_callback(index_uid: &str, data: &Data, status: ProcessedUpda
         return;
     }
 
+    if let Some(search_cache) = &data.search_cache {
+        search_cache.invalidate_index(index_uid);
+    }
+
     if let Some(index) = data.db.open_index(&index_uid) {
         let db = &data.db;
         let mut writer = match db.main_write_txn() {
@@ -1,14 +1,17 @@
 use crate::data::Data;
 
+pub mod analyzer;
 pub mod document;
 pub mod health;
 pub mod index;
 pub mod key;
+pub mod query_rules;
 pub mod search;
 pub mod setting;
 pub mod stats;
 pub mod stop_words;
 pub mod synonym;
+pub mod words;
 
 pub fn load_routes(app: &mut tide::App<Data>) {
     app.at("").nest(|router| {
@@ -53,6 +56,10 @@ pub fn load_routes(app: &mut tide::App<Data>) {
                             .at("/")
                             .get(document::get_document)
                             .delete(document::delete_document);
+
+                        router.at("/similar").get(document::get_similar_documents);
+
+                        router.at("/highlight").get(search::search_in_document);
                     });
 
                     router
@@ -74,6 +81,8 @@ pub fn load_routes(app: &mut tide::App<Data>) {
                         .delete(synonym::delete);
 
                     router.at("/batch").post(synonym::batch_write);
+
+                    router.at("/import").post(synonym::import);
                 });
 
                 router.at("/stop-words").nest(|router| {
@@ -82,12 +91,31 @@ pub fn load_routes(app: &mut tide::App<Data>) {
                         .get(stop_words::list)
                         .patch(stop_words::add)
                         .delete(stop_words::delete);
+
+                    router.at("/import").post(stop_words::import);
                 });
 
+                router
+                    .at("/analyzer")
+                    .get(analyzer::get)
+                    .post(analyzer::update);
+
+                router
+                    .at("/analyzers/:lang")
+                    .get(analyzer::get_language)
+                    .post(analyzer::update_language);
+
+                router
+                    .at("/query-rules")
+                    .get(query_rules::get)
+                    .post(query_rules::update);
+
                 router
                     .at("/settings")
                     .get(setting::get)
                     .post(setting::update);
+
+                router.at("/words").get(words::list);
             });
         });
 
@@ -0,0 +1,64 @@
+use http::StatusCode;
+use meilisearch_core::QueryRule;
+use tide::response::IntoResponse;
+use tide::{Context, Response};
+
+use crate::error::{ResponseError, SResult};
+use crate::helpers::tide::ContextExt;
+use crate::models::token::ACL::*;
+use crate::routes::document::IndexUpdateResponse;
+use crate::Data;
+
+pub async fn get(ctx: Context<Data>) -> SResult<Response> {
+    ctx.is_allowed(SettingsRead)?;
+    let index = ctx.index()?;
+
+    let db = &ctx.state().db;
+    let reader = db.main_read_txn().map_err(ResponseError::internal)?;
+
+    let rules = index
+        .main
+        .query_rules(&reader)
+        .map_err(ResponseError::internal)?
+        .unwrap_or_default();
+
+    Ok(tide::response::json(rules))
+}
+
+pub async fn update(mut ctx: Context<Data>) -> SResult<Response> {
+    ctx.is_allowed(SettingsWrite)?;
+    let index = ctx.index()?;
+
+    let rules: Vec<QueryRule> = ctx.body_json().await.map_err(ResponseError::bad_request)?;
+
+    for rule in &rules {
+        if rule.pattern.trim().is_empty() {
+            return Err(ResponseError::bad_request("a query rule has an empty pattern"));
+        }
+
+        if let Some(filters) = &rule.filters {
+            let mut split = filters.split(':');
+            match (split.next(), split.next()) {
+                (Some(_), Some(value)) if !value.is_empty() => (),
+                _ => {
+                    let message = format!("the filter {:?} of a query rule is invalid", filters);
+                    return Err(ResponseError::bad_request(message));
+                }
+            }
+        }
+    }
+
+    let db = &ctx.state().db;
+    let mut writer = db.update_write_txn().map_err(ResponseError::internal)?;
+
+    let update_id = index
+        .query_rules_update(&mut writer, rules)
+        .map_err(ResponseError::internal)?;
+
+    writer.commit().map_err(ResponseError::internal)?;
+
+    let response_body = IndexUpdateResponse { update_id };
+    Ok(tide::response::json(response_body)
+        .with_status(StatusCode::ACCEPTED)
+        .into_response())
+}
@@ -1,8 +1,9 @@
 use std::collections::HashMap;
 use std::collections::HashSet;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use meilisearch_core::Index;
+use meilisearch_core::{Index, Language};
+use meilisearch_schema::Schema;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use serde::{Deserialize, Serialize};
 use tide::querystring::ContextExt as QSContextExt;
@@ -11,9 +12,10 @@ use tide::{Context, Response};
 use crate::error::{ResponseError, SResult};
 use crate::helpers::meilisearch::{Error, IndexSearchExt, SearchHit};
 use crate::helpers::tide::ContextExt;
+use crate::models::token::ACL::*;
 use crate::Data;
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 struct SearchQuery {
     q: String,
@@ -27,13 +29,75 @@ struct SearchQuery {
     filters: Option<String>,
     timeout_ms: Option<u64>,
     matches: Option<bool>,
+    lang: Option<String>,
+}
+
+impl SearchQuery {
+    /// Returns a representation of this query that does not depend
+    /// on the order in which the attributes lists were specified.
+    fn normalized(&self) -> String {
+        fn sorted_list(list: &Option<String>) -> Option<String> {
+            list.as_ref().map(|list| {
+                let mut attributes: Vec<_> = list.split(',').collect();
+                attributes.sort_unstable();
+                attributes.dedup();
+                attributes.join(",")
+            })
+        }
+
+        let query = SearchQuery {
+            q: self.q.clone(),
+            offset: self.offset,
+            limit: self.limit,
+            attributes_to_retrieve: sorted_list(&self.attributes_to_retrieve),
+            attributes_to_search_in: sorted_list(&self.attributes_to_search_in),
+            attributes_to_crop: sorted_list(&self.attributes_to_crop),
+            crop_length: self.crop_length,
+            attributes_to_highlight: sorted_list(&self.attributes_to_highlight),
+            filters: self.filters.clone(),
+            timeout_ms: self.timeout_ms,
+            matches: self.matches,
+            lang: self.lang.clone(),
+        };
+
+        serde_json::to_string(&query).unwrap()
+    }
 }
 
 pub async fn search_with_url_query(ctx: Context<Data>) -> SResult<Response> {
     // ctx.is_allowed(DocumentsRead)?;
 
+    let start = Instant::now();
+    let index_uid = ctx.url_param("index")?;
     let index = ctx.index()?;
     let db = &ctx.state().db;
+
+    let query: SearchQuery = ctx
+        .url_query()
+        .map_err(|_| ResponseError::bad_request("invalid query parameter"))?;
+
+    // the last update id is read before the documents, a response is therefore
+    // never cached with the id of an update it does not take into account
+    let search_cache = ctx.state().search_cache.as_ref();
+    let cache_entry = match search_cache {
+        Some(search_cache) => {
+            let update_reader = db.update_read_txn().map_err(ResponseError::internal)?;
+            let update_id = index
+                .last_processed_update_id(&update_reader)
+                .map_err(ResponseError::internal)?;
+            let request = query.normalized();
+
+            // the processing time is the one of this request, not the cached one
+            if let Some(mut response) = search_cache.get(&index_uid, update_id, &request) {
+                response.processing_time_ms = start.elapsed().as_millis() as usize;
+                return Ok(tide::response::json(response));
+            }
+
+            Some((search_cache, update_id, request))
+        }
+        None => None,
+    };
+
     let reader = db.main_read_txn().map_err(ResponseError::internal)?;
 
     let schema = index
@@ -42,10 +106,6 @@ pub async fn search_with_url_query(ctx: Context<Data>) -> SResult<Response> {
         .map_err(ResponseError::internal)?
         .ok_or(ResponseError::open_index("No Schema found"))?;
 
-    let query: SearchQuery = ctx
-        .url_query()
-        .map_err(|_| ResponseError::bad_request("invalid query parameter"))?;
-
     let mut search_builder = index.new_search(query.q.clone());
 
     if let Some(offset) = query.offset {
@@ -67,31 +127,12 @@ pub async fn search_with_url_query(ctx: Context<Data>) -> SResult<Response> {
     }
     if let Some(attributes_to_crop) = query.attributes_to_crop {
         let crop_length = query.crop_length.unwrap_or(200);
-        if attributes_to_crop == "*" {
-            let attributes_to_crop = schema
-                .iter()
-                .map(|(attr, ..)| (attr.to_string(), crop_length))
-                .collect();
-            search_builder.attributes_to_crop(attributes_to_crop);
-        } else {
-            let attributes_to_crop = attributes_to_crop
-                .split(',')
-                .map(|r| (r.to_string(), crop_length))
-                .collect();
-            search_builder.attributes_to_crop(attributes_to_crop);
-        }
+        let attributes_to_crop = crop_list(&schema, &attributes_to_crop, crop_length);
+        search_builder.attributes_to_crop(attributes_to_crop);
     }
 
     if let Some(attributes_to_highlight) = query.attributes_to_highlight {
-        let attributes_to_highlight = if attributes_to_highlight == "*" {
-            schema.iter().map(|(attr, ..)| attr.to_string()).collect()
-        } else {
-            attributes_to_highlight
-                .split(',')
-                .map(ToString::to_string)
-                .collect()
-        };
-
+        let attributes_to_highlight = highlight_list(&schema, &attributes_to_highlight);
         search_builder.attributes_to_highlight(attributes_to_highlight);
     }
 
@@ -99,6 +140,12 @@ pub async fn search_with_url_query(ctx: Context<Data>) -> SResult<Response> {
         search_builder.filters(filters);
     }
 
+    if let Some(lang) = query.lang {
+        let language = Language::from_code(&lang)
+            .ok_or_else(|| ResponseError::bad_request(format!("unknown language {}", lang)))?;
+        search_builder.language(language);
+    }
+
     if let Some(timeout_ms) = query.timeout_ms {
         search_builder.timeout(Duration::from_millis(timeout_ms));
     }
@@ -115,9 +162,113 @@ pub async fn search_with_url_query(ctx: Context<Data>) -> SResult<Response> {
         Err(others) => return Err(ResponseError::bad_request(others)),
     };
 
+    if let Some((search_cache, update_id, request)) = cache_entry {
+        if response.cacheable {
+            search_cache.insert(&index_uid, update_id, request, response.clone());
+        }
+    }
+
     Ok(tide::response::json(response))
 }
 
+fn crop_list(schema: &Schema, attributes: &str, crop_length: usize) -> HashMap<String, usize> {
+    if attributes == "*" {
+        schema
+            .iter()
+            .map(|(attr, ..)| (attr.to_string(), crop_length))
+            .collect()
+    } else {
+        attributes
+            .split(',')
+            .map(|r| (r.to_string(), crop_length))
+            .collect()
+    }
+}
+
+fn highlight_list(schema: &Schema, attributes: &str) -> HashSet<String> {
+    if attributes == "*" {
+        schema.iter().map(|(attr, ..)| attr.to_string()).collect()
+    } else {
+        attributes.split(',').map(ToString::to_string).collect()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct DocumentSearchQuery {
+    q: String,
+    attributes_to_retrieve: Option<String>,
+    attributes_to_search_in: Option<String>,
+    attributes_to_crop: Option<String>,
+    crop_length: Option<usize>,
+    attributes_to_highlight: Option<String>,
+    matches: Option<bool>,
+    lang: Option<String>,
+}
+
+pub async fn search_in_document(ctx: Context<Data>) -> SResult<Response> {
+    ctx.is_allowed(DocumentsRead)?;
+
+    let index = ctx.index()?;
+    let identifier = ctx.identifier()?;
+    let document_id = meilisearch_core::serde::compute_document_id(identifier.clone());
+
+    let db = &ctx.state().db;
+    let reader = db.main_read_txn().map_err(ResponseError::internal)?;
+
+    let schema = index
+        .main
+        .schema(&reader)
+        .map_err(ResponseError::internal)?
+        .ok_or(ResponseError::open_index("No Schema found"))?;
+
+    let query: DocumentSearchQuery = ctx
+        .url_query()
+        .map_err(|_| ResponseError::bad_request("invalid query parameter"))?;
+
+    let mut search_builder = index.new_search(query.q);
+
+    if let Some(attributes_to_retrieve) = query.attributes_to_retrieve {
+        for attr in attributes_to_retrieve.split(',') {
+            search_builder.add_retrievable_field(attr.to_string());
+        }
+    }
+    if let Some(attributes_to_search_in) = query.attributes_to_search_in {
+        for attr in attributes_to_search_in.split(',') {
+            search_builder.add_attribute_to_search_in(attr.to_string());
+        }
+    }
+    if let Some(attributes_to_crop) = query.attributes_to_crop {
+        let crop_length = query.crop_length.unwrap_or(200);
+        let attributes_to_crop = crop_list(&schema, &attributes_to_crop, crop_length);
+        search_builder.attributes_to_crop(attributes_to_crop);
+    }
+
+    // the terms are highlighted in every attribute unless specified otherwise
+    let attributes_to_highlight = match &query.attributes_to_highlight {
+        Some(attributes) => highlight_list(&schema, attributes),
+        None => highlight_list(&schema, "*"),
+    };
+    search_builder.attributes_to_highlight(attributes_to_highlight);
+
+    if let Some(lang) = query.lang {
+        let language = Language::from_code(&lang)
+            .ok_or_else(|| ResponseError::bad_request(format!("unknown language {}", lang)))?;
+        search_builder.language(language);
+    }
+
+    if query.matches.unwrap_or(false) {
+        search_builder.get_matches();
+    }
+
+    match search_builder.search_in_document(&reader, document_id) {
+        Ok(hit) => Ok(tide::response::json(hit)),
+        Err(Error::DocumentNotFound(_)) => Err(ResponseError::document_not_found(identifier)),
+        Err(Error::Internal(message)) => Err(ResponseError::Internal(message)),
+        Err(others) => Err(ResponseError::bad_request(others)),
+    }
+}
+
 #[derive(Clone, Deserialize)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 struct SearchMultiBody {
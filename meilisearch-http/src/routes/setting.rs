@@ -1,34 +1,135 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
+use chrono::Utc;
 use http::StatusCode;
+use meilisearch_core::criterion::{Decay, DecayFunction, Formula, BOOST_ATTRIBUTE};
+use meilisearch_core::{stop_words_preset, Index, Language, MResult, MainT, RankedMap};
 use serde::{Deserialize, Serialize};
 use tide::response::IntoResponse;
 use tide::{Context, Response};
 
 use crate::error::{ResponseError, SResult};
+use crate::helpers::meilisearch::custom_ranking;
 use crate::helpers::tide::ContextExt;
 use crate::models::token::ACL::*;
 use crate::routes::document::IndexUpdateResponse;
 use crate::Data;
 
-#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct SettingBody {
     pub ranking_order: Option<RankingOrder>,
     pub distinct_field: Option<DistinctField>,
     pub ranking_rules: Option<RankingRules>,
+    pub stop_words_preset: Option<StopWordsPreset>,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum RankingOrdering {
     Asc,
     Dsc,
+    Decay(DecayRule),
+    /// An arithmetic expression over the ranked attributes,
+    /// e.g. `popularity * 0.7 + log(reviews)`, higher is better.
+    Formula(String),
+}
+
+/// Ranks the documents by the distance between the date of an
+/// attribute and the origin, by default the time of the search.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct DecayRule {
+    pub function: DecayFunction,
+    /// The distance in seconds at which the score is equal to `decay`.
+    pub scale: u64,
+    pub offset: Option<u64>,
+    pub decay: Option<f64>,
+    pub origin: Option<i64>,
+}
+
+impl DecayRule {
+    pub fn to_decay(self) -> Decay {
+        Decay {
+            function: self.function,
+            origin: self.origin.unwrap_or_else(|| Utc::now().timestamp()),
+            scale: self.scale,
+            offset: self.offset.unwrap_or(0),
+            decay: self.decay.unwrap_or(0.5),
+        }
+    }
+}
+
+/// The settings as they were stored by the previous versions,
+/// before the stop words preset was part of them.
+#[derive(Deserialize)]
+struct SettingBodyV0 {
+    ranking_order: Option<RankingOrder>,
+    distinct_field: Option<DistinctField>,
+    ranking_rules: Option<RankingRules>,
+}
+
+impl SettingBody {
+    /// Decodes the settings stored in the customs of an index,
+    /// the ones stored by the previous versions are converted.
+    pub fn from_customs(bytes: &[u8]) -> bincode::Result<SettingBody> {
+        let error = match bincode::deserialize(bytes) {
+            Ok(settings) => return Ok(settings),
+            Err(error) => error,
+        };
+
+        match bincode::deserialize::<SettingBodyV0>(bytes) {
+            Ok(settings) => Ok(SettingBody {
+                ranking_order: settings.ranking_order,
+                distinct_field: settings.distinct_field,
+                ranking_rules: settings.ranking_rules,
+                stop_words_preset: None,
+            }),
+            Err(_) => Err(error),
+        }
+    }
+}
+
+/// Converts the settings stored by the previous versions to the current format.
+pub fn upgrade_customs(writer: &mut heed::RwTxn<MainT>, index: &Index) -> MResult<()> {
+    let bytes = match index.main.customs(writer)? {
+        Some(bytes) => bytes,
+        None => return Ok(()),
+    };
+
+    if bincode::deserialize::<SettingBody>(bytes).is_err() {
+        if let Ok(settings) = SettingBody::from_customs(bytes) {
+            let bytes = bincode::serialize(&settings)?;
+            index.main.put_customs(writer, &bytes)?;
+        }
+    }
+
+    Ok(())
 }
 
 pub type RankingOrder = Vec<String>;
 pub type DistinctField = String;
 pub type RankingRules = HashMap<String, RankingOrdering>;
+/// The codes of the languages whose built-in stop words are used, e.g. `["en", "fr"]`,
+/// only the English (`en`) and French (`fr`) lists are available.
+pub type StopWordsPreset = Vec<String>;
+
+fn preset_stop_words(preset: &[String]) -> SResult<BTreeSet<&'static str>> {
+    let mut stop_words = BTreeSet::new();
+    for code in preset {
+        match Language::from_code(code) {
+            Some(language) => stop_words.extend(stop_words_preset(language)),
+            None => {
+                let message = format!(
+                    "there is no stop words preset for {:?}, only \"en\" and \"fr\" are available",
+                    code
+                );
+                return Err(ResponseError::bad_request(message));
+            }
+        }
+    }
+    Ok(stop_words)
+}
 
 pub async fn get(ctx: Context<Data>) -> SResult<Response> {
     ctx.is_allowed(SettingsRead)?;
@@ -37,8 +138,12 @@ pub async fn get(ctx: Context<Data>) -> SResult<Response> {
     let db = &ctx.state().db;
     let reader = db.main_read_txn().map_err(ResponseError::internal)?;
 
-    let settings = match index.main.customs(&reader).unwrap() {
-        Some(bytes) => bincode::deserialize(bytes).unwrap(),
+    let customs = index
+        .main
+        .customs(&reader)
+        .map_err(ResponseError::internal)?;
+    let settings = match customs {
+        Some(bytes) => SettingBody::from_customs(bytes).map_err(ResponseError::internal)?,
         None => SettingBody::default(),
     };
 
@@ -56,12 +161,28 @@ pub async fn update(mut ctx: Context<Data>) -> SResult<Response> {
     let reader = db.main_write_txn().map_err(ResponseError::internal)?;
     let mut writer = db.update_write_txn().map_err(ResponseError::internal)?;
 
-    let mut current_settings = match index.main.customs(&reader).unwrap() {
-        Some(bytes) => bincode::deserialize(bytes).unwrap(),
+    let customs = index
+        .main
+        .customs(&reader)
+        .map_err(ResponseError::internal)?;
+    let mut current_settings = match customs {
+        Some(bytes) => SettingBody::from_customs(bytes).map_err(ResponseError::internal)?,
         None => SettingBody::default(),
     };
 
     if let Some(ranking_order) = settings.ranking_order {
+        // the boost is checked now to not fail at search time
+        let schema = index
+            .main
+            .schema(&reader)
+            .map_err(ResponseError::internal)?;
+        if let Some(schema) = schema {
+            if ranking_order.iter().any(|rule| rule == BOOST_ATTRIBUTE) {
+                let ranked_map = RankedMap::default();
+                Formula::boost(&ranked_map, &schema).map_err(ResponseError::bad_request)?;
+            }
+        }
+
         current_settings.ranking_order = Some(ranking_order);
     }
 
@@ -70,10 +191,55 @@ pub async fn update(mut ctx: Context<Data>) -> SResult<Response> {
     }
 
     if let Some(ranking_rules) = settings.ranking_rules {
+        // the ranking rules are checked now to not fail at search time
+        let schema = index
+            .main
+            .schema(&reader)
+            .map_err(ResponseError::internal)?;
+        if let Some(schema) = schema {
+            let ranked_map = RankedMap::default();
+            for (attribute, order) in &ranking_rules {
+                custom_ranking(&ranked_map, &schema, attribute, order)
+                    .map_err(ResponseError::bad_request)?;
+            }
+        }
+
         current_settings.ranking_rules = Some(ranking_rules);
     }
 
-    let bytes = bincode::serialize(&current_settings).unwrap();
+    if let Some(preset) = settings.stop_words_preset {
+        let stop_words = preset_stop_words(&preset)?;
+
+        // the stop words of the languages removed from the preset are deleted,
+        // even if they have been added by other means since then
+        let previous_preset = current_settings.stop_words_preset.unwrap_or_default();
+        let previous_stop_words = preset_stop_words(&previous_preset)?;
+        let removed: Vec<_> = previous_stop_words.difference(&stop_words).collect();
+
+        if !removed.is_empty() {
+            let mut stop_words_deletion = index.stop_words_deletion();
+            for stop_word in removed {
+                stop_words_deletion.delete_stop_word(stop_word);
+            }
+            stop_words_deletion
+                .finalize(&mut writer)
+                .map_err(ResponseError::internal)?;
+        }
+
+        if !stop_words.is_empty() {
+            let mut stop_words_addition = index.stop_words_addition();
+            for stop_word in stop_words {
+                stop_words_addition.add_stop_word(stop_word);
+            }
+            stop_words_addition
+                .finalize(&mut writer)
+                .map_err(ResponseError::internal)?;
+        }
+
+        current_settings.stop_words_preset = Some(preset);
+    }
+
+    let bytes = bincode::serialize(&current_settings).map_err(ResponseError::internal)?;
 
     let update_id = index
         .customs_update(&mut writer, bytes)
@@ -86,3 +252,36 @@ pub async fn update(mut ctx: Context<Data>) -> SResult<Response> {
         .with_status(StatusCode::ACCEPTED)
         .into_response())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn previous_customs() {
+        // the settings stored by the previous versions had no stop words preset
+        let mut ranking_rules = RankingRules::new();
+        ranking_rules.insert("release_date".to_string(), RankingOrdering::Dsc);
+        let ranking_order = vec!["_sum_of_typos".to_string(), "release_date".to_string()];
+        let distinct_field = "id".to_string();
+        let previous = (
+            Some(ranking_order),
+            Some(distinct_field),
+            Some(ranking_rules),
+        );
+        let bytes = bincode::serialize(&previous).unwrap();
+
+        let settings = SettingBody::from_customs(&bytes).unwrap();
+        assert_eq!(settings.ranking_order, previous.0);
+        assert_eq!(settings.distinct_field, previous.1);
+        assert_eq!(settings.ranking_rules, previous.2);
+        assert_eq!(settings.stop_words_preset, None);
+
+        let settings = SettingBody {
+            stop_words_preset: Some(vec!["en".to_string()]),
+            ..settings
+        };
+        let bytes = bincode::serialize(&settings).unwrap();
+        assert_eq!(SettingBody::from_customs(&bytes).unwrap(), settings);
+    }
+}
@@ -7,22 +7,93 @@ use tide::{Context, Response};
 
 use crate::error::{ResponseError, SResult};
 use crate::helpers::tide::ContextExt;
+use crate::models::import::parse_synonyms_file;
 use crate::models::token::ACL::*;
 use crate::routes::document::IndexUpdateResponse;
 use crate::Data;
 
-#[derive(Clone, Serialize, Deserialize)]
+/// An alternative of a synonym, the documents matching by an alternative
+/// of weight lower than 1 are ranked after the ones matching directly.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
+pub enum Alternative {
+    Word(String),
+    Weighted { word: String, weight: f64 },
+}
+
+impl Alternative {
+    fn new(word: String, weight: f64) -> Alternative {
+        if weight >= 1.0 {
+            Alternative::Word(word)
+        } else {
+            Alternative::Weighted { word, weight }
+        }
+    }
+
+    fn word(&self) -> &str {
+        match self {
+            Alternative::Word(word) => word,
+            Alternative::Weighted { word, .. } => word,
+        }
+    }
+
+    fn weight(&self) -> f64 {
+        match self {
+            Alternative::Word(_) => 1.0,
+            Alternative::Weighted { weight, .. } => *weight,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
 pub enum Synonym {
-    OneWay(SynonymOneWay),
-    MultiWay { synonyms: Vec<String> },
+    /// The input is searched as its synonyms but not the other way around.
+    OneWay {
+        input: String,
+        synonyms: Vec<Alternative>,
+    },
+    /// Every synonym is searched as all the others.
+    MultiWay { synonyms: Vec<Alternative> },
+}
+
+impl Synonym {
+    /// The words to add to the synonyms store, associated with their alternatives.
+    fn into_alternatives(self) -> Vec<(String, Vec<Alternative>)> {
+        match self {
+            Synonym::OneWay { input, synonyms } => vec![(input, synonyms)],
+            Synonym::MultiWay { synonyms } => {
+                if synonyms.len() < 2 {
+                    return Vec::new();
+                }
+
+                let mut alternatives = Vec::with_capacity(synonyms.len());
+                for (i, synonym) in synonyms.iter().enumerate() {
+                    let mut others = synonyms.clone();
+                    others.remove(i);
+                    alternatives.push((synonym.word().to_owned(), others));
+                }
+                alternatives
+            }
+        }
+    }
+}
+
+fn check_weights(alternatives: &[Alternative]) -> SResult<()> {
+    for alternative in alternatives {
+        let weight = alternative.weight();
+        if !(0.0..=1.0).contains(&weight) {
+            return Err(ResponseError::bad_request(format!(
+                "the weight of the synonym {:?} must be between 0 and 1",
+                alternative.word()
+            )));
+        }
+    }
+    Ok(())
 }
 
-#[derive(Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
-pub struct SynonymOneWay {
-    pub input: String,
-    pub synonyms: Vec<String>,
+fn weighted_alternatives(alternatives: &[Alternative]) -> impl Iterator<Item = (&str, f64)> {
+    alternatives.iter().map(|a| (a.word(), a.weight()))
 }
 
 pub type Synonyms = Vec<Synonym>;
@@ -47,13 +118,13 @@ pub async fn list(ctx: Context<Data>) -> SResult<Response> {
     let index_synonyms = &index.synonyms;
 
     for synonym in synonyms_list {
-        let alternative_list = index_synonyms
-            .synonyms(&reader, synonym.as_bytes())
-            .unwrap()
-            .unwrap()
-            .stream()
-            .into_strs()
-            .unwrap();
+        let alternative_list: Vec<_> = index_synonyms
+            .weighted_synonyms(&reader, synonym.as_bytes())
+            .map_err(ResponseError::internal)?
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(word, weight)| Alternative::new(word, weight))
+            .collect();
         response.insert(synonym, alternative_list);
     }
 
@@ -68,14 +139,14 @@ pub async fn get(ctx: Context<Data>) -> SResult<Response> {
     let db = &ctx.state().db;
     let reader = db.main_read_txn().map_err(ResponseError::internal)?;
 
-    let synonym_list = index
+    let synonym_list: Vec<_> = index
         .synonyms
-        .synonyms(&reader, synonym.as_bytes())
-        .unwrap()
-        .unwrap()
-        .stream()
-        .into_strs()
-        .unwrap();
+        .weighted_synonyms(&reader, synonym.as_bytes())
+        .map_err(ResponseError::internal)?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(word, weight)| Alternative::new(word, weight))
+        .collect();
 
     Ok(tide::response::json(synonym_list))
 }
@@ -91,20 +162,9 @@ pub async fn create(mut ctx: Context<Data>) -> SResult<Response> {
     let mut writer = db.update_write_txn().map_err(ResponseError::internal)?;
 
     let mut synonyms_addition = index.synonyms_addition();
-
-    match data.clone() {
-        Synonym::OneWay(content) => {
-            synonyms_addition.add_synonym(content.input, content.synonyms.into_iter())
-        }
-        Synonym::MultiWay { mut synonyms } => {
-            if synonyms.len() > 1 {
-                for _ in 0..synonyms.len() {
-                    let (first, elems) = synonyms.split_first().unwrap();
-                    synonyms_addition.add_synonym(first, elems.iter());
-                    synonyms.rotate_left(1);
-                }
-            }
-        }
+    for (synonym, alternatives) in data.into_alternatives() {
+        check_weights(&alternatives)?;
+        synonyms_addition.add_weighted_synonym(synonym, weighted_alternatives(&alternatives));
     }
 
     let update_id = synonyms_addition
@@ -123,13 +183,14 @@ pub async fn update(mut ctx: Context<Data>) -> SResult<Response> {
     ctx.is_allowed(SettingsWrite)?;
     let synonym = ctx.url_param("synonym")?;
     let index = ctx.index()?;
-    let data: Vec<String> = ctx.body_json().await.map_err(ResponseError::bad_request)?;
+    let data: Vec<Alternative> = ctx.body_json().await.map_err(ResponseError::bad_request)?;
+    check_weights(&data)?;
 
     let db = &ctx.state().db;
     let mut writer = db.update_write_txn().map_err(ResponseError::internal)?;
 
     let mut synonyms_addition = index.synonyms_addition();
-    synonyms_addition.add_synonym(synonym.clone(), data.clone().into_iter());
+    synonyms_addition.add_weighted_synonym(synonym, weighted_alternatives(&data));
     let update_id = synonyms_addition
         .finalize(&mut writer)
         .map_err(ResponseError::internal)?;
@@ -176,19 +237,38 @@ pub async fn batch_write(mut ctx: Context<Data>) -> SResult<Response> {
 
     let mut synonyms_addition = index.synonyms_addition();
     for raw in data {
-        match raw {
-            Synonym::OneWay(content) => {
-                synonyms_addition.add_synonym(content.input, content.synonyms.into_iter())
-            }
-            Synonym::MultiWay { mut synonyms } => {
-                if synonyms.len() > 1 {
-                    for _ in 0..synonyms.len() {
-                        let (first, elems) = synonyms.split_first().unwrap();
-                        synonyms_addition.add_synonym(first, elems.iter());
-                        synonyms.rotate_left(1);
-                    }
-                }
-            }
+        for (synonym, alternatives) in raw.into_alternatives() {
+            check_weights(&alternatives)?;
+            synonyms_addition.add_weighted_synonym(synonym, weighted_alternatives(&alternatives));
+        }
+    }
+    let update_id = synonyms_addition
+        .finalize(&mut writer)
+        .map_err(ResponseError::internal)?;
+
+    writer.commit().map_err(ResponseError::internal)?;
+
+    let response_body = IndexUpdateResponse { update_id };
+    Ok(tide::response::json(response_body)
+        .with_status(StatusCode::ACCEPTED)
+        .into_response())
+}
+
+pub async fn import(mut ctx: Context<Data>) -> SResult<Response> {
+    ctx.is_allowed(SettingsWrite)?;
+
+    let text = ctx.body_string().await.map_err(ResponseError::bad_request)?;
+    let data = parse_synonyms_file(&text).map_err(ResponseError::bad_request)?;
+
+    let index = ctx.index()?;
+
+    let db = &ctx.state().db;
+    let mut writer = db.update_write_txn().map_err(ResponseError::internal)?;
+
+    let mut synonyms_addition = index.synonyms_addition();
+    for raw in data {
+        for (synonym, alternatives) in raw.into_alternatives() {
+            synonyms_addition.add_weighted_synonym(synonym, weighted_alternatives(&alternatives));
         }
     }
     let update_id = synonyms_addition
@@ -0,0 +1,171 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, BinaryHeap, HashMap};
+use std::str;
+
+use fst::{IntoStreamer, Streamer};
+use meilisearch_schema::SchemaAttr;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tide::querystring::ContextExt as QSContextExt;
+use tide::{Context, Response};
+
+use crate::error::{ResponseError, SResult};
+use crate::helpers::tide::ContextExt;
+use crate::models::token::ACL::*;
+use crate::Data;
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct WordsQuery {
+    prefix: Option<String>,
+    regex: Option<String>,
+    /// Returns the most frequent words instead of browsing them in order.
+    ///
+    /// The documents frequency of every word matching the prefix and the regex is
+    /// read to find them, it costs one postings list lookup by word of the index,
+    /// a prefix should be given to restrict the browsed words on big indexes.
+    top: Option<usize>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    /// Breaks the documents frequency of each word down by attribute.
+    attributes: Option<bool>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WordStatistics {
+    word: String,
+    documents_frequency: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    attributes: Option<HashMap<String, u64>>,
+}
+
+pub async fn list(ctx: Context<Data>) -> SResult<Response> {
+    ctx.is_allowed(Admin)?;
+    let index = ctx.index()?;
+
+    let query: WordsQuery = ctx
+        .url_query()
+        .map_err(|_| ResponseError::bad_request("invalid query parameter"))?;
+
+    let regex = match &query.regex {
+        Some(regex) => {
+            let regex = Regex::new(regex).map_err(|e| ResponseError::bad_parameter("regex", e))?;
+            Some(regex)
+        }
+        None => None,
+    };
+
+    let offset = query.offset.unwrap_or(0);
+    let limit = query.limit.unwrap_or(20);
+    let prefix = query.prefix.unwrap_or_default();
+
+    let db = &ctx.state().db;
+    let reader = db.main_read_txn().map_err(ResponseError::internal)?;
+
+    let schema = index
+        .main
+        .schema(&reader)
+        .map_err(ResponseError::internal)?
+        .ok_or(ResponseError::open_index("No Schema found"))?;
+
+    let words = index
+        .main
+        .words_fst(&reader)
+        .map_err(ResponseError::internal)?
+        .unwrap_or_default();
+
+    // the words are stored in lexicographic order, the ones
+    // starting with the prefix are all found after it
+    let mut selected = Vec::new();
+    let mut top_words = BinaryHeap::new();
+    let mut skipped = 0;
+    let mut stream = words.range().ge(&prefix).into_stream();
+    while let Some(word) = stream.next() {
+        if !word.starts_with(prefix.as_bytes()) {
+            break;
+        }
+
+        let word = match str::from_utf8(word) {
+            Ok(word) => word,
+            Err(_) => continue,
+        };
+
+        if let Some(regex) = &regex {
+            if !regex.is_match(word) {
+                continue;
+            }
+        }
+
+        match query.top {
+            Some(top) => {
+                let frequency = index
+                    .postings_lists
+                    .postings_docids(&reader, word.as_bytes())
+                    .map_err(ResponseError::internal)?
+                    .map_or(0, |docids| docids.len());
+
+                // only the most frequent words seen so far are kept
+                top_words.push(Reverse((frequency, Reverse(word.to_owned()))));
+                if top_words.len() > top {
+                    top_words.pop();
+                }
+            }
+            None if skipped < offset => skipped += 1,
+            None if selected.len() < limit => selected.push((0, word.to_owned())),
+            None => break,
+        }
+    }
+
+    if query.top.is_some() {
+        // the most frequent words first, then in lexicographic order
+        let top_words = top_words.into_sorted_vec();
+        selected = top_words
+            .into_iter()
+            .map(|Reverse((frequency, Reverse(word)))| (frequency, word))
+            .collect();
+    }
+
+    let with_attributes = query.attributes.unwrap_or(false);
+    let mut response = Vec::with_capacity(selected.len());
+    for (_, word) in selected {
+        let documents_frequency = index
+            .postings_lists
+            .postings_docids(&reader, word.as_bytes())
+            .map_err(ResponseError::internal)?
+            .map_or(0, |docids| docids.len());
+
+        // the positions are only decompressed when the breakdown is asked
+        let attributes = if with_attributes {
+            let postings = index
+                .postings_lists
+                .postings_list(&reader, word.as_bytes())
+                .map_err(ResponseError::internal)?;
+
+            let mut attributes = HashMap::new();
+            if let Some(postings) = postings {
+                let pairs: BTreeSet<_> = postings
+                    .matches
+                    .iter()
+                    .map(|di| (di.attribute, di.document_id))
+                    .collect();
+
+                for (attribute, _) in pairs {
+                    let name = schema.attribute_name(SchemaAttr::new(attribute));
+                    *attributes.entry(name.to_owned()).or_insert(0) += 1;
+                }
+            }
+            Some(attributes)
+        } else {
+            None
+        };
+
+        response.push(WordStatistics {
+            word,
+            documents_frequency,
+            attributes,
+        });
+    }
+
+    Ok(tide::response::json(response))
+}
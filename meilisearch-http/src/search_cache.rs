@@ -0,0 +1,159 @@
+use std::sync::Mutex;
+
+use lru::LruCache;
+
+use crate::helpers::meilisearch::SearchResult;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    index_uid: String,
+    update_id: Option<u64>,
+    request: String,
+}
+
+/// A least recently used cache of search responses.
+///
+/// Responses are keyed on the normalized request and on the id of the last update
+/// processed by the index, an entry can therefore never be returned once the index
+/// has changed, the entries of an index are also dropped as soon as it is updated.
+pub struct SearchCache {
+    cache: Mutex<LruCache<CacheKey, SearchResult>>,
+}
+
+impl SearchCache {
+    /// Returns `None` if the capacity is zero, the cache is disabled.
+    pub fn new(capacity: usize) -> Option<SearchCache> {
+        if capacity == 0 {
+            return None;
+        }
+
+        Some(SearchCache {
+            cache: Mutex::new(LruCache::new(capacity)),
+        })
+    }
+
+    pub fn get(
+        &self,
+        index_uid: &str,
+        update_id: Option<u64>,
+        request: &str,
+    ) -> Option<SearchResult> {
+        let key = CacheKey {
+            index_uid: index_uid.to_owned(),
+            update_id,
+            request: request.to_owned(),
+        };
+
+        let mut cache = self.cache.lock().unwrap();
+        cache.get(&key).cloned()
+    }
+
+    pub fn insert(
+        &self,
+        index_uid: &str,
+        update_id: Option<u64>,
+        request: String,
+        result: SearchResult,
+    ) {
+        let key = CacheKey {
+            index_uid: index_uid.to_owned(),
+            update_id,
+            request,
+        };
+
+        let mut cache = self.cache.lock().unwrap();
+        cache.put(key, result);
+    }
+
+    /// Drops all the entries related to the given index.
+    pub fn invalidate_index(&self, index_uid: &str) {
+        let mut cache = self.cache.lock().unwrap();
+
+        let keys: Vec<_> = cache
+            .iter()
+            .filter(|(key, _)| key.index_uid == index_uid)
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        for key in keys {
+            cache.pop(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(query: &str) -> SearchResult {
+        SearchResult {
+            hits: Vec::new(),
+            offset: 0,
+            limit: 20,
+            processing_time_ms: 0,
+            query: query.to_owned(),
+            cacheable: true,
+        }
+    }
+
+    #[test]
+    fn cache_hit() {
+        let cache = SearchCache::new(10).unwrap();
+        assert!(cache.get("movies", Some(1), "q=hello").is_none());
+
+        cache.insert("movies", Some(1), "q=hello".to_owned(), result("hello"));
+        let hit = cache.get("movies", Some(1), "q=hello").unwrap();
+        assert_eq!(hit.query, "hello");
+
+        assert!(cache.get("movies", Some(1), "q=world").is_none());
+        assert!(cache.get("books", Some(1), "q=hello").is_none());
+    }
+
+    #[test]
+    fn evict_least_recently_used() {
+        let cache = SearchCache::new(2).unwrap();
+        cache.insert("movies", Some(1), "q=a".to_owned(), result("a"));
+        cache.insert("movies", Some(1), "q=b".to_owned(), result("b"));
+
+        // "a" is now more recently used than "b"
+        assert!(cache.get("movies", Some(1), "q=a").is_some());
+
+        cache.insert("movies", Some(1), "q=c".to_owned(), result("c"));
+        assert!(cache.get("movies", Some(1), "q=a").is_some());
+        assert!(cache.get("movies", Some(1), "q=b").is_none());
+        assert!(cache.get("movies", Some(1), "q=c").is_some());
+    }
+
+    #[test]
+    fn miss_on_new_update_id() {
+        let cache = SearchCache::new(10).unwrap();
+        cache.insert("movies", None, "q=hello".to_owned(), result("hello"));
+        assert!(cache.get("movies", None, "q=hello").is_some());
+
+        // the index has processed an update since the result was cached
+        assert!(cache.get("movies", Some(0), "q=hello").is_none());
+
+        cache.insert("movies", Some(0), "q=hello".to_owned(), result("hello"));
+        assert!(cache.get("movies", Some(0), "q=hello").is_some());
+        assert!(cache.get("movies", Some(1), "q=hello").is_none());
+    }
+
+    #[test]
+    fn invalidate_index_entries() {
+        let cache = SearchCache::new(10).unwrap();
+        cache.insert("movies", Some(1), "q=hello".to_owned(), result("hello"));
+        cache.insert("books", Some(1), "q=hello".to_owned(), result("hello"));
+
+        assert!(cache.get("movies", Some(1), "q=hello").is_some());
+        assert!(cache.get("movies", Some(2), "q=hello").is_none());
+
+        cache.invalidate_index("movies");
+        assert!(cache.get("movies", Some(1), "q=hello").is_none());
+        assert!(cache.get("books", Some(1), "q=hello").is_some());
+    }
+
+    #[test]
+    fn zero_capacity_disables_the_cache() {
+        assert!(SearchCache::new(0).is_none());
+    }
+}
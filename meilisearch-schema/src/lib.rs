@@ -10,17 +10,68 @@ pub const DISPLAYED: SchemaProps = SchemaProps {
     displayed: true,
     indexed: false,
     ranked: false,
+    infix: false,
+    phonetic: false,
+    language: None,
 };
 pub const INDEXED: SchemaProps = SchemaProps {
     displayed: false,
     indexed: true,
     ranked: false,
+    infix: false,
+    phonetic: false,
+    language: None,
 };
 pub const RANKED: SchemaProps = SchemaProps {
     displayed: false,
     indexed: false,
     ranked: true,
+    infix: false,
+    phonetic: false,
+    language: None,
 };
+pub const INFIX: SchemaProps = SchemaProps {
+    displayed: false,
+    indexed: false,
+    ranked: false,
+    infix: true,
+    phonetic: false,
+    language: None,
+};
+pub const PHONETIC: SchemaProps = SchemaProps {
+    displayed: false,
+    indexed: false,
+    ranked: false,
+    infix: false,
+    phonetic: true,
+    language: None,
+};
+
+/// The languages for which the words can be specifically analyzed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    English,
+    French,
+}
+
+impl Language {
+    /// Returns the language corresponding to an ISO 639-1 code (e.g. `en`).
+    pub fn from_code(code: &str) -> Option<Language> {
+        match code {
+            "en" => Some(Language::English),
+            "fr" => Some(Language::French),
+            _ => None,
+        }
+    }
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::French => "fr",
+        }
+    }
+}
 
 #[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SchemaProps {
@@ -32,6 +83,56 @@ pub struct SchemaProps {
 
     #[serde(default)]
     pub ranked: bool,
+
+    /// The words of this attribute can also be found by
+    /// searching for a part of them, e.g. "phone" in "smartphone".
+    #[serde(default)]
+    pub infix: bool,
+
+    /// The words of this attribute can also be found by searching for
+    /// a word that sounds alike, e.g. "Kathryn" for "Catherine".
+    #[serde(default)]
+    pub phonetic: bool,
+
+    /// The language of the texts of this attribute, its words
+    /// are indexed using the analyzer of this language.
+    #[serde(default)]
+    pub language: Option<Language>,
+}
+
+/// The properties of an attribute as stored by the previous versions,
+/// before the infix, phonetic and language options were introduced.
+#[derive(Deserialize)]
+struct SchemaPropsV0 {
+    displayed: bool,
+    indexed: bool,
+    ranked: bool,
+}
+
+/// A schema as stored by the previous versions, its properties do not have the
+/// binary layout of the current ones and must be converted to be read again.
+#[derive(Deserialize)]
+pub struct SchemaV0 {
+    identifier: String,
+    attributes: IndexMap<String, SchemaPropsV0>,
+}
+
+impl From<SchemaV0> for Schema {
+    fn from(schema: SchemaV0) -> Schema {
+        let mut builder = SchemaBuilder::with_identifier(schema.identifier);
+        for (name, props) in schema.attributes {
+            let props = SchemaProps {
+                displayed: props.displayed,
+                indexed: props.indexed,
+                ranked: props.ranked,
+                infix: false,
+                phonetic: false,
+                language: None,
+            };
+            builder.new_attribute(name, props);
+        }
+        builder.build()
+    }
 }
 
 impl SchemaProps {
@@ -46,6 +147,18 @@ impl SchemaProps {
     pub fn is_ranked(self) -> bool {
         self.ranked
     }
+
+    pub fn is_infix(self) -> bool {
+        self.infix
+    }
+
+    pub fn is_phonetic(self) -> bool {
+        self.phonetic
+    }
+
+    pub fn language(self) -> Option<Language> {
+        self.language
+    }
 }
 
 impl BitOr for SchemaProps {
@@ -56,6 +169,9 @@ impl BitOr for SchemaProps {
             displayed: self.displayed | other.displayed,
             indexed: self.indexed | other.indexed,
             ranked: self.ranked | other.ranked,
+            infix: self.infix | other.infix,
+            phonetic: self.phonetic | other.phonetic,
+            language: self.language.or(other.language),
         }
     }
 }
@@ -72,6 +188,12 @@ impl fmt::Debug for SchemaProps {
         #[derive(Debug)]
         struct RANKED;
 
+        #[derive(Debug)]
+        struct INFIX;
+
+        #[derive(Debug)]
+        struct PHONETIC;
+
         let mut debug_set = f.debug_set();
 
         if self.displayed {
@@ -86,6 +208,18 @@ impl fmt::Debug for SchemaProps {
             debug_set.entry(&RANKED);
         }
 
+        if self.infix {
+            debug_set.entry(&INFIX);
+        }
+
+        if self.phonetic {
+            debug_set.entry(&PHONETIC);
+        }
+
+        if let Some(language) = self.language {
+            debug_set.entry(&language);
+        }
+
         debug_set.finish()
     }
 }
@@ -425,6 +559,27 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn deserialize_v0() -> bincode::Result<()> {
+        // the properties were only made of the displayed, indexed and ranked flags
+        let mut attributes = IndexMap::new();
+        attributes.insert("alpha", (true, false, false));
+        attributes.insert("beta", (true, true, false));
+        attributes.insert("gamma", (false, true, true));
+        let buffer = bincode::serialize(&("id", attributes))?;
+
+        let mut builder = SchemaBuilder::with_identifier("id");
+        builder.new_attribute("alpha", DISPLAYED);
+        builder.new_attribute("beta", DISPLAYED | INDEXED);
+        builder.new_attribute("gamma", INDEXED | RANKED);
+        let schema = builder.build();
+
+        let schema_v0: SchemaV0 = bincode::deserialize(&buffer)?;
+        assert_eq!(Schema::from(schema_v0), schema);
+
+        Ok(())
+    }
+
     #[test]
     fn serialize_deserialize_toml() -> Result<(), Box<dyn Error>> {
         let mut builder = SchemaBuilder::with_identifier("id");
@@ -492,6 +647,39 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn attribute_language() -> Result<(), Box<dyn Error>> {
+        let data = r#"
+            {
+                "identifier": "id",
+                "attributes": {
+                    "title_en": {
+                        "indexed": true,
+                        "language": "english"
+                    },
+                    "title_fr": {
+                        "indexed": true,
+                        "language": "french"
+                    },
+                    "year": {
+                        "indexed": true
+                    }
+                }
+            }"#;
+        let schema: Schema = serde_json::from_str(data)?;
+
+        let title_fr = schema.attribute("title_fr").unwrap();
+        assert_eq!(schema.props(title_fr).language(), Some(Language::French));
+
+        let year = schema.attribute("year").unwrap();
+        assert_eq!(schema.props(year).language(), None);
+
+        assert_eq!(Language::from_code("en"), Some(Language::English));
+        assert_eq!(Language::from_code("de"), None);
+
+        Ok(())
+    }
+
     #[test]
     fn debug_output() {
         use std::fmt::Write as _;
@@ -1,8 +1,14 @@
+mod patterns;
+mod segmenter;
+
 use self::SeparatorCategory::*;
 use deunicode::deunicode_char;
 use slice_group_by::StrGroupBy;
+use std::collections::VecDeque;
 use std::iter::Peekable;
 
+pub use self::segmenter::segment;
+
 pub fn is_cjk(c: char) -> bool {
     (c >= '\u{2e80}' && c <= '\u{2eff}')
         || (c >= '\u{2f00}' && c <= '\u{2fdf}')
@@ -38,10 +44,6 @@ impl SeparatorCategory {
     }
 }
 
-fn is_separator(c: char) -> bool {
-    classify_separator(c).is_some()
-}
-
 fn classify_separator(c: char) -> Option<SeparatorCategory> {
     match c {
         c if c.is_whitespace() => Some(Soft), // whitespaces
@@ -60,35 +62,140 @@ enum CharCategory {
     Other,
 }
 
-fn classify_char(c: char) -> CharCategory {
-    if let Some(category) = classify_separator(c) {
-        CharCategory::Separator(category)
-    } else if is_cjk(c) {
-        CharCategory::Cjk
-    } else {
-        CharCategory::Other
-    }
+// fold the number of chars along with the index position
+fn chars_count_index((n, _): (usize, usize), (i, c): (usize, char)) -> (usize, usize) {
+    (n + 1, i + c.len_utf8())
 }
 
-fn is_str_word(s: &str) -> bool {
-    !s.chars().any(is_separator)
+pub fn split_query_string(query: &str) -> impl Iterator<Item = &str> {
+    split_query_string_with_options(query, TokenizerOptions::default())
 }
 
-fn same_group_category(a: char, b: char) -> bool {
-    match (classify_char(a), classify_char(b)) {
-        (CharCategory::Cjk, _) | (_, CharCategory::Cjk) => false,
-        (CharCategory::Separator(_), CharCategory::Separator(_)) => true,
-        (a, b) => a == b,
-    }
+pub fn split_query_string_with_options(
+    query: &str,
+    options: TokenizerOptions,
+) -> impl Iterator<Item = &str> {
+    Tokenizer::with_options(query, options).map(|t| t.word)
 }
 
-// fold the number of chars along with the index position
-fn chars_count_index((n, _): (usize, usize), (i, c): (usize, char)) -> (usize, usize) {
-    (n + 1, i + c.len_utf8())
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TokenizerOptions {
+    /// Splits the runs of CJK characters into the words found in the Chinese
+    /// dictionary instead of considering every character as a word. The dictionary
+    /// is only bundled with the `chinese-dictionary` feature, Japanese is not supported.
+    pub cjk_segmentation: bool,
+    /// Characters that must be considered as soft separators.
+    pub separators: Vec<char>,
+    /// Characters that must no more be considered as separators.
+    pub non_separators: Vec<char>,
+    /// Keeps the emails (e.g. `john.doe@example.com`) as a single word.
+    pub keep_emails: bool,
+    /// Keeps the urls (e.g. `https://example.com/path`) as a single word.
+    pub keep_urls: bool,
+    /// Keeps the version numbers (e.g. `3.2.1`) as a single word.
+    pub keep_versions: bool,
+    /// Words that must not be split (e.g. `c++`, `c#` or `.net`),
+    /// they are matched without regard to the ASCII case.
+    pub keep_together: Vec<String>,
+    /// Also emits the sub words found at the case and letter/digit transitions of a
+    /// word (e.g. `getUserById` or `iphone11pro`), after it and at its position.
+    pub split_sub_words: bool,
 }
 
-pub fn split_query_string(query: &str) -> impl Iterator<Item = &str> {
-    Tokenizer::new(query).map(|t| t.word)
+impl TokenizerOptions {
+    fn classify_separator(&self, c: char) -> Option<SeparatorCategory> {
+        if self.non_separators.contains(&c) {
+            None
+        } else if self.separators.contains(&c) {
+            Some(classify_separator(c).unwrap_or(Soft))
+        } else {
+            classify_separator(c)
+        }
+    }
+
+    fn is_separator(&self, c: char) -> bool {
+        self.classify_separator(c).is_some()
+    }
+
+    fn classify_char(&self, c: char) -> CharCategory {
+        if let Some(category) = self.classify_separator(c) {
+            CharCategory::Separator(category)
+        } else if is_cjk(c) {
+            CharCategory::Cjk
+        } else {
+            CharCategory::Other
+        }
+    }
+
+    fn is_str_word(&self, s: &str) -> bool {
+        !s.chars().any(|c| self.is_separator(c))
+    }
+
+    fn same_group_category(&self, a: char, b: char) -> bool {
+        match (self.classify_char(a), self.classify_char(b)) {
+            (CharCategory::Cjk, CharCategory::Cjk) => self.cjk_segmentation,
+            (CharCategory::Cjk, _) | (_, CharCategory::Cjk) => false,
+            (CharCategory::Separator(_), CharCategory::Separator(_)) => true,
+            (a, b) => a == b,
+        }
+    }
+
+    fn separators_weight(&self, s: &str) -> usize {
+        s.chars()
+            .filter_map(|c| self.classify_separator(c))
+            .fold(Soft, |a, x| a.merge(x))
+            .to_usize()
+    }
+
+    /// Returns the length of the longest word to keep together
+    /// found at the start of the given text, if any.
+    fn keep_together_len(&self, text: &str) -> Option<usize> {
+        let words = self.keep_together.iter().filter_map(|word| {
+            let prefix = text.get(..word.len())?;
+            if word.is_empty() || !prefix.eq_ignore_ascii_case(word) {
+                return None;
+            }
+            Some(word.len())
+        });
+
+        let emails = Some(text).filter(|_| self.keep_emails).and_then(patterns::email_len);
+        let urls = Some(text).filter(|_| self.keep_urls).and_then(patterns::url_len);
+        let versions = Some(text).filter(|_| self.keep_versions).and_then(patterns::version_len);
+
+        words
+            .chain(emails)
+            .chain(urls)
+            .chain(versions)
+            // the word must not be followed by other letters
+            .filter(|&len| text[len..].chars().next().map_or(true, |c| !c.is_alphanumeric()))
+            .max()
+    }
+}
+
+// split a word at its lower to upper case and letter to digit transitions,
+// an uppercase letter followed by a lowercase one starts a word (e.g. `HTTPServer`)
+fn sub_words(word: &str) -> Vec<&str> {
+    let chars: Vec<_> = word.char_indices().collect();
+    let mut words = Vec::new();
+    let mut start = 0;
+
+    for (n, &(i, c)) in chars.iter().enumerate().skip(1) {
+        let (_, prev) = chars[n - 1];
+        let next = chars.get(n + 1).map(|&(_, c)| c);
+
+        let is_boundary = (prev.is_lowercase() && c.is_uppercase())
+            || (prev.is_alphabetic() && c.is_numeric())
+            || (prev.is_numeric() && c.is_alphabetic())
+            || (prev.is_uppercase() && c.is_uppercase() && next.map_or(false, char::is_lowercase));
+
+        if is_boundary {
+            words.push(&word[start..i]);
+            start = i;
+        }
+    }
+
+    words.push(&word[start..]);
+    words
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -100,23 +207,36 @@ pub struct Token<'a> {
 
 pub struct Tokenizer<'a> {
     inner: &'a str,
+    options: TokenizerOptions,
     word_index: usize,
     char_index: usize,
+    // a word to keep together can only start after a separator
+    word_start: bool,
+    pending: VecDeque<Token<'a>>,
 }
 
 impl<'a> Tokenizer<'a> {
     pub fn new(string: &str) -> Tokenizer {
+        Tokenizer::with_options(string, TokenizerOptions::default())
+    }
+
+    pub fn with_options(string: &str, options: TokenizerOptions) -> Tokenizer {
         // skip every separator and set `char_index`
         // to the number of char trimmed
         let (count, index) = string
             .char_indices()
-            .take_while(|(_, c)| is_separator(*c))
+            .take_while(|(i, c)| {
+                options.is_separator(*c) && options.keep_together_len(&string[*i..]).is_none()
+            })
             .fold((0, 0), chars_count_index);
 
         Tokenizer {
             inner: &string[index..],
+            options,
             word_index: 0,
             char_index: count,
+            word_start: true,
+            pending: VecDeque::new(),
         }
     }
 }
@@ -125,36 +245,110 @@ impl<'a> Iterator for Tokenizer<'a> {
     type Item = Token<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let mut iter = self.inner.linear_group_by(same_group_category).peekable();
+        if let Some(token) = self.pending.pop_front() {
+            return Some(token);
+        }
 
-        while let (Some(string), next_string) = (iter.next(), iter.peek()) {
-            let (count, index) = string.char_indices().fold((0, 0), chars_count_index);
+        let options = &self.options;
 
-            if !is_str_word(string) {
-                self.word_index += string
-                    .chars()
-                    .filter_map(classify_separator)
-                    .fold(Soft, |a, x| a.merge(x))
-                    .to_usize();
-                self.char_index += count;
-                self.inner = &self.inner[index..];
-                continue;
+        loop {
+            if self.word_start {
+                if let Some(len) = options.keep_together_len(self.inner) {
+                    let (word, inner) = self.inner.split_at(len);
+                    let token = Token {
+                        word,
+                        word_index: self.word_index,
+                        char_index: self.char_index,
+                    };
+
+                    if inner.chars().next().map_or(false, |c| !options.is_separator(c)) {
+                        self.word_index += 1;
+                    }
+
+                    self.word_start = false;
+                    self.char_index += word.chars().count();
+                    self.inner = inner;
+
+                    return Some(token);
+                }
             }
 
-            let token = Token {
-                word: string,
-                word_index: self.word_index,
-                char_index: self.char_index,
+            let mut iter = self
+                .inner
+                .linear_group_by(|a, b| options.same_group_category(a, b));
+
+            let (string, next_string) = match (iter.next(), iter.next()) {
+                (Some(string), next_string) => (string, next_string),
+                (None, _) => break,
             };
 
-            if next_string.filter(|s| is_str_word(s)).is_some() {
+            if !options.is_str_word(string) {
+                // a word to keep together can start inside of the separators (e.g. `.net`)
+                let end = string
+                    .char_indices()
+                    .skip(1)
+                    .map(|(i, _)| i)
+                    .find(|&i| options.keep_together_len(&self.inner[i..]).is_some())
+                    .unwrap_or_else(|| string.len());
+
+                let separators = &string[..end];
+                self.word_index += options.separators_weight(separators);
+                self.char_index += separators.chars().count();
+                self.inner = &self.inner[end..];
+                self.word_start = true;
+                continue;
+            }
+
+            let (count, index) = string.char_indices().fold((0, 0), chars_count_index);
+
+            if options.cjk_segmentation && string.chars().all(is_cjk) {
+                // a run of CJK chars must be split into multiple words
+                let mut char_index = self.char_index;
+                for (i, word) in segment(string).into_iter().enumerate() {
+                    self.pending.push_back(Token {
+                        word,
+                        word_index: self.word_index + i,
+                        char_index,
+                    });
+                    char_index += word.chars().count();
+                }
+                self.word_index += self.pending.len() - 1;
+            } else {
+                self.pending.push_back(Token {
+                    word: string,
+                    word_index: self.word_index,
+                    char_index: self.char_index,
+                });
+
+                // the sub words are at the position of the whole word,
+                // the positions of the following words are not shifted
+                let sub_words = if options.split_sub_words {
+                    sub_words(string)
+                } else {
+                    Vec::new()
+                };
+                if sub_words.len() > 1 {
+                    let mut char_index = self.char_index;
+                    for word in sub_words {
+                        self.pending.push_back(Token {
+                            word,
+                            word_index: self.word_index,
+                            char_index,
+                        });
+                        char_index += word.chars().count();
+                    }
+                }
+            }
+
+            if next_string.filter(|s| options.is_str_word(s)).is_some() {
                 self.word_index += 1;
             }
 
+            self.word_start = false;
             self.char_index += count;
             self.inner = &self.inner[index..];
 
-            return Some(token);
+            return self.pending.pop_front();
         }
 
         self.inner = "";
@@ -167,6 +361,7 @@ where
     I: Iterator<Item = &'a str>,
 {
     inner: I,
+    options: TokenizerOptions,
     current: Option<Peekable<Tokenizer<'a>>>,
     word_offset: usize,
     char_offset: usize,
@@ -176,10 +371,17 @@ impl<'a, I> SeqTokenizer<'a, I>
 where
     I: Iterator<Item = &'a str>,
 {
-    pub fn new(mut iter: I) -> SeqTokenizer<'a, I> {
-        let current = iter.next().map(|s| Tokenizer::new(s).peekable());
+    pub fn new(iter: I) -> SeqTokenizer<'a, I> {
+        SeqTokenizer::with_options(iter, TokenizerOptions::default())
+    }
+
+    pub fn with_options(mut iter: I, options: TokenizerOptions) -> SeqTokenizer<'a, I> {
+        let current = iter
+            .next()
+            .map(|s| Tokenizer::with_options(s, options.clone()).peekable());
         SeqTokenizer {
             inner: iter,
+            options,
             current,
             word_offset: 0,
             char_offset: 0,
@@ -219,7 +421,11 @@ where
                     None => {
                         // no more words in this text we must
                         // start tokenizing the next text
-                        self.current = self.inner.next().map(|s| Tokenizer::new(s).peekable());
+                        let options = &self.options;
+                        self.current = self
+                            .inner
+                            .next()
+                            .map(|s| Tokenizer::with_options(s, options.clone()).peekable());
                         self.next()
                     }
                 }
@@ -501,4 +707,259 @@ mod tests {
         );
         assert_eq!(tokenizer.next(), None);
     }
+
+    #[test]
+    #[cfg(feature = "chinese-dictionary")]
+    fn cjk_segmentation() {
+        let options = TokenizerOptions {
+            cjk_segmentation: true,
+            ..TokenizerOptions::default()
+        };
+        let mut tokenizer = Tokenizer::with_options("我们在北京大学学习 hello 上海", options);
+
+        assert_eq!(
+            tokenizer.next(),
+            Some(Token {
+                word: "我们",
+                word_index: 0,
+                char_index: 0
+            })
+        );
+        assert_eq!(
+            tokenizer.next(),
+            Some(Token {
+                word: "在",
+                word_index: 1,
+                char_index: 2
+            })
+        );
+        assert_eq!(
+            tokenizer.next(),
+            Some(Token {
+                word: "北京大学",
+                word_index: 2,
+                char_index: 3
+            })
+        );
+        assert_eq!(
+            tokenizer.next(),
+            Some(Token {
+                word: "学习",
+                word_index: 3,
+                char_index: 7
+            })
+        );
+        assert_eq!(
+            tokenizer.next(),
+            Some(Token {
+                word: "hello",
+                word_index: 4,
+                char_index: 10
+            })
+        );
+        assert_eq!(
+            tokenizer.next(),
+            Some(Token {
+                word: "上海",
+                word_index: 5,
+                char_index: 16
+            })
+        );
+        assert_eq!(tokenizer.next(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "chinese-dictionary")]
+    fn cjk_segmentation_in_sequence() {
+        let options = TokenizerOptions {
+            cjk_segmentation: true,
+            ..TokenizerOptions::default()
+        };
+        let texts = vec!["我们在北京", "学习"];
+        let mut tokenizer = SeqTokenizer::with_options(texts.into_iter(), options);
+
+        assert_eq!(
+            tokenizer.next(),
+            Some(Token {
+                word: "我们",
+                word_index: 0,
+                char_index: 0
+            })
+        );
+        assert_eq!(
+            tokenizer.next(),
+            Some(Token {
+                word: "在",
+                word_index: 1,
+                char_index: 2
+            })
+        );
+        assert_eq!(
+            tokenizer.next(),
+            Some(Token {
+                word: "北京",
+                word_index: 2,
+                char_index: 3
+            })
+        );
+        assert_eq!(
+            tokenizer.next(),
+            Some(Token {
+                word: "学习",
+                word_index: 10,
+                char_index: 11
+            })
+        );
+        assert_eq!(tokenizer.next(), None);
+    }
+
+    #[test]
+    fn custom_separators() {
+        let options = TokenizerOptions {
+            separators: vec!['+'],
+            non_separators: vec!['-'],
+            ..TokenizerOptions::default()
+        };
+        let words: Vec<_> = split_query_string_with_options("black+white t-shirt", options).collect();
+
+        assert_eq!(words, vec!["black", "white", "t-shirt"]);
+    }
+
+    #[test]
+    fn keep_together() {
+        let options = TokenizerOptions {
+            keep_emails: true,
+            keep_urls: true,
+            keep_versions: true,
+            keep_together: vec!["c++".to_string(), ".net".to_string()],
+            ..TokenizerOptions::default()
+        };
+        let text = "C++ and .NET 3.2.1, mail john.doe@example.com or see https://example.com/docs.";
+        let mut tokenizer = Tokenizer::with_options(text, options);
+
+        assert_eq!(
+            tokenizer.next(),
+            Some(Token {
+                word: "C++",
+                word_index: 0,
+                char_index: 0
+            })
+        );
+        assert_eq!(
+            tokenizer.next(),
+            Some(Token {
+                word: "and",
+                word_index: 1,
+                char_index: 4
+            })
+        );
+        assert_eq!(
+            tokenizer.next(),
+            Some(Token {
+                word: ".NET",
+                word_index: 2,
+                char_index: 8
+            })
+        );
+        assert_eq!(
+            tokenizer.next(),
+            Some(Token {
+                word: "3.2.1",
+                word_index: 3,
+                char_index: 13
+            })
+        );
+        assert_eq!(
+            tokenizer.next(),
+            Some(Token {
+                word: "mail",
+                word_index: 11,
+                char_index: 20
+            })
+        );
+        assert_eq!(
+            tokenizer.next(),
+            Some(Token {
+                word: "john.doe@example.com",
+                word_index: 12,
+                char_index: 25
+            })
+        );
+        assert_eq!(
+            tokenizer.next(),
+            Some(Token {
+                word: "or",
+                word_index: 13,
+                char_index: 46
+            })
+        );
+        assert_eq!(
+            tokenizer.next(),
+            Some(Token {
+                word: "see",
+                word_index: 14,
+                char_index: 49
+            })
+        );
+        assert_eq!(
+            tokenizer.next(),
+            Some(Token {
+                word: "https://example.com/docs",
+                word_index: 15,
+                char_index: 53
+            })
+        );
+        assert_eq!(tokenizer.next(), None);
+
+        let words: Vec<_> = split_query_string("3.2.1 c++").collect();
+        assert_eq!(words, vec!["3", "2", "1", "c++"]);
+    }
+
+    #[test]
+    fn split_sub_words() {
+        assert_eq!(sub_words("getUserById"), vec!["get", "User", "By", "Id"]);
+        assert_eq!(sub_words("iphone11pro"), vec!["iphone", "11", "pro"]);
+        assert_eq!(sub_words("HTTPServer"), vec!["HTTP", "Server"]);
+        assert_eq!(sub_words("hello"), vec!["hello"]);
+
+        let options = TokenizerOptions {
+            split_sub_words: true,
+            ..TokenizerOptions::default()
+        };
+        let mut tokenizer = Tokenizer::with_options("iphone11 case", options);
+
+        assert_eq!(
+            tokenizer.next(),
+            Some(Token {
+                word: "iphone11",
+                word_index: 0,
+                char_index: 0
+            })
+        );
+        assert_eq!(
+            tokenizer.next(),
+            Some(Token {
+                word: "iphone",
+                word_index: 0,
+                char_index: 0
+            })
+        );
+        assert_eq!(
+            tokenizer.next(),
+            Some(Token {
+                word: "11",
+                word_index: 0,
+                char_index: 6
+            })
+        );
+        assert_eq!(
+            tokenizer.next(),
+            Some(Token {
+                word: "case",
+                word_index: 1,
+                char_index: 9
+            })
+        );
+        assert_eq!(tokenizer.next(), None);
+    }
 }
@@ -0,0 +1,142 @@
+//! Recognizes, at the start of a text, the tokens that must not be split
+//! by the tokenizer. Every function returns the length in bytes of the match.
+
+const URL_TRAILING_PUNCTUATION: &[char] = &['.', ',', ';', ':', '!', '?', ')', '\'', '"'];
+
+fn prefix_len<F: Fn(char) -> bool>(text: &str, f: F) -> usize {
+    text.char_indices()
+        .find(|(_, c)| !f(*c))
+        .map_or(text.len(), |(i, _)| i)
+}
+
+fn strip_prefix_ignore_case<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = text.get(..prefix.len())?;
+    if head.eq_ignore_ascii_case(prefix) {
+        Some(&text[prefix.len()..])
+    } else {
+        None
+    }
+}
+
+/// Matches an email address like `john.doe@example.com`.
+pub fn email_len(text: &str) -> Option<usize> {
+    let is_local_char = |c: char| c.is_alphanumeric() || "._%+-".contains(c);
+    let local_len = prefix_len(text, is_local_char);
+    if local_len == 0 || !text[local_len..].starts_with('@') {
+        return None;
+    }
+
+    let domain = &text[local_len + 1..];
+    let domain_len = domain_len(domain)?;
+
+    Some(local_len + 1 + domain_len)
+}
+
+// a domain is made of at least two labels separated by dots
+fn domain_len(text: &str) -> Option<usize> {
+    let is_label_char = |c: char| c.is_alphanumeric() || c == '-';
+
+    let mut len = 0;
+    let mut labels = 0;
+    loop {
+        let label_len = prefix_len(&text[len..], is_label_char);
+        if label_len == 0 {
+            break;
+        }
+
+        len += label_len;
+        labels += 1;
+
+        let rest = &text[len..];
+        if rest.starts_with('.') && rest[1..].starts_with(is_label_char) {
+            len += 1;
+        } else {
+            break;
+        }
+    }
+
+    if labels >= 2 {
+        Some(len)
+    } else {
+        None
+    }
+}
+
+/// Matches an url starting with a scheme (e.g. `https://`) or `www.`,
+/// the trailing punctuation is not considered part of the url.
+pub fn url_len(text: &str) -> Option<usize> {
+    let rest = strip_prefix_ignore_case(text, "http://")
+        .or_else(|| strip_prefix_ignore_case(text, "https://"))
+        .or_else(|| strip_prefix_ignore_case(text, "www."))?;
+
+    let start = text.len() - rest.len();
+    let is_url_char = |c: char| !c.is_whitespace() && !"<>\"()[]{}".contains(c);
+    let len = prefix_len(rest, is_url_char);
+    let url = rest[..len].trim_end_matches(URL_TRAILING_PUNCTUATION);
+
+    if url.is_empty() {
+        None
+    } else {
+        Some(start + url.len())
+    }
+}
+
+/// Matches a version number like `3.2.1` or `v1.0`,
+/// it must be made of at least two numbers separated by dots.
+pub fn version_len(text: &str) -> Option<usize> {
+    let start = if text.starts_with('v') || text.starts_with('V') { 1 } else { 0 };
+
+    let mut len = start;
+    let mut numbers = 0;
+    loop {
+        let digits_len = prefix_len(&text[len..], |c| c.is_ascii_digit());
+        if digits_len == 0 {
+            break;
+        }
+
+        len += digits_len;
+        numbers += 1;
+
+        let rest = &text[len..];
+        if rest.starts_with('.') && rest[1..].starts_with(|c: char| c.is_ascii_digit()) {
+            len += 1;
+        } else {
+            break;
+        }
+    }
+
+    if numbers >= 2 {
+        Some(len)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn emails() {
+        assert_eq!(email_len("john.doe@example.com is"), Some(20));
+        assert_eq!(email_len("john@example.com."), Some(16));
+        assert_eq!(email_len("john@localhost"), None);
+        assert_eq!(email_len("@example.com"), None);
+    }
+
+    #[test]
+    fn urls() {
+        assert_eq!(url_len("https://example.com/a?b=c, next"), Some(25));
+        assert_eq!(url_len("www.example.com."), Some(15));
+        assert_eq!(url_len("example.com"), None);
+        assert_eq!(url_len("https:// "), None);
+    }
+
+    #[test]
+    fn versions() {
+        assert_eq!(version_len("3.2.1-beta"), Some(5));
+        assert_eq!(version_len("v1.0 is out"), Some(4));
+        assert_eq!(version_len("3."), None);
+        assert_eq!(version_len("v3"), None);
+    }
+}
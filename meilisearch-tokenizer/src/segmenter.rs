@@ -0,0 +1,134 @@
+#[cfg(feature = "chinese-dictionary")]
+use std::collections::HashMap;
+#[cfg(feature = "chinese-dictionary")]
+use std::iter::once;
+
+#[cfg(feature = "chinese-dictionary")]
+use once_cell::sync::Lazy;
+
+// the dictionary of the Jieba project, it is only bundled
+// with the `chinese-dictionary` feature as it weighs 5MB
+#[cfg(feature = "chinese-dictionary")]
+static DICTIONARY: Lazy<Dictionary> =
+    Lazy::new(|| Dictionary::from_text(include_str!("../dictionaries/zh.txt")));
+
+#[cfg(feature = "chinese-dictionary")]
+struct Dictionary {
+    // the log probability of each word of the dictionary
+    words: HashMap<&'static str, f64>,
+    // the log probability given to characters not in the dictionary
+    unknown: f64,
+    // the length of the longest word, in chars
+    max_word_len: usize,
+}
+
+#[cfg(feature = "chinese-dictionary")]
+impl Dictionary {
+    fn from_text(text: &'static str) -> Dictionary {
+        let mut frequencies = HashMap::new();
+
+        let lines = text.lines().map(str::trim);
+        for line in lines.filter(|l| !l.is_empty() && !l.starts_with('#')) {
+            let mut iter = line.split_whitespace();
+            let (word, freq) = match (iter.next(), iter.next().and_then(|f| f.parse().ok())) {
+                (Some(word), Some(freq)) => (word, freq),
+                _ => continue,
+            };
+
+            let entry = frequencies.entry(word).or_insert(0u64);
+            *entry = (*entry).max(freq);
+        }
+
+        let total = frequencies.values().sum::<u64>().max(1) as f64;
+        let max_word_len = frequencies.keys().map(|w| w.chars().count()).max().unwrap_or(1);
+        let words = frequencies
+            .into_iter()
+            .map(|(word, freq)| (word, (freq as f64 / total).ln()))
+            .collect();
+
+        Dictionary {
+            words,
+            unknown: (1.0 / total).ln(),
+            max_word_len,
+        }
+    }
+}
+
+/// Splits a run of CJK characters into the most probable sequence of words
+/// according to the bundled Chinese dictionary, unknown characters are kept alone.
+#[cfg(feature = "chinese-dictionary")]
+pub fn segment(text: &str) -> Vec<&str> {
+    let dictionary = &*DICTIONARY;
+
+    let indices: Vec<_> = text
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain(once(text.len()))
+        .collect();
+    let len = indices.len() - 1;
+
+    // the score of the best segmentation of the first `i` chars
+    // along with the char position where its last word starts
+    let mut best = vec![(f64::NEG_INFINITY, 0); len + 1];
+    best[0] = (0.0, 0);
+
+    for end in 1..=len {
+        for start in end.saturating_sub(dictionary.max_word_len)..end {
+            let word = &text[indices[start]..indices[end]];
+            let prob = match dictionary.words.get(word) {
+                Some(prob) => *prob,
+                None if end - start == 1 => dictionary.unknown,
+                None => continue,
+            };
+
+            let score = best[start].0 + prob;
+            if score > best[end].0 {
+                best[end] = (score, start);
+            }
+        }
+    }
+
+    let mut words = Vec::new();
+    let mut end = len;
+    while end > 0 {
+        let start = best[end].1;
+        words.push(&text[indices[start]..indices[end]]);
+        end = start;
+    }
+
+    words.reverse();
+    words
+}
+
+/// Splits a run of CJK characters into single characters,
+/// no dictionary is bundled without the `chinese-dictionary` feature.
+#[cfg(not(feature = "chinese-dictionary"))]
+pub fn segment(text: &str) -> Vec<&str> {
+    text.char_indices()
+        .map(|(i, c)| &text[i..i + c.len_utf8()])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "chinese-dictionary")]
+    fn chinese() {
+        assert_eq!(segment("中华人民共和国"), vec!["中华人民共和国"]);
+        assert_eq!(segment("我们在北京大学学习"), vec!["我们", "在", "北京大学", "学习"]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "chinese-dictionary"))]
+    fn without_dictionary() {
+        assert_eq!(segment("北京大学"), vec!["北", "京", "大", "学"]);
+    }
+
+    #[test]
+    fn unknown_chars() {
+        assert_eq!(segment("\u{2ec4}\u{2ed3}"), vec!["\u{2ec4}", "\u{2ed3}"]);
+        assert!(segment("").is_empty());
+    }
+}
@@ -62,4 +62,28 @@ pub struct Highlight {
     /// It informs on the original word area in the text indexed
     /// without needing to run the tokenizer again.
     pub char_length: u16,
+
+    /// The index of the query word that matched the found word
+    /// in the query words returned along with the highlights.
+    pub query_index: u16,
+
+    /// The way the query word matched the found word.
+    pub kind: MatchKind,
+}
+
+/// The way a query word matched a word of a document.
+///
+/// The order of the variants is important, the best kind of match comes first.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MatchKind {
+    /// The found word is the query word.
+    Exact,
+    /// The found word starts with the query word.
+    Prefix,
+    /// The found word is the query word with the given number of typos.
+    Typo(u8),
+    /// The found word sounds like the query word.
+    Phonetic,
+    /// The found word comes from a synonym of the query word.
+    Synonym,
 }